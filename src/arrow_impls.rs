@@ -0,0 +1,186 @@
+use crate::{
+    Date, DateResolution, Minutes, Month, Quarter, Rounding, TimeRange, TimeResolution, Year,
+};
+use arrow::{
+    array::{self, Array},
+    datatypes, error, record_batch,
+};
+use std::{collections, sync};
+
+// Resolutions which can be stored in an Arrow column, using the start of
+// each period. Date resolutions are stored as `Date32` and sub-date
+// resolutions as `Timestamp(Second)`.
+pub trait ArrowResolution: TimeResolution {
+    fn arrow_data_type() -> datatypes::DataType;
+    fn to_arrow_array(periods: &[Self]) -> array::ArrayRef;
+    // errors if the array is of the wrong type or if any value
+    // is not on a period boundary
+    fn from_arrow_array(array: &dyn Array) -> crate::Result<Vec<Option<Self>>>;
+}
+
+fn wrong_type(expected: &datatypes::DataType, array: &dyn Array) -> crate::Error {
+    error::ArrowError::CastError(format!(
+        "Expected an array of {:?} but got {:?}",
+        expected,
+        array.data_type()
+    ))
+    .into()
+}
+
+fn to_date32<P: DateResolution>(periods: &[P]) -> array::ArrayRef {
+    let days = periods
+        .iter()
+        .map(|p| Date::from(p.start()).to_unix_days())
+        .collect::<Vec<i32>>();
+    sync::Arc::new(array::Date32Array::from(days))
+}
+
+fn from_date32<P: DateResolution>(array: &dyn Array) -> crate::Result<Vec<Option<P>>> {
+    let dates = array
+        .as_any()
+        .downcast_ref::<array::Date32Array>()
+        .ok_or_else(|| wrong_type(&datatypes::DataType::Date32, array))?;
+    (0..dates.len())
+        .map(|i| {
+            if dates.is_null(i) {
+                return Ok(None);
+            }
+            let date = Date::from_unix_days(dates.value(i))?;
+            let midnight = date.start().and_hms_opt(0, 0, 0).expect("Midnight is always valid");
+            P::from_datetime_with(midnight, Rounding::Strict).map(Some)
+        })
+        .collect()
+}
+
+macro_rules! impl_date_resolution {
+    ($($ty:ty),*) => {
+        $(
+            impl ArrowResolution for $ty {
+                fn arrow_data_type() -> datatypes::DataType {
+                    datatypes::DataType::Date32
+                }
+                fn to_arrow_array(periods: &[Self]) -> array::ArrayRef {
+                    to_date32(periods)
+                }
+                fn from_arrow_array(array: &dyn Array) -> crate::Result<Vec<Option<Self>>> {
+                    from_date32(array)
+                }
+            }
+        )*
+    };
+}
+
+impl_date_resolution!(Date, Month, Quarter, Year);
+
+impl<const N: u32> ArrowResolution for Minutes<N> {
+    fn arrow_data_type() -> datatypes::DataType {
+        datatypes::DataType::Timestamp(datatypes::TimeUnit::Second, None)
+    }
+    fn to_arrow_array(periods: &[Self]) -> array::ArrayRef {
+        let secs = periods
+            .iter()
+            .map(|p| p.start_timestamp())
+            .collect();
+        sync::Arc::new(array::TimestampSecondArray::from_vec(secs, None))
+    }
+    fn from_arrow_array(array: &dyn Array) -> crate::Result<Vec<Option<Self>>> {
+        let secs = array
+            .as_any()
+            .downcast_ref::<array::TimestampSecondArray>()
+            .ok_or_else(|| wrong_type(&Self::arrow_data_type(), array))?;
+        (0..secs.len())
+            .map(|i| {
+                if secs.is_null(i) {
+                    return Ok(None);
+                }
+                let dt = chrono::NaiveDateTime::from_timestamp_opt(secs.value(i), 0).ok_or(
+                    crate::Error::InvalidMonotonic {
+                        ty_name: "Minutes",
+                        index: secs.value(i),
+                    },
+                )?;
+                Minutes::from_datetime_with(dt, Rounding::Strict).map(Some)
+            })
+            .collect()
+    }
+}
+
+impl<P: ArrowResolution> TimeRange<P> {
+    pub fn to_arrow_array(&self) -> array::ArrayRef {
+        P::to_arrow_array(&self.iter().collect::<Vec<P>>())
+    }
+}
+
+// A two column batch of `period` and `value_name`, in period order
+pub fn to_record_batch<P: ArrowResolution>(
+    values: &collections::BTreeMap<P, f64>,
+    value_name: &str,
+) -> crate::Result<record_batch::RecordBatch> {
+    let schema = datatypes::Schema::new(vec![
+        datatypes::Field::new("period", P::arrow_data_type(), false),
+        datatypes::Field::new(value_name, datatypes::DataType::Float64, false),
+    ]);
+    let periods = values.keys().copied().collect::<Vec<P>>();
+    let columns = vec![
+        P::to_arrow_array(&periods),
+        sync::Arc::new(array::Float64Array::from(
+            values.values().copied().collect::<Vec<f64>>(),
+        )) as array::ArrayRef,
+    ];
+    Ok(record_batch::RecordBatch::try_new(
+        sync::Arc::new(schema),
+        columns,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_record_batch, ArrowResolution};
+    use crate::{Date, HalfHour, Month, TimeRange, TimeResolution};
+    use arrow::array;
+
+    #[test]
+    fn test_date_roundtrip() {
+        let days = TimeRange::new(Date::from_ymd(2021, 7, 15).unwrap(), 3);
+        let array = days.to_arrow_array();
+        let dates = array.as_any().downcast_ref::<array::Date32Array>().unwrap();
+        assert_eq!(dates.value(0), 18_823);
+        let parsed = Date::from_arrow_array(array.as_ref()).unwrap();
+        assert_eq!(parsed, days.iter().map(Some).collect::<Vec<_>>());
+        // mid-month dates are not on a `Month` boundary
+        assert!(Month::from_arrow_array(array.as_ref()).is_err());
+        assert!(HalfHour::from_arrow_array(array.as_ref()).is_err());
+    }
+
+    #[test]
+    fn test_minutes_roundtrip() {
+        let start = "2021-07-15 10:30".parse::<HalfHour>().unwrap();
+        let periods = vec![start, start.succ()];
+        let array = HalfHour::to_arrow_array(&periods);
+        let secs = array
+            .as_any()
+            .downcast_ref::<array::TimestampSecondArray>()
+            .unwrap();
+        assert_eq!(secs.value(0), 1_626_345_000);
+        let parsed = HalfHour::from_arrow_array(array.as_ref()).unwrap();
+        assert_eq!(parsed, vec![Some(start), Some(start.succ())]);
+        let nulls = array::TimestampSecondArray::from_opt_vec(vec![None, Some(60)], None);
+        assert!(HalfHour::from_arrow_array(&nulls).is_err());
+        let nulls = array::TimestampSecondArray::from_opt_vec(vec![None, Some(1800)], None);
+        assert_eq!(
+            HalfHour::from_arrow_array(&nulls).unwrap(),
+            vec![None, Some(HalfHour::from_monotonic(1))]
+        );
+    }
+
+    #[test]
+    fn test_record_batch() {
+        let jan = Month::from_year_month(2021, 1).unwrap();
+        let values = vec![(jan.succ(), 2.0), (jan, 1.0)].into_iter().collect();
+        let batch = to_record_batch(&values, "price").unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema().field(1).name(), "price");
+        let periods = Month::from_arrow_array(batch.column(0).as_ref()).unwrap();
+        assert_eq!(periods, vec![Some(jan), Some(jan.succ())]);
+    }
+}
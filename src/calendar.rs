@@ -0,0 +1,338 @@
+use crate::{Date, DateResolution, TimeRange, TimeRangeIter, TimeResolution};
+use std::{collections, fmt, str};
+
+// A calendar decides which days are business days. Implement
+// this for custom calendars (eg: backed by a holiday database)
+pub trait HolidayCalendar {
+    fn is_weekend(&self, date: Date) -> bool;
+    fn is_holiday(&self, date: Date) -> bool;
+    fn is_business_day(&self, date: Date) -> bool {
+        !self.is_weekend(date) && !self.is_holiday(date)
+    }
+}
+
+// The days of the week which are considered to be the weekend
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeekendDef {
+    days: collections::HashSet<chrono::Weekday>,
+}
+
+impl WeekendDef {
+    pub fn new(days: collections::HashSet<chrono::Weekday>) -> WeekendDef {
+        WeekendDef { days }
+    }
+    pub fn saturday_sunday() -> WeekendDef {
+        WeekendDef::new(
+            vec![chrono::Weekday::Sat, chrono::Weekday::Sun]
+                .into_iter()
+                .collect(),
+        )
+    }
+    pub fn contains(&self, day: chrono::Weekday) -> bool {
+        self.days.contains(&day)
+    }
+}
+
+impl Default for WeekendDef {
+    fn default() -> WeekendDef {
+        WeekendDef::saturday_sunday()
+    }
+}
+
+// The day a week starts on, for when this is only known at runtime
+// (eg: read from a config file)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WeekStartDay {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+impl WeekStartDay {
+    pub const ALL: [WeekStartDay; 7] = [
+        WeekStartDay::Mon,
+        WeekStartDay::Tue,
+        WeekStartDay::Wed,
+        WeekStartDay::Thu,
+        WeekStartDay::Fri,
+        WeekStartDay::Sat,
+        WeekStartDay::Sun,
+    ];
+    pub fn weekday(&self) -> chrono::Weekday {
+        chrono::Weekday::from(*self)
+    }
+}
+
+impl From<WeekStartDay> for chrono::Weekday {
+    fn from(day: WeekStartDay) -> chrono::Weekday {
+        match day {
+            WeekStartDay::Mon => chrono::Weekday::Mon,
+            WeekStartDay::Tue => chrono::Weekday::Tue,
+            WeekStartDay::Wed => chrono::Weekday::Wed,
+            WeekStartDay::Thu => chrono::Weekday::Thu,
+            WeekStartDay::Fri => chrono::Weekday::Fri,
+            WeekStartDay::Sat => chrono::Weekday::Sat,
+            WeekStartDay::Sun => chrono::Weekday::Sun,
+        }
+    }
+}
+
+impl From<chrono::Weekday> for WeekStartDay {
+    fn from(day: chrono::Weekday) -> WeekStartDay {
+        match day {
+            chrono::Weekday::Mon => WeekStartDay::Mon,
+            chrono::Weekday::Tue => WeekStartDay::Tue,
+            chrono::Weekday::Wed => WeekStartDay::Wed,
+            chrono::Weekday::Thu => WeekStartDay::Thu,
+            chrono::Weekday::Fri => WeekStartDay::Fri,
+            chrono::Weekday::Sat => WeekStartDay::Sat,
+            chrono::Weekday::Sun => WeekStartDay::Sun,
+        }
+    }
+}
+
+impl fmt::Display for WeekStartDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.weekday())
+    }
+}
+
+// Accepts short or full day names, in any case (eg: "mon", "Monday")
+impl str::FromStr for WeekStartDay {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<chrono::Weekday>()
+            .map(WeekStartDay::from)
+            .map_err(|_| crate::Error::ParseCustom {
+                ty_name: "WeekStartDay",
+                input: s.to_string(),
+            })
+    }
+}
+
+// The simplest useful calendar, a set of weekend days
+// and a set of (non-weekend) holidays
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimpleCalendar {
+    weekend: WeekendDef,
+    holidays: collections::BTreeSet<chrono::NaiveDate>,
+}
+
+impl SimpleCalendar {
+    pub fn new(
+        weekend: WeekendDef,
+        holidays: collections::BTreeSet<chrono::NaiveDate>,
+    ) -> SimpleCalendar {
+        SimpleCalendar { weekend, holidays }
+    }
+}
+
+impl HolidayCalendar for SimpleCalendar {
+    fn is_weekend(&self, date: Date) -> bool {
+        date.is_weekend(&self.weekend)
+    }
+    fn is_holiday(&self, date: Date) -> bool {
+        self.holidays.contains(&date.start())
+    }
+}
+
+// The number of business days in `(a, b]`, negated if `b` is before `a`.
+// This is the inverse of `Date::add_business_days` when `b` is a business day.
+pub fn business_days_between<C: HolidayCalendar>(a: Date, b: Date, calendar: &C) -> i64 {
+    let (from, to, sign) = if a <= b { (a, b, 1) } else { (b, a, -1) };
+    let count = (1..=from.between(to))
+        .map(|i| Date::from_monotonic(from.to_monotonic() + i))
+        .filter(|d| calendar.is_business_day(*d))
+        .count();
+    sign * count as i64
+}
+
+pub struct BusinessDays<'a, C: HolidayCalendar> {
+    days: TimeRangeIter<Date>,
+    calendar: &'a C,
+}
+
+impl<'a, C: HolidayCalendar> BusinessDays<'a, C> {
+    pub(crate) fn new(days: TimeRangeIter<Date>, calendar: &'a C) -> BusinessDays<'a, C> {
+        BusinessDays { days, calendar }
+    }
+}
+
+impl<'a, C: HolidayCalendar> Iterator for BusinessDays<'a, C> {
+    type Item = Date;
+    fn next(&mut self) -> Option<Self::Item> {
+        let calendar = self.calendar;
+        self.days.find(|d| calendar.is_business_day(*d))
+    }
+}
+
+// A range of dates viewed through a calendar, so that lengths and positions
+// count only business days (eg: an SLA of 5 business days)
+pub struct BusinessTimeRange<'a, C: HolidayCalendar> {
+    range: TimeRange<Date>,
+    calendar: &'a C,
+}
+
+impl<'a, C: HolidayCalendar> BusinessTimeRange<'a, C> {
+    pub fn new(range: TimeRange<Date>, calendar: &'a C) -> BusinessTimeRange<'a, C> {
+        BusinessTimeRange { range, calendar }
+    }
+    // all the dates, including those which aren't business days
+    pub fn range(&self) -> TimeRange<Date> {
+        self.range
+    }
+    pub fn iter(&self) -> BusinessDays<'a, C> {
+        BusinessDays::new(self.range.iter(), self.calendar)
+    }
+    pub fn len_business_days(&self) -> usize {
+        self.iter().count()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.first_business_day().is_none()
+    }
+    // 0-based, so `nth_business_day(0)` is the first business day
+    pub fn nth_business_day(&self, n: usize) -> Option<Date> {
+        self.iter().nth(n)
+    }
+    pub fn first_business_day(&self) -> Option<Date> {
+        self.iter().next()
+    }
+    pub fn last_business_day(&self) -> Option<Date> {
+        let calendar = self.calendar;
+        self.range.iter().rev().find(|d| calendar.is_business_day(*d))
+    }
+    pub fn contains(&self, date: Date) -> bool {
+        self.range.index_of(date).is_some() && self.calendar.is_business_day(date)
+    }
+    // `None` when the ranges don't overlap, the result may still have no business days
+    pub fn intersect(&self, other: TimeRange<Date>) -> Option<BusinessTimeRange<'a, C>> {
+        if self.range.is_empty() || other.is_empty() {
+            return None;
+        }
+        let range = self.range.intersect(other)?;
+        Some(BusinessTimeRange::new(range, self.calendar))
+    }
+}
+
+impl<'a, C: HolidayCalendar> Clone for BusinessTimeRange<'a, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, C: HolidayCalendar> Copy for BusinessTimeRange<'a, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{business_days_between, BusinessTimeRange, HolidayCalendar, SimpleCalendar, WeekStartDay, WeekendDef};
+    use crate::{Date, DateResolution, Month, TimeRange, TimeResolution};
+
+    fn calendar() -> SimpleCalendar {
+        SimpleCalendar::new(
+            WeekendDef::saturday_sunday(),
+            vec![chrono::NaiveDate::from_ymd_opt(2021, 12, 27).unwrap()]
+                .into_iter()
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_is_business_day() {
+        let cal = calendar();
+        assert!(cal.is_business_day(Date::from_ymd(2021, 12, 24).unwrap()));
+        assert!(!cal.is_business_day(Date::from_ymd(2021, 12, 25).unwrap()));
+        assert!(!cal.is_business_day(Date::from_ymd(2021, 12, 27).unwrap()));
+    }
+
+    #[test]
+    fn test_business_days() {
+        let cal = calendar();
+        let dec = Month::from_date(chrono::NaiveDate::from_ymd_opt(2021, 12, 1).unwrap());
+        assert_eq!(dec.num_business_days(&cal), 22);
+        let days = dec.business_days(&cal).collect::<Vec<Date>>();
+        assert_eq!(days[0], Date::from_ymd(2021, 12, 1).unwrap());
+        assert_eq!(days[17], Date::from_ymd(2021, 12, 24).unwrap());
+        assert_eq!(days[18], Date::from_ymd(2021, 12, 28).unwrap());
+        assert_eq!(days[21], Date::from_ymd(2021, 12, 31).unwrap());
+    }
+
+    #[test]
+    fn test_business_day_arithmetic() {
+        let cal = calendar();
+        let thu = Date::from_ymd(2021, 12, 23).unwrap();
+        let fri = Date::from_ymd(2021, 12, 24).unwrap();
+        let tue = Date::from_ymd(2021, 12, 28).unwrap();
+        assert_eq!(thu.next_business_day(&cal), Some(fri));
+        assert_eq!(fri.next_business_day(&cal), Some(tue));
+        assert_eq!(tue.previous_business_day(&cal), Some(fri));
+        assert_eq!(Date::from_ymd(2021, 12, 25).unwrap().next_business_day(&cal), Some(tue));
+        assert_eq!(thu.add_business_days(2, &cal), Some(tue));
+        assert_eq!(tue.add_business_days(-2, &cal), Some(thu));
+        assert_eq!(thu.add_business_days(0, &cal), Some(thu));
+        assert_eq!(business_days_between(thu, tue, &cal), 2);
+        assert_eq!(business_days_between(tue, thu, &cal), -2);
+        assert_eq!(business_days_between(thu, thu, &cal), 0);
+        let sat = Date::from_ymd(2021, 12, 25).unwrap();
+        assert_eq!(business_days_between(fri, sat, &cal), 0);
+        for n in -10..10 {
+            assert_eq!(business_days_between(thu, thu.add_business_days(n, &cal).unwrap(), &cal), i64::from(n));
+        }
+
+        // a calendar without any business days
+        let every_day = WeekendDef::new(WeekStartDay::ALL.iter().map(|d| d.weekday()).collect());
+        let never = SimpleCalendar::new(every_day, std::collections::BTreeSet::new());
+        assert_eq!(thu.next_business_day(&never), None);
+        assert_eq!(thu.previous_business_day(&never), None);
+        assert_eq!(thu.add_business_days(3, &never), None);
+        assert_eq!(thu.add_business_days(0, &never), Some(thu));
+        assert_eq!(Date::from(chrono::NaiveDate::MAX).next_business_day(&cal), None);
+    }
+
+    #[test]
+    fn test_business_time_range() {
+        let cal = calendar();
+        let thu = Date::from_ymd(2021, 12, 23).unwrap();
+        let range = BusinessTimeRange::new(TimeRange::new(thu, 10), &cal);
+        // thu, fri, tue, wed, thu, fri
+        assert_eq!(range.len_business_days(), 6);
+        assert_eq!(range.nth_business_day(0), Some(thu));
+        assert_eq!(range.nth_business_day(2), Some(Date::from_ymd(2021, 12, 28).unwrap()));
+        assert_eq!(range.nth_business_day(5), Some(Date::from_ymd(2021, 12, 31).unwrap()));
+        assert_eq!(range.nth_business_day(6), None);
+        assert_eq!(range.first_business_day(), Some(thu));
+        assert_eq!(range.last_business_day(), Some(Date::from_ymd(2021, 12, 31).unwrap()));
+        assert!(range.contains(thu));
+        assert!(!range.contains(Date::from_ymd(2021, 12, 27).unwrap()));
+        assert!(!range.contains(thu.pred()));
+        for n in 0..6 {
+            assert_eq!(range.nth_business_day(n), thu.add_business_days(n as i32, &cal));
+        }
+
+        let weekend = range.intersect(TimeRange::new(Date::from_ymd(2021, 12, 25).unwrap(), 3)).unwrap();
+        assert_eq!(weekend.range().len(), 3);
+        assert!(weekend.is_empty());
+        assert_eq!(weekend.len_business_days(), 0);
+        assert_eq!(weekend.last_business_day(), None);
+        let overlap = range.intersect(TimeRange::new(thu.pred_n(3), 5)).unwrap();
+        assert_eq!(overlap.range(), TimeRange::new(thu, 2));
+        assert_eq!(overlap.iter().collect::<Vec<Date>>(), vec![thu, thu.succ()]);
+        assert!(range.intersect(TimeRange::new(thu.pred_n(3), 3)).is_none());
+    }
+
+    #[test]
+    fn test_week_start_day() {
+        assert_eq!("Mon".parse::<WeekStartDay>().unwrap(), WeekStartDay::Mon);
+        assert_eq!("sunday".parse::<WeekStartDay>().unwrap(), WeekStartDay::Sun);
+        assert!("Someday".parse::<WeekStartDay>().is_err());
+        for day in WeekStartDay::ALL.iter() {
+            assert_eq!(WeekStartDay::from(day.weekday()), *day);
+            assert_eq!(day.to_string().parse::<WeekStartDay>().unwrap(), *day);
+        }
+        assert_eq!(chrono::Weekday::from(WeekStartDay::Wed), chrono::Weekday::Wed);
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{RequestTracker, TimeRange, TimeResolution};
+use std::{convert::TryFrom, marker, mem};
+
+const BITS: i64 = 64;
+
+// A set of periods stored as a bitset over their monotonic indexes,
+// starting from the earliest period ever inserted. This is far smaller
+// than a `BTreeSet` when the periods are dense (eg: years of minutes).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageMap<P: TimeResolution> {
+    // the monotonic index of the first bit, always a multiple of 64
+    offset: i64,
+    words: Vec<u64>,
+    resolution: marker::PhantomData<P>,
+}
+
+impl<P: TimeResolution> CoverageMap<P> {
+    pub fn new() -> CoverageMap<P> {
+        CoverageMap {
+            offset: 0,
+            words: Vec::new(),
+            resolution: marker::PhantomData,
+        }
+    }
+    pub fn contains(&self, period: P) -> bool {
+        self.contains_index(period.to_monotonic())
+    }
+    pub fn insert(&mut self, period: P) {
+        let (word, bit) = self.position_growing(period.to_monotonic());
+        self.words[word] |= 1 << bit;
+    }
+    pub fn insert_range(&mut self, range: TimeRange<P>) {
+        if range.is_empty() {
+            return;
+        }
+        // growing to fit both ends first means the rest never reallocates
+        self.position_growing(range.start().to_monotonic());
+        self.position_growing(range.end().to_monotonic());
+        for period in range.iter() {
+            self.insert(period);
+        }
+    }
+    pub fn remove(&mut self, period: P) {
+        if let Some((word, bit)) = self.position(period.to_monotonic()) {
+            self.words[word] &= !(1 << bit);
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+    // the runs of periods within `range` which are not in the map
+    pub fn missing_ranges(&self, range: TimeRange<P>) -> Vec<TimeRange<P>> {
+        let mut missing = Vec::new();
+        let mut index = range.start().to_monotonic();
+        let end = index + i64::from(range.len);
+        while index < end {
+            let gap_start = self.next_with(index, end, false);
+            if gap_start == end {
+                break;
+            }
+            let gap_end = self.next_with(gap_start, end, true);
+            let len = u32::try_from(gap_end - gap_start).expect("Within a TimeRange");
+            missing.push(TimeRange::new(P::from_monotonic(gap_start), len));
+            index = gap_end;
+        }
+        missing
+    }
+
+    fn contains_index(&self, index: i64) -> bool {
+        self.position(index)
+            .is_some_and(|(word, bit)| self.words[word] & (1 << bit) != 0)
+    }
+
+    fn position(&self, index: i64) -> Option<(usize, u32)> {
+        let relative = index.checked_sub(self.offset)?;
+        let word = usize::try_from(relative.div_euclid(BITS)).ok()?;
+        if word < self.words.len() {
+            Some((word, relative.rem_euclid(BITS) as u32))
+        } else {
+            None
+        }
+    }
+
+    fn position_growing(&mut self, index: i64) -> (usize, u32) {
+        let aligned = index.div_euclid(BITS) * BITS;
+        if self.words.is_empty() {
+            self.offset = aligned;
+        } else if aligned < self.offset {
+            let extra = usize::try_from((self.offset - aligned) / BITS).expect("Fits in memory");
+            self.words.splice(0..0, std::iter::repeat_n(0, extra));
+            self.offset = aligned;
+        }
+        let word = usize::try_from((aligned - self.offset) / BITS).expect("Fits in memory");
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        (word, index.rem_euclid(BITS) as u32)
+    }
+
+    // the first index in `from..end` which is (or isn't) in the map, or `end` if there are none.
+    // Whole words are skipped at a time.
+    fn next_with(&self, from: i64, end: i64, present: bool) -> i64 {
+        let mut index = from;
+        while index < end {
+            if index < self.offset {
+                if !present {
+                    return index;
+                }
+                index = self.offset;
+                continue;
+            }
+            let (word, bit) = match self.position(index) {
+                Some(position) => position,
+                None => return if present { end } else { index },
+            };
+            let bits = if present { self.words[word] } else { !self.words[word] } >> bit;
+            if bits != 0 {
+                return (index + i64::from(bits.trailing_zeros())).min(end);
+            }
+            index += BITS - i64::from(bit);
+        }
+        end
+    }
+}
+
+impl<P: TimeResolution> Default for CoverageMap<P> {
+    fn default() -> CoverageMap<P> {
+        CoverageMap::new()
+    }
+}
+
+impl<P: TimeResolution> RequestTracker<P> for CoverageMap<P> {
+    fn contains(&self, key: &P) -> bool {
+        CoverageMap::contains(self, *key)
+    }
+    fn insert(&mut self, key: P) {
+        CoverageMap::insert(self, key)
+    }
+    fn approx_memory_bytes(&self) -> usize {
+        self.words.capacity() * mem::size_of::<u64>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CoverageMap;
+    use crate::{Cache, CacheResponse, Minute, TimeRange};
+
+    #[test]
+    fn test_coverage_map() {
+        let start = "2021-07-15 10:00".parse::<Minute>().unwrap();
+        let mut map = CoverageMap::new();
+        assert!(map.is_empty());
+        map.insert_range(TimeRange::new(start, 200));
+        map.insert(start.pred_n(100));
+        assert_eq!(map.len(), 201);
+        assert!(map.contains(start.succ_n(199)));
+        assert!(!map.contains(start.succ_n(200)));
+        assert!(map.contains(start.pred_n(100)));
+        assert!(!map.contains(start.pred_n(99)));
+        assert!(!map.contains(start.pred_n(10_000)));
+        map.remove(start.succ_n(150));
+        map.remove(start.succ_n(100_000));
+        assert_eq!(map.len(), 200);
+
+        let query = TimeRange::new(start.pred_n(101), 500);
+        assert_eq!(
+            map.missing_ranges(query),
+            vec![
+                TimeRange::new(start.pred_n(101), 1),
+                TimeRange::new(start.pred_n(99), 99),
+                TimeRange::new(start.succ_n(150), 1),
+                TimeRange::new(start.succ_n(200), 199),
+            ]
+        );
+        assert!(map.missing_ranges(TimeRange::new(start, 100)).is_empty());
+    }
+
+    #[test]
+    fn test_cache_with_coverage_map() {
+        let start = "2021-07-15 10:00".parse::<Minute>().unwrap();
+        let mut cache = Cache::<Minute, i32, CoverageMap<Minute>>::with_tracker();
+        let first = TimeRange::new(start, 10);
+        cache.add(first.set(), first.iter().map(|p| (p, 1)).collect());
+        assert_eq!(cache.get(first.set()), CacheResponse::Hit(first.iter().map(|p| (p, 1)).collect()));
+        let wider = TimeRange::new(start, 15);
+        assert_eq!(
+            cache.get(wider.set()),
+            CacheResponse::Miss(vec![TimeRange::new(start.succ_n(10), 5).set()])
+        );
+    }
+}
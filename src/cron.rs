@@ -0,0 +1,210 @@
+use crate::{Minutes, Recurrence, TimeRange, TimeResolution};
+use chrono::{Datelike, Timelike};
+use std::{fmt, str};
+
+const MONTHS: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAYS: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+// A standard 5-field cron expression (minute, hour, day of month, month, day of week)
+// supporting `*`, ranges, steps, lists, and month/day names. As in cron, when both
+// the day of month and day of week are restricted, a day matching either is selected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cron {
+    expr: String,
+    // bit sets of the allowed values of each field
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Cron {
+    // Checks that every firing time is the start of a `Minutes<N>` period,
+    // so that matching periods correspond exactly to when the job fires
+    pub fn check_resolution<const N: u32>(&self) -> crate::Result<()> {
+        let aligned = N != 0 && 60 % N == 0 && (0..60).all(|m| !contains(self.minutes, m) || m % N == 0);
+        if aligned {
+            Ok(())
+        } else {
+            Err(crate::Error::IncompatibleResolution {
+                resolution: format!("Minutes[Length:{}]", N),
+                input: self.expr.clone(),
+            })
+        }
+    }
+
+    // The periods within `range` that the expression fires in
+    pub fn periods_in<const N: u32>(
+        &self,
+        range: TimeRange<Minutes<N>>,
+    ) -> crate::Result<impl Iterator<Item = Minutes<N>> + '_> {
+        self.check_resolution::<N>()?;
+        Ok(range.iter().filter(move |p| self.matches(*p)))
+    }
+
+    fn matches_date_time(&self, dt: chrono::NaiveDateTime) -> bool {
+        let day = contains(self.days, dt.day());
+        let weekday = contains(self.weekdays, dt.weekday().num_days_from_sunday());
+        let day_matches = match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        };
+        day_matches
+            && contains(self.minutes, dt.minute())
+            && contains(self.hours, dt.hour())
+            && contains(self.months, dt.month())
+    }
+}
+
+// A period matches when the expression fires at its start
+impl<const N: u32> Recurrence<Minutes<N>> for Cron {
+    fn matches(&self, period: Minutes<N>) -> bool {
+        self.matches_date_time(period.naive_date_time())
+    }
+}
+
+fn contains(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+fn parse_value(s: &str, names: &[&str], offset: u32) -> Option<u32> {
+    s.parse::<u32>().ok().or_else(|| {
+        names
+            .iter()
+            .position(|n| n.eq_ignore_ascii_case(s))
+            .map(|i| i as u32 + offset)
+    })
+}
+
+// Parses one field into a bit set, returning whether it was `*`
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Option<(u64, bool)> {
+    let mut set = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|s| *s > 0)?),
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start, names, min)?, parse_value(end, names, min)?)
+        } else {
+            let value = parse_value(range, names, min)?;
+            // `5/15` means from 5 to the end of the range
+            (value, if part.contains('/') { max } else { value })
+        };
+        if start < min || end > max || start > end {
+            return None;
+        }
+        for value in (start..=end).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Some((set, field == "*"))
+}
+
+impl str::FromStr for Cron {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> crate::Result<Cron> {
+        let err = || crate::Error::ParseCustom {
+            ty_name: "Cron",
+            input: s.to_string(),
+        };
+        let fields = s.split_whitespace().collect::<Vec<_>>();
+        if fields.len() != 5 {
+            return Err(err());
+        }
+        let (minutes, _) = parse_field(fields[0], 0, 59, &[]).ok_or_else(err)?;
+        let (hours, _) = parse_field(fields[1], 0, 23, &[]).ok_or_else(err)?;
+        let (days, any_day) = parse_field(fields[2], 1, 31, &[]).ok_or_else(err)?;
+        let (months, _) = parse_field(fields[3], 1, 12, &MONTHS).ok_or_else(err)?;
+        let (mut weekdays, any_weekday) = parse_field(fields[4], 0, 7, &WEEKDAYS).ok_or_else(err)?;
+        // both 0 and 7 are Sunday
+        if contains(weekdays, 7) {
+            weekdays |= 1;
+        }
+        Ok(Cron {
+            expr: fields.join(" "),
+            minutes,
+            hours,
+            days,
+            months,
+            weekdays,
+            any_day,
+            any_weekday,
+        })
+    }
+}
+
+impl fmt::Display for Cron {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cron;
+    use crate::{FiveMinute, Minutes, Recurrence, TimeRange};
+
+    fn minute(s: &str) -> Minutes<1> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!("* * * * *".parse::<Cron>().is_ok());
+        assert!("*/15 9-17 * JAN-jun mon-FRI".parse::<Cron>().is_ok());
+        assert!("0 0 1,15 * 0".parse::<Cron>().is_ok());
+        assert!("* * * *".parse::<Cron>().is_err());
+        assert!("60 * * * *".parse::<Cron>().is_err());
+        assert!("* * 0 * *".parse::<Cron>().is_err());
+        assert!("*/0 * * * *".parse::<Cron>().is_err());
+        assert!("5-1 * * * *".parse::<Cron>().is_err());
+        assert_eq!("0  12 * * *".parse::<Cron>().unwrap().to_string(), "0 12 * * *");
+    }
+
+    #[test]
+    fn test_matches() {
+        let weekdays = "30 9 * * 1-5".parse::<Cron>().unwrap();
+        // 2021-07-16 is a Friday
+        assert!(weekdays.matches(minute("2021-07-16 09:30")));
+        assert!(!weekdays.matches(minute("2021-07-17 09:30")));
+        assert!(!weekdays.matches(minute("2021-07-16 09:31")));
+        let sundays = "0 0 * * 7".parse::<Cron>().unwrap();
+        assert!(sundays.matches(minute("2021-07-18 00:00")));
+        // either the 1st of the month or a Monday
+        let either = "0 0 1 * MON".parse::<Cron>().unwrap();
+        assert!(either.matches(minute("2021-07-01 00:00")));
+        assert!(either.matches(minute("2021-07-19 00:00")));
+        assert!(!either.matches(minute("2021-07-20 00:00")));
+        let offset = "5/20 * * * *".parse::<Cron>().unwrap();
+        assert!(offset.matches(minute("2021-07-20 00:45")));
+        assert!(!offset.matches(minute("2021-07-20 00:40")));
+    }
+
+    #[test]
+    fn test_periods_in() {
+        let cron = "*/15 10 * * *".parse::<Cron>().unwrap();
+        let start = "2021-07-15 09:00".parse::<FiveMinute>().unwrap();
+        let periods = cron
+            .periods_in(TimeRange::new(start, 12 * 26))
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(periods.len(), 8);
+        assert_eq!(periods[0], "2021-07-15 10:00".parse::<FiveMinute>().unwrap());
+        assert_eq!(periods[7], "2021-07-16 10:45".parse::<FiveMinute>().unwrap());
+        let range = TimeRange::new(Minutes::<45>::from_monotonic(0), 10);
+        assert!(cron.periods_in(range).is_err());
+        let odd = "*/3 * * * *".parse::<Cron>().unwrap();
+        assert!(odd.check_resolution::<5>().is_err());
+        assert!(odd.check_resolution::<1>().is_ok());
+    }
+}
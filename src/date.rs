@@ -1,12 +1,17 @@
-use crate::DateResolution;
-use serde::{
-    de,
-    ser::{self, SerializeStruct},
-};
-use std::{str, fmt};
+use crate::{calendar, month, DateResolution, TimeResolution};
+use chrono::Datelike;
+#[cfg(feature = "serde")]
+use serde::de;
+use std::{borrow, cmp, convert::{self, TryFrom}, fmt, str};
 
 const DATE_FORMAT: &str = "%Y-%m-%d";
+const NUM_SECS_PER_DAY: i64 = 24 * 60 * 60;
+// the year and the day of the year, eg: `2021-196`
+const ORDINAL_FORMAT: &str = "%Y-%j";
+// How far to search for a business day before giving up on the calendar having any
+const MAX_NON_BUSINESS_DAYS: u32 = 366;
 
+#[cfg(feature = "serde")]
 impl<'de> de::Deserialize<'de> for Date 
 {
     fn deserialize<D>(
@@ -22,6 +27,7 @@ impl<'de> de::Deserialize<'de> for Date
     }
 }
 
+#[cfg(feature = "serde")]
 impl serde::Serialize for Date {
     fn serialize<S>(
         &self,
@@ -39,10 +45,13 @@ impl serde::Serialize for Date {
 
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes, compare(PartialEq, PartialOrd)))]
+#[cfg_attr(feature = "rkyv", archive_attr(derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)))]
 pub struct Date(i64);
 
 fn base() -> chrono::NaiveDate {
-    chrono::NaiveDate::from_ymd(0, 1, 1)
+    chrono::NaiveDate::from_ymd_opt(0, 1, 1).expect("Base date is always valid")
 }
 
 
@@ -54,6 +63,40 @@ impl str::FromStr for Date {
     }
 }
 
+impl<'a> convert::TryFrom<&'a str> for Date {
+    type Error = crate::Error;
+    fn try_from(s: &'a str) -> crate::Result<Date> {
+        s.parse()
+    }
+}
+
+impl convert::TryFrom<i64> for Date {
+    type Error = crate::Error;
+    fn try_from(idx: i64) -> crate::Result<Date> {
+        <Date as crate::TimeResolution>::try_from_monotonic(idx)
+    }
+}
+
+
+impl PartialEq<chrono::NaiveDate> for Date {
+    fn eq(&self, other: &chrono::NaiveDate) -> bool {
+        crate::cmp_date(self, other) == cmp::Ordering::Equal
+    }
+}
+
+impl PartialOrd<chrono::NaiveDate> for Date {
+    fn partial_cmp(&self, other: &chrono::NaiveDate) -> Option<cmp::Ordering> {
+        Some(crate::cmp_date(self, other))
+    }
+}
+
+#[cfg(feature = "locale")]
+impl Date {
+    // the weekday followed by the date, in the order preferred by the locale, eg: `mer. 15/12/2021` for `fr_FR`
+    pub fn format_localized(&self, locale: chrono::Locale) -> String {
+        crate::format_localized(self.start(), "%a %x", locale)
+    }
+}
 
 impl fmt::Display for Date {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -63,35 +106,316 @@ impl fmt::Display for Date {
 
 impl crate::DateResolution for Date {
     fn start(&self) -> chrono::NaiveDate {
-        base() + chrono::Duration::days(self.0)
+        base()
+            .checked_add_signed(chrono::Duration::days(self.0))
+            .expect("Not pre/post historic")
+    }
+}
+
+// Panics when the result is outside of chrono's range, as chrono does
+impl std::ops::Add<chrono::Days> for Date {
+    type Output = Date;
+    fn add(self, days: chrono::Days) -> Date {
+        Date::from(self.start().checked_add_days(days).expect("Not pre/post historic"))
+    }
+}
+
+impl std::ops::Sub<chrono::Days> for Date {
+    type Output = Date;
+    fn sub(self, days: chrono::Days) -> Date {
+        Date::from(self.start().checked_sub_days(days).expect("Not pre/post historic"))
     }
 }
 
 impl std::convert::From<chrono::NaiveDate> for Date {
     fn from(d: chrono::NaiveDate) -> Date {
-        Date((base() - d).num_days())
+        Date((d - base()).num_days())
     }
 }
 
 impl crate::TimeResolution for Date {
+    fn name(&self) -> borrow::Cow<'static, str> {
+        borrow::Cow::Borrowed("Date")
+    }
     fn between(&self, other: Self) -> i64 {
         other.0 - self.0
     }
     fn succ_n(&self, n: u32) -> Date {
-        Date(self.0 + i64::from(n))
+        Date::succ_n(self, n)
     }
     fn pred_n(&self, n: u32) -> Date {
-        Date(self.0 - i64::from(n))
+        Date::pred_n(self, n)
     }
     fn naive_date_time(&self) -> chrono::NaiveDateTime {
-        self.start().and_hms(0, 0, 0)
+        self.start().and_hms_opt(0, 0, 0).expect("Midnight is always valid")
+    }
+    fn from_datetime(dt: chrono::NaiveDateTime) -> Self {
+        Date::from(dt.date())
+    }
+    fn from_utc_timestamp(secs: i64) -> Self {
+        Date(Date::UNIX_EPOCH.0 + secs.div_euclid(NUM_SECS_PER_DAY))
+    }
+    fn start_timestamp(&self) -> i64 {
+        (self.0 - Date::UNIX_EPOCH.0) * NUM_SECS_PER_DAY
     }
     fn to_monotonic(&self) -> i64 {
-        self.0
+        Date::to_monotonic(self)
     }
     fn from_monotonic(idx: i64) -> Self {
+        Date::from_monotonic(idx)
+    }
+    fn try_from_monotonic(idx: i64) -> crate::Result<Self> {
+        crate::check_monotonic(
+            "Date",
+            idx,
+            Date::from(chrono::NaiveDate::MIN),
+            Date::from(chrono::NaiveDate::MAX),
+        )
+    }
+}
+
+impl Date {
+    // The Julian day number of the monotonic epoch, the 1st of January in year 0
+    // of the proleptic Gregorian calendar, so `jdn = monotonic index + JULIAN_DAY_OF_EPOCH`
+    pub const JULIAN_DAY_OF_EPOCH: i64 = 1_721_060;
+    // 1970-01-01
+    pub const UNIX_EPOCH: Date = Date(719_528);
+
+    // the start of the period with monotonic index 0
+    pub fn monotonic_epoch() -> chrono::NaiveDateTime {
+        base().and_hms_opt(0, 0, 0).expect("Midnight is always valid")
+    }
+    pub const fn from_monotonic(idx: i64) -> Date {
         Date(idx)
     }
+    pub const fn to_monotonic(&self) -> i64 {
+        self.0
+    }
+    pub const fn succ_n(&self, n: u32) -> Date {
+        Date(self.0 + n as i64)
+    }
+    pub const fn pred_n(&self, n: u32) -> Date {
+        Date(self.0 - n as i64)
+    }
+    pub fn from_ymd(year: i32, month: u32, day: u32) -> Option<Date> {
+        chrono::NaiveDate::from_ymd_opt(year, month, day).map(Date::from)
+    }
+    // the day of the year, starting from 1
+    pub fn day_of_year(&self) -> u32 {
+        self.start().ordinal()
+    }
+    pub fn weekday(&self) -> chrono::Weekday {
+        self.start().weekday()
+    }
+    pub fn iso_week(&self) -> chrono::IsoWeek {
+        self.start().iso_week()
+    }
+    pub fn is_weekend(&self, weekend: &calendar::WeekendDef) -> bool {
+        weekend.contains(self.weekday())
+    }
+    // moves by `n` months (or backwards, when negative), using `policy` when the
+    // day doesn't exist in the target month. Errors when out of chrono's range.
+    pub fn add_months(&self, n: i32, policy: month::MonthEndPolicy) -> crate::Result<Date> {
+        let current = month::Month::from_date(self.start());
+        let target = month::Month::try_from_monotonic(current.to_monotonic() + i64::from(n))?;
+        Ok(target.with_same_day_as(*self, policy))
+    }
+    // days since 1970-01-01, as used by Arrow's `Date32`, Postgres and parquet
+    pub fn to_unix_days(&self) -> i32 {
+        i32::try_from(self.0 - Date::UNIX_EPOCH.0).expect("All chrono dates are within i32 days of 1970")
+    }
+    pub fn from_unix_days(days: i32) -> crate::Result<Date> {
+        <Date as crate::TimeResolution>::try_from_monotonic(Date::UNIX_EPOCH.0 + i64::from(days))
+    }
+    // the (chronological) Julian day number, as used in astronomy
+    pub const fn to_julian_day(&self) -> i64 {
+        self.0 + Date::JULIAN_DAY_OF_EPOCH
+    }
+    pub fn from_julian_day(jdn: i64) -> crate::Result<Date> {
+        let idx = jdn.checked_sub(Date::JULIAN_DAY_OF_EPOCH).ok_or(crate::Error::OutOfRange)?;
+        <Date as crate::TimeResolution>::try_from_monotonic(idx)
+    }
+    // parses an ordinal date, eg: `2021-196`
+    pub fn parse_ordinal(s: &str) -> crate::Result<Date> {
+        let date = chrono::NaiveDate::parse_from_str(s, ORDINAL_FORMAT)?;
+        Ok(date.into())
+    }
+    pub fn format_ordinal(&self) -> String {
+        self.start().format(ORDINAL_FORMAT).to_string()
+    }
+    // the same day `n` years later (or earlier), with `policy` deciding how the 29th of February is handled.
+    // Errors when out of chrono's range.
+    pub fn offset_years(&self, n: i32, policy: month::MonthEndPolicy) -> crate::Result<Date> {
+        let months = n.checked_mul(12).ok_or(crate::Error::OutOfRange)?;
+        self.add_months(months, policy)
+    }
+    pub fn year_over_year(&self, policy: month::MonthEndPolicy) -> crate::Result<Date> {
+        self.offset_years(-1, policy)
+    }
+    // The first business day strictly after this date, or `None` if there
+    // isn't one within a year (eg: a weekend of all seven days)
+    pub fn next_business_day<C: calendar::HolidayCalendar>(&self, calendar: &C) -> Option<Date> {
+        (1..=MAX_NON_BUSINESS_DAYS)
+            .map_while(|n| self.checked_succ_n(n).ok())
+            .find(|d| calendar.is_business_day(*d))
+    }
+    // The last business day strictly before this date, or `None` if there isn't one within a year
+    pub fn previous_business_day<C: calendar::HolidayCalendar>(&self, calendar: &C) -> Option<Date> {
+        (1..=MAX_NON_BUSINESS_DAYS)
+            .map_while(|n| self.checked_pred_n(n).ok())
+            .find(|d| calendar.is_business_day(*d))
+    }
+    // Moves `n` business days forward (or backward, when negative), so that
+    // T+2 settlement is `trade.add_business_days(2, &cal)`. When `n` is zero
+    // the date is returned unchanged, even if it is not a business day.
+    pub fn add_business_days<C: calendar::HolidayCalendar>(&self, n: i32, calendar: &C) -> Option<Date> {
+        let mut date = *self;
+        for _ in 0..n.unsigned_abs() {
+            date = if n > 0 {
+                date.next_business_day(calendar)?
+            } else {
+                date.previous_business_day(calendar)?
+            };
+        }
+        Some(date)
+    }
 }
 
-impl Date {}
+#[cfg(test)]
+mod tests {
+    use super::Date;
+    use crate::{MonthEndPolicy, TimeResolution, WeekendDef};
+
+    #[test]
+    fn test_chrono_days() {
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        assert_eq!(date + chrono::Days::new(20), Date::from_ymd(2021, 8, 4).unwrap());
+        assert_eq!(date - chrono::Days::new(15), Date::from_ymd(2021, 6, 30).unwrap());
+        assert_eq!(date + chrono::Days::new(0), date);
+    }
+
+    #[test]
+    fn test_unix_days() {
+        assert_eq!(Date::UNIX_EPOCH, Date::from_ymd(1970, 1, 1).unwrap());
+        assert_eq!(Date::UNIX_EPOCH.to_unix_days(), 0);
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        assert_eq!(date.to_unix_days(), 18_823);
+        assert_eq!(Date::from_unix_days(18_823).unwrap(), date);
+        assert_eq!(Date::from_unix_days(-1).unwrap(), Date::from_ymd(1969, 12, 31).unwrap());
+        assert!(Date::from_unix_days(i32::MAX).is_err());
+        assert!(Date::from_unix_days(i32::MIN).is_err());
+    }
+
+    #[test]
+    fn test_julian_day() {
+        // J2000.0 is at noon on this day
+        let date = Date::from_ymd(2000, 1, 1).unwrap();
+        assert_eq!(date.to_julian_day(), 2_451_545);
+        assert_eq!(Date::from_julian_day(2_451_545).unwrap(), date);
+        assert_eq!(Date::from_ymd(1970, 1, 1).unwrap().to_julian_day(), 2_440_588);
+        // the start of the Julian period is the 24th of November 4714 BC in the proleptic Gregorian calendar
+        assert_eq!(Date::from_julian_day(0).unwrap(), Date::from_ymd(-4713, 11, 24).unwrap());
+        assert_eq!(Date::from_monotonic(0).to_julian_day(), Date::JULIAN_DAY_OF_EPOCH);
+        assert!(Date::from_julian_day(i64::MIN).is_err());
+        assert!(Date::from_julian_day(i64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_ordinal() {
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        assert_eq!(date.day_of_year(), 196);
+        assert_eq!(date.format_ordinal(), "2021-196");
+        assert_eq!(Date::parse_ordinal("2021-196").unwrap(), date);
+        assert_eq!(Date::parse_ordinal("2020-366").unwrap(), Date::from_ymd(2020, 12, 31).unwrap());
+        assert_eq!(Date::from_ymd(2021, 1, 1).unwrap().format_ordinal(), "2021-001");
+        assert!(Date::parse_ordinal("2021-366").is_err());
+        assert!(Date::parse_ordinal("2021-07-15").is_err());
+    }
+
+    #[test]
+    fn test_offset_years() {
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        assert_eq!(date.year_over_year(MonthEndPolicy::Clamp).unwrap(), Date::from_ymd(2020, 7, 15).unwrap());
+        assert_eq!(date.offset_years(3, MonthEndPolicy::Clamp).unwrap(), Date::from_ymd(2024, 7, 15).unwrap());
+        let leap_day = Date::from_ymd(2020, 2, 29).unwrap();
+        assert_eq!(leap_day.offset_years(1, MonthEndPolicy::Clamp).unwrap(), Date::from_ymd(2021, 2, 28).unwrap());
+        assert_eq!(leap_day.offset_years(4, MonthEndPolicy::Clamp).unwrap(), Date::from_ymd(2024, 2, 29).unwrap());
+        let feb_28 = Date::from_ymd(2021, 2, 28).unwrap();
+        assert_eq!(feb_28.offset_years(-1, MonthEndPolicy::Clamp).unwrap(), Date::from_ymd(2020, 2, 28).unwrap());
+        assert_eq!(feb_28.offset_years(-1, MonthEndPolicy::Preserve).unwrap(), leap_day);
+        assert!(date.offset_years(1_000_000, MonthEndPolicy::Clamp).is_err());
+        assert!(date.offset_years(i32::MIN, MonthEndPolicy::Clamp).is_err());
+    }
+
+    #[test]
+    fn test_accessors() {
+        let d = Date::from_ymd(2021, 3, 1).unwrap();
+        assert_eq!(d, Date::from(chrono::NaiveDate::from_ymd_opt(2021, 3, 1).unwrap()));
+        assert_eq!(d.day_of_year(), 60);
+        assert_eq!(d.weekday(), chrono::Weekday::Mon);
+        assert_eq!(d.iso_week().week(), 9);
+        assert_eq!(d.iso_week().year(), 2021);
+        assert!(!d.is_weekend(&WeekendDef::saturday_sunday()));
+        assert!(Date::from_ymd(2021, 2, 28).unwrap().is_weekend(&WeekendDef::default()));
+        assert_eq!(Date::from_ymd(2021, 2, 29), None);
+    }
+
+    #[test]
+    fn test_add_months() {
+        let jan_31 = Date::from_ymd(2021, 1, 31).unwrap();
+        assert_eq!(jan_31.add_months(1, MonthEndPolicy::Clamp).unwrap(), Date::from_ymd(2021, 2, 28).unwrap());
+        assert_eq!(jan_31.add_months(13, MonthEndPolicy::Clamp).unwrap(), Date::from_ymd(2022, 2, 28).unwrap());
+        assert_eq!(jan_31.add_months(-2, MonthEndPolicy::Clamp).unwrap(), Date::from_ymd(2020, 11, 30).unwrap());
+        assert_eq!(jan_31.add_months(0, MonthEndPolicy::Clamp).unwrap(), jan_31);
+        let feb_28 = Date::from_ymd(2021, 2, 28).unwrap();
+        assert_eq!(feb_28.add_months(1, MonthEndPolicy::Clamp).unwrap(), Date::from_ymd(2021, 3, 28).unwrap());
+        assert_eq!(feb_28.add_months(1, MonthEndPolicy::Preserve).unwrap(), Date::from_ymd(2021, 3, 31).unwrap());
+        assert_eq!(feb_28.add_months(-12, MonthEndPolicy::Preserve).unwrap(), Date::from_ymd(2020, 2, 29).unwrap());
+        let mid = Date::from_ymd(2021, 3, 15).unwrap();
+        assert_eq!(mid.add_months(1, MonthEndPolicy::Preserve).unwrap(), Date::from_ymd(2021, 4, 15).unwrap());
+        assert!(mid.add_months(i32::MAX, MonthEndPolicy::Clamp).is_err());
+        assert!(mid.add_months(i32::MIN, MonthEndPolicy::Clamp).is_err());
+        let max = Date::from(chrono::NaiveDate::MAX);
+        assert_eq!(max.add_months(0, MonthEndPolicy::Clamp).unwrap(), max);
+        assert!(max.add_months(1, MonthEndPolicy::Clamp).is_err());
+        assert_eq!(max.add_months(-1, MonthEndPolicy::Preserve).unwrap(), Date::from_ymd(262_143, 11, 30).unwrap());
+    }
+
+    #[test]
+    fn test_try_from_monotonic() {
+        let d = Date::from_ymd(2021, 3, 1).unwrap();
+        assert_eq!(Date::try_from_monotonic(d.to_monotonic()).unwrap(), d);
+        let max = Date::from(chrono::NaiveDate::MAX).to_monotonic();
+        assert!(Date::try_from_monotonic(max).is_ok());
+        assert!(Date::try_from_monotonic(max + 1).is_err());
+        assert!(Date::try_from_monotonic(i64::MIN).is_err());
+    }
+
+    #[test]
+    fn test_try_start() {
+        use crate::DateResolution;
+        let d = Date::from_ymd(2021, 3, 1).unwrap();
+        assert_eq!(d.try_start().unwrap(), d.start());
+        assert!(Date::from_monotonic(i64::MAX).try_start().is_err());
+        assert!(Date::from_monotonic(i64::MIN).try_naive_date_time().is_err());
+    }
+
+    #[test]
+    fn test_try_from() {
+        use std::convert::TryFrom;
+        let d = Date::try_from("2021-03-01").unwrap();
+        assert_eq!(d, Date::from_ymd(2021, 3, 1).unwrap());
+        assert_eq!(Date::try_from(d.to_monotonic()).unwrap(), d);
+        assert!(Date::try_from("2021-02-29").is_err());
+        assert!(Date::try_from(i64::MAX).is_err());
+    }
+
+    #[cfg(feature = "locale")]
+    #[test]
+    fn test_format_localized() {
+        let date = Date::from_ymd(2021, 12, 15).unwrap();
+        assert_eq!(date.format_localized(chrono::Locale::fr_FR), "mer. 15/12/2021");
+        assert_eq!(date.format_localized(chrono::Locale::de_DE), "Mi 15.12.2021");
+        assert_eq!(date.format_localized(chrono::Locale::en_US), "Wed 12/15/2021");
+    }
+}
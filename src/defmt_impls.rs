@@ -0,0 +1,48 @@
+use crate::{Date, Minutes, Month, Quarter, TimeRange, TimeResolution, Year};
+
+// Periods are logged as their resolution and monotonic index, eg: `Date:738351`, which
+// is cheap to encode on the device. The host can convert the index back to a period.
+macro_rules! impl_format {
+    ($($ty:ident),*) => {
+        $(
+            impl defmt::Format for $ty {
+                fn format(&self, f: defmt::Formatter<'_>) {
+                    defmt::write!(f, "{=str}:{=i64}", stringify!($ty), self.to_monotonic())
+                }
+            }
+        )*
+    };
+}
+
+impl_format!(Date, Month, Quarter, Year);
+
+impl<const N: u32> defmt::Format for Minutes<N> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "Minutes[Length:{=u32}]:{=i64}", N, self.to_monotonic())
+    }
+}
+
+impl<P: TimeResolution + defmt::Format> defmt::Format for TimeRange<P> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "{}+{=usize}", self.start(), self.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Date, FiveMinute, Hour, Month, Quarter, TimeRange, Year};
+
+    fn assert_format<T: defmt::Format>() {}
+
+    #[test]
+    fn test_format_impls() {
+        assert_format::<Date>();
+        assert_format::<Month>();
+        assert_format::<Quarter>();
+        assert_format::<Year>();
+        assert_format::<FiveMinute>();
+        assert_format::<Hour>();
+        assert_format::<TimeRange<Date>>();
+        assert_format::<TimeRange<FiveMinute>>();
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{Date, Minutes, Month, Quarter, Rounding, TimeResolution, Year};
+use diesel::{
+    backend::Backend,
+    deserialize::{self, FromSql, FromSqlRow, Queryable},
+    expression::{bound::Bound, AsExpression},
+    row::Row,
+    serialize::{self, Output, ToSql},
+    sql_types,
+};
+use std::io;
+
+// Date resolutions map to `Date` columns and sub-date resolutions to
+// `Timestamp` columns, using the start of the period. This is what
+// `#[derive(AsExpression, FromSqlRow)]` would generate, which can't be
+// used as it doesn't support const generics.
+macro_rules! impl_expression {
+    ([$($gen:tt)*] $ty:ty, $sql:ty) => {
+        impl<$($gen)*> AsExpression<$sql> for $ty {
+            type Expression = Bound<$sql, Self>;
+            fn as_expression(self) -> Self::Expression {
+                Bound::new(self)
+            }
+        }
+
+        impl<$($gen)*> AsExpression<sql_types::Nullable<$sql>> for $ty {
+            type Expression = Bound<sql_types::Nullable<$sql>, Self>;
+            fn as_expression(self) -> Self::Expression {
+                Bound::new(self)
+            }
+        }
+
+        impl<'expr, $($gen)*> AsExpression<$sql> for &'expr $ty {
+            type Expression = Bound<$sql, Self>;
+            fn as_expression(self) -> Self::Expression {
+                Bound::new(self)
+            }
+        }
+
+        impl<'expr, $($gen)*> AsExpression<sql_types::Nullable<$sql>> for &'expr $ty {
+            type Expression = Bound<sql_types::Nullable<$sql>, Self>;
+            fn as_expression(self) -> Self::Expression {
+                Bound::new(self)
+            }
+        }
+
+        impl<$($gen)* DB> ToSql<sql_types::Nullable<$sql>, DB> for $ty
+        where
+            DB: Backend,
+            Self: ToSql<$sql, DB>,
+        {
+            fn to_sql<W: io::Write>(&self, out: &mut Output<W, DB>) -> serialize::Result {
+                ToSql::<$sql, DB>::to_sql(self, out)
+            }
+        }
+
+        impl<$($gen)* ST, DB> FromSqlRow<ST, DB> for $ty
+        where
+            DB: Backend,
+            Self: FromSql<ST, DB>,
+        {
+            fn build_from_row<R: Row<DB>>(row: &mut R) -> deserialize::Result<Self> {
+                FromSql::<ST, DB>::from_sql(row.take())
+            }
+        }
+
+        impl<$($gen)* ST, DB> Queryable<ST, DB> for $ty
+        where
+            DB: Backend,
+            Self: FromSql<ST, DB>,
+        {
+            type Row = Self;
+            fn build(row: Self::Row) -> Self {
+                row
+            }
+        }
+    };
+}
+
+macro_rules! impl_date_resolution {
+    ($($ty:ty),*) => {
+        $(
+            impl_expression!([] $ty, sql_types::Date);
+
+            impl<DB> ToSql<sql_types::Date, DB> for $ty
+            where
+                DB: Backend,
+                chrono::NaiveDate: ToSql<sql_types::Date, DB>,
+            {
+                fn to_sql<W: io::Write>(&self, out: &mut Output<W, DB>) -> serialize::Result {
+                    ToSql::<sql_types::Date, DB>::to_sql(&self.naive_date_time().date(), out)
+                }
+            }
+
+            impl<DB> FromSql<sql_types::Date, DB> for $ty
+            where
+                DB: Backend,
+                chrono::NaiveDate: FromSql<sql_types::Date, DB>,
+            {
+                fn from_sql(bytes: Option<&DB::RawValue>) -> deserialize::Result<Self> {
+                    let date = chrono::NaiveDate::from_sql(bytes)?;
+                    let midnight = date.and_hms_opt(0, 0, 0).expect("Midnight is always valid");
+                    Ok(<$ty>::from_datetime_with(midnight, Rounding::Strict)?)
+                }
+            }
+        )*
+    };
+}
+
+impl_date_resolution!(Date, Month, Quarter, Year);
+
+impl_expression!([const N: u32,] Minutes<N>, sql_types::Timestamp);
+
+impl<const N: u32, DB> ToSql<sql_types::Timestamp, DB> for Minutes<N>
+where
+    DB: Backend,
+    chrono::NaiveDateTime: ToSql<sql_types::Timestamp, DB>,
+{
+    fn to_sql<W: io::Write>(&self, out: &mut Output<W, DB>) -> serialize::Result {
+        ToSql::<sql_types::Timestamp, DB>::to_sql(&self.naive_date_time(), out)
+    }
+}
+
+impl<const N: u32, DB> FromSql<sql_types::Timestamp, DB> for Minutes<N>
+where
+    DB: Backend,
+    chrono::NaiveDateTime: FromSql<sql_types::Timestamp, DB>,
+{
+    fn from_sql(bytes: Option<&DB::RawValue>) -> deserialize::Result<Self> {
+        let dt = chrono::NaiveDateTime::from_sql(bytes)?;
+        Ok(Minutes::from_datetime_with(dt, Rounding::Strict)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Date, HalfHour, Month};
+    use diesel::{sql_types, sqlite::SqliteConnection, Connection, IntoSql, RunQueryDsl};
+
+    fn connection() -> SqliteConnection {
+        SqliteConnection::establish(":memory:").unwrap()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let conn = connection();
+        let jul = Month::from_year_month(2021, 7).unwrap();
+        let read = diesel::select(jul.into_sql::<sql_types::Date>())
+            .get_result::<Month>(&conn)
+            .unwrap();
+        assert_eq!(read, jul);
+        let period = "2021-07-15 10:30".parse::<HalfHour>().unwrap();
+        let read = diesel::select(period.into_sql::<sql_types::Timestamp>())
+            .get_result::<HalfHour>(&conn)
+            .unwrap();
+        assert_eq!(read, period);
+        let read =
+            diesel::select(Some(period).into_sql::<sql_types::Nullable<sql_types::Timestamp>>())
+                .get_result::<Option<HalfHour>>(&conn)
+                .unwrap();
+        assert_eq!(read, Some(period));
+    }
+
+    #[test]
+    fn test_not_on_boundary() {
+        let conn = connection();
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        assert!(diesel::select(date.into_sql::<sql_types::Date>())
+            .get_result::<Month>(&conn)
+            .is_err());
+        let dt = chrono::NaiveDate::from_ymd_opt(2021, 7, 15).unwrap().and_hms_opt(10, 45, 0).unwrap();
+        assert!(diesel::select(dt.into_sql::<sql_types::Timestamp>())
+            .get_result::<HalfHour>(&conn)
+            .is_err());
+    }
+}
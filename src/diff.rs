@@ -0,0 +1,150 @@
+use crate::{Cache, RequestTracker, TimeRange, TimeResolution, TimeSeries};
+use std::{collections, fmt};
+
+// The changes needed to go from one period-keyed map to another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeriesDiff<P: TimeResolution, T> {
+    pub added: collections::BTreeMap<P, T>,
+    pub removed: collections::BTreeSet<P>,
+    // the new value of each changed period
+    pub changed: collections::BTreeMap<P, T>,
+}
+
+impl<P: TimeResolution, T> SeriesDiff<P, T> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+    pub fn added_ranges(&self) -> Vec<TimeRange<P>> {
+        contiguous_ranges(self.added.keys().copied())
+    }
+    pub fn removed_ranges(&self) -> Vec<TimeRange<P>> {
+        contiguous_ranges(self.removed.iter().copied())
+    }
+    pub fn changed_ranges(&self) -> Vec<TimeRange<P>> {
+        contiguous_ranges(self.changed.keys().copied())
+    }
+    // applies the diff to a map, turning the `old` map it was created from into the `new` one
+    pub fn apply_to(&self, map: &mut collections::BTreeMap<P, T>)
+    where
+        T: Clone,
+    {
+        for period in &self.removed {
+            map.remove(period);
+        }
+        for (period, value) in self.added.iter().chain(self.changed.iter()) {
+            map.insert(*period, value.clone());
+        }
+    }
+}
+
+pub fn diff<P: TimeResolution, T: PartialEq + Clone>(
+    old: &collections::BTreeMap<P, T>,
+    new: &collections::BTreeMap<P, T>,
+) -> SeriesDiff<P, T> {
+    let mut added = collections::BTreeMap::new();
+    let mut changed = collections::BTreeMap::new();
+    for (period, value) in new {
+        match old.get(period) {
+            None => {
+                added.insert(*period, value.clone());
+            }
+            Some(old_value) if old_value != value => {
+                changed.insert(*period, value.clone());
+            }
+            Some(_) => (),
+        }
+    }
+    let removed = old.keys().filter(|p| !new.contains_key(p)).copied().collect();
+    SeriesDiff {
+        added,
+        removed,
+        changed,
+    }
+}
+
+// groups sorted periods into runs of consecutive periods
+fn contiguous_ranges<P: TimeResolution>(periods: impl Iterator<Item = P>) -> Vec<TimeRange<P>> {
+    let mut ranges = Vec::<TimeRange<P>>::new();
+    for period in periods {
+        match ranges.last_mut() {
+            Some(last) if last.end().succ() == period => *last = TimeRange::new(last.start(), last.len + 1),
+            _ => ranges.push(TimeRange::new(period, 1)),
+        }
+    }
+    ranges
+}
+
+impl<P: TimeResolution, T: Clone> TimeSeries<P, T> {
+    pub fn apply(&mut self, diff: &SeriesDiff<P, T>) {
+        for period in &diff.removed {
+            self.remove(*period);
+        }
+        for (period, value) in diff.added.iter().chain(diff.changed.iter()) {
+            self.insert(*period, value.clone());
+        }
+    }
+}
+
+impl<K: TimeResolution, T: Send + fmt::Debug + Eq + Copy, R: RequestTracker<K>> Cache<K, T, R> {
+    // Updates the cached data, treating removed periods as
+    // requested, but with no data, rather than as not requested
+    pub fn apply(&mut self, diff: &SeriesDiff<K, T>) {
+        for period in &diff.removed {
+            self.data.remove(period);
+        }
+        let updates = diff
+            .added
+            .iter()
+            .chain(diff.changed.iter())
+            .map(|(k, v)| (*k, *v))
+            .collect::<collections::BTreeMap<_, _>>();
+        self.add(updates.keys().copied().collect(), updates);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff;
+    use crate::{Cache, CacheResponse, Date, TimeRange, TimeResolution, TimeSeries};
+    use std::collections;
+
+    fn map(start: Date, values: &[Option<i32>]) -> collections::BTreeMap<Date, i32> {
+        values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, v)| v.map(|v| (start.succ_n(i as u32), v)))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let start = Date::from_ymd(2021, 7, 1).unwrap();
+        let old = map(start, &[Some(1), Some(2), Some(3), None, None, Some(6), Some(7)]);
+        let new = map(start, &[Some(1), Some(20), Some(30), Some(4), Some(5), None, Some(7)]);
+        let d = diff(&old, &new);
+        assert_eq!(d.changed_ranges(), vec![TimeRange::new(start.succ(), 2)]);
+        assert_eq!(d.added_ranges(), vec![TimeRange::new(start.succ_n(3), 2)]);
+        assert_eq!(d.removed_ranges(), vec![TimeRange::new(start.succ_n(5), 1)]);
+        assert_eq!(d.changed.get(&start.succ()), Some(&20));
+        assert!(diff(&old, &old).is_empty());
+
+        let mut patched = old.clone();
+        d.apply_to(&mut patched);
+        assert_eq!(patched, new);
+        let mut series = TimeSeries::from(old);
+        series.apply(&d);
+        assert_eq!(series.into_map(), new);
+    }
+
+    #[test]
+    fn test_apply_to_cache() {
+        let start = Date::from_ymd(2021, 7, 1).unwrap();
+        let range = TimeRange::new(start, 3);
+        let old = map(start, &[Some(1), Some(2), Some(3)]);
+        let new = map(start, &[Some(1), None, Some(30), Some(4)]);
+        let mut cache = Cache::empty();
+        cache.add(range.set(), old.clone());
+        cache.apply(&diff(&old, &new));
+        assert_eq!(cache.get(TimeRange::new(start, 4).set()), CacheResponse::Hit(new));
+    }
+}
@@ -0,0 +1,734 @@
+use crate::{
+    Date, FiveMinute, HalfHour, Hour, Minute, Month, Quarter, TimeRange, TimeResolution, Year,
+};
+#[cfg(feature = "serde")]
+use serde::de;
+use std::{
+    any, borrow,
+    convert::{self, TryFrom},
+    fmt, str,
+};
+
+// An object-safe companion to `TimeResolution`, for when a
+// heterogeneous collection of periods is needed, eg:
+// `Vec<Box<dyn DynTimeResolution>>`
+pub trait DynTimeResolution: fmt::Debug + fmt::Display + Send + Sync {
+    fn name(&self) -> borrow::Cow<'static, str>;
+    fn type_id(&self) -> any::TypeId;
+    fn to_monotonic(&self) -> i64;
+    fn start_datetime(&self) -> chrono::NaiveDateTime;
+    fn succ_boxed(&self) -> Box<dyn DynTimeResolution>;
+    fn pred_boxed(&self) -> Box<dyn DynTimeResolution>;
+}
+
+impl<P: TimeResolution + 'static> DynTimeResolution for P {
+    fn name(&self) -> borrow::Cow<'static, str> {
+        TimeResolution::name(self)
+    }
+    fn type_id(&self) -> any::TypeId {
+        any::TypeId::of::<P>()
+    }
+    fn to_monotonic(&self) -> i64 {
+        TimeResolution::to_monotonic(self)
+    }
+    fn start_datetime(&self) -> chrono::NaiveDateTime {
+        self.naive_date_time()
+    }
+    fn succ_boxed(&self) -> Box<dyn DynTimeResolution> {
+        Box::new(self.succ())
+    }
+    fn pred_boxed(&self) -> Box<dyn DynTimeResolution> {
+        Box::new(self.pred())
+    }
+}
+
+// The built-in resolutions, for when the resolution
+// is only known at runtime (eg: from configuration)
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResolutionKind {
+    Minute,
+    FiveMinute,
+    HalfHour,
+    Hour,
+    Date,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl ResolutionKind {
+    pub const ALL: [ResolutionKind; 8] = [
+        ResolutionKind::Minute,
+        ResolutionKind::FiveMinute,
+        ResolutionKind::HalfHour,
+        ResolutionKind::Hour,
+        ResolutionKind::Date,
+        ResolutionKind::Month,
+        ResolutionKind::Quarter,
+        ResolutionKind::Year,
+    ];
+    // the kind of a typed resolution, if it is one of the built-ins
+    pub fn of<P: TimeResolution + 'static>() -> Option<ResolutionKind> {
+        let type_id = any::TypeId::of::<P>();
+        ResolutionKind::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.type_id() == type_id)
+    }
+    pub fn type_id(&self) -> any::TypeId {
+        match self {
+            ResolutionKind::Minute => any::TypeId::of::<Minute>(),
+            ResolutionKind::FiveMinute => any::TypeId::of::<FiveMinute>(),
+            ResolutionKind::HalfHour => any::TypeId::of::<HalfHour>(),
+            ResolutionKind::Hour => any::TypeId::of::<Hour>(),
+            ResolutionKind::Date => any::TypeId::of::<Date>(),
+            ResolutionKind::Month => any::TypeId::of::<Month>(),
+            ResolutionKind::Quarter => any::TypeId::of::<Quarter>(),
+            ResolutionKind::Year => any::TypeId::of::<Year>(),
+        }
+    }
+}
+
+impl str::FromStr for ResolutionKind {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ResolutionKind::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.to_string() == s)
+            .ok_or_else(|| crate::Error::UnknownResolution(s.to_string()))
+    }
+}
+
+// matches `TimeResolution::name`
+impl fmt::Display for ResolutionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolutionKind::Minute => write!(f, "Minutes[Length:1]"),
+            ResolutionKind::FiveMinute => write!(f, "Minutes[Length:5]"),
+            ResolutionKind::HalfHour => write!(f, "Minutes[Length:30]"),
+            ResolutionKind::Hour => write!(f, "Minutes[Length:60]"),
+            ResolutionKind::Date => write!(f, "Date"),
+            ResolutionKind::Month => write!(f, "Month"),
+            ResolutionKind::Quarter => write!(f, "Quarter"),
+            ResolutionKind::Year => write!(f, "Year"),
+        }
+    }
+}
+
+// Stands in for a closure that is generic over the resolution,
+// to be used with `AnyResolution::try_map`
+pub trait ResolutionMapper {
+    type Output;
+    fn map<P: TimeResolution + 'static>(self, period: P) -> crate::Result<Self::Output>;
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum AnyResolution {
+    Minute(Minute),
+    FiveMinute(FiveMinute),
+    HalfHour(HalfHour),
+    Hour(Hour),
+    Date(Date),
+    Month(Month),
+    Quarter(Quarter),
+    Year(Year),
+}
+
+impl AnyResolution {
+    // parses a value in the `Display` format of the given resolution
+    pub fn parse_as(kind: ResolutionKind, s: &str) -> crate::Result<AnyResolution> {
+        Ok(match kind {
+            ResolutionKind::Minute => AnyResolution::Minute(s.parse()?),
+            ResolutionKind::FiveMinute => AnyResolution::FiveMinute(s.parse()?),
+            ResolutionKind::HalfHour => AnyResolution::HalfHour(s.parse()?),
+            ResolutionKind::Hour => AnyResolution::Hour(s.parse()?),
+            ResolutionKind::Date => AnyResolution::Date(s.parse()?),
+            ResolutionKind::Month => AnyResolution::Month(s.parse()?),
+            ResolutionKind::Quarter => AnyResolution::Quarter(s.parse()?),
+            ResolutionKind::Year => AnyResolution::Year(s.parse()?),
+        })
+    }
+    // parses a resolution name followed by a value, as produced
+    // by `to_descriptor_string`, eg: `Minutes[Length:5]:2021-01-01 10:00`
+    pub fn parse_descriptor(s: &str) -> crate::Result<AnyResolution> {
+        let (kind, value) = split_descriptor(s);
+        let value = value
+            .and_then(|v| v.strip_prefix(':'))
+            .ok_or_else(|| crate::Error::ParseCustom {
+                ty_name: "AnyResolution",
+                input: s.to_string(),
+            })?;
+        AnyResolution::parse_as(kind.parse()?, value)
+    }
+    pub fn to_descriptor_string(&self) -> String {
+        format!("{}:{}", self.kind(), self)
+    }
+    pub fn is<P: TimeResolution + 'static>(&self) -> bool {
+        self.kind().type_id() == any::TypeId::of::<P>()
+    }
+    pub fn downcast<P: TimeResolution + 'static>(&self) -> Option<P> {
+        self.as_any().downcast_ref::<P>().copied()
+    }
+    pub fn try_map<M: ResolutionMapper>(&self, mapper: M) -> crate::Result<M::Output> {
+        match *self {
+            AnyResolution::Minute(p) => mapper.map(p),
+            AnyResolution::FiveMinute(p) => mapper.map(p),
+            AnyResolution::HalfHour(p) => mapper.map(p),
+            AnyResolution::Hour(p) => mapper.map(p),
+            AnyResolution::Date(p) => mapper.map(p),
+            AnyResolution::Month(p) => mapper.map(p),
+            AnyResolution::Quarter(p) => mapper.map(p),
+            AnyResolution::Year(p) => mapper.map(p),
+        }
+    }
+    fn as_any(&self) -> &dyn any::Any {
+        match self {
+            AnyResolution::Minute(p) => p,
+            AnyResolution::FiveMinute(p) => p,
+            AnyResolution::HalfHour(p) => p,
+            AnyResolution::Hour(p) => p,
+            AnyResolution::Date(p) => p,
+            AnyResolution::Month(p) => p,
+            AnyResolution::Quarter(p) => p,
+            AnyResolution::Year(p) => p,
+        }
+    }
+    pub fn from_parts(kind: ResolutionKind, idx: i64) -> crate::Result<AnyResolution> {
+        Ok(match kind {
+            ResolutionKind::Minute => AnyResolution::Minute(Minute::try_from_monotonic(idx)?),
+            ResolutionKind::FiveMinute => {
+                AnyResolution::FiveMinute(FiveMinute::try_from_monotonic(idx)?)
+            }
+            ResolutionKind::HalfHour => AnyResolution::HalfHour(HalfHour::try_from_monotonic(idx)?),
+            ResolutionKind::Hour => AnyResolution::Hour(Hour::try_from_monotonic(idx)?),
+            ResolutionKind::Date => AnyResolution::Date(Date::try_from_monotonic(idx)?),
+            ResolutionKind::Month => AnyResolution::Month(Month::try_from_monotonic(idx)?),
+            ResolutionKind::Quarter => AnyResolution::Quarter(Quarter::try_from_monotonic(idx)?),
+            ResolutionKind::Year => AnyResolution::Year(Year::try_from_monotonic(idx)?),
+        })
+    }
+    // the period of the given resolution containing the date-time
+    pub fn from_datetime(kind: ResolutionKind, dt: chrono::NaiveDateTime) -> AnyResolution {
+        match kind {
+            ResolutionKind::Minute => AnyResolution::Minute(Minute::from_datetime(dt)),
+            ResolutionKind::FiveMinute => AnyResolution::FiveMinute(FiveMinute::from_datetime(dt)),
+            ResolutionKind::HalfHour => AnyResolution::HalfHour(HalfHour::from_datetime(dt)),
+            ResolutionKind::Hour => AnyResolution::Hour(Hour::from_datetime(dt)),
+            ResolutionKind::Date => AnyResolution::Date(Date::from_datetime(dt)),
+            ResolutionKind::Month => AnyResolution::Month(Month::from_datetime(dt)),
+            ResolutionKind::Quarter => AnyResolution::Quarter(Quarter::from_datetime(dt)),
+            ResolutionKind::Year => AnyResolution::Year(Year::from_datetime(dt)),
+        }
+    }
+    // The range of periods of the target resolution which overlap this period.
+    // When the target is finer this is all the periods within this one, and
+    // when it is coarser this is the single period containing this one.
+    pub fn rescale_to(&self, target: ResolutionKind) -> crate::Result<AnyTimeRange> {
+        let start = self.try_naive_date_time()?;
+        let end = self.succ().try_naive_date_time()? - chrono::Duration::nanoseconds(1);
+        let first = AnyResolution::from_datetime(target, start);
+        let last = AnyResolution::from_datetime(target, end);
+        let range = AnyTimeRange::from_start_end(first, last)
+            .expect("Both ends are of the target resolution and in order");
+        AnyTimeRange::new(range.kind, range.start, range.len)
+    }
+    pub fn kind(&self) -> ResolutionKind {
+        match self {
+            AnyResolution::Minute(_) => ResolutionKind::Minute,
+            AnyResolution::FiveMinute(_) => ResolutionKind::FiveMinute,
+            AnyResolution::HalfHour(_) => ResolutionKind::HalfHour,
+            AnyResolution::Hour(_) => ResolutionKind::Hour,
+            AnyResolution::Date(_) => ResolutionKind::Date,
+            AnyResolution::Month(_) => ResolutionKind::Month,
+            AnyResolution::Quarter(_) => ResolutionKind::Quarter,
+            AnyResolution::Year(_) => ResolutionKind::Year,
+        }
+    }
+    pub fn to_monotonic(&self) -> i64 {
+        match self {
+            AnyResolution::Minute(p) => p.to_monotonic(),
+            AnyResolution::FiveMinute(p) => p.to_monotonic(),
+            AnyResolution::HalfHour(p) => p.to_monotonic(),
+            AnyResolution::Hour(p) => p.to_monotonic(),
+            AnyResolution::Date(p) => p.to_monotonic(),
+            AnyResolution::Month(p) => p.to_monotonic(),
+            AnyResolution::Quarter(p) => p.to_monotonic(),
+            AnyResolution::Year(p) => p.to_monotonic(),
+        }
+    }
+    pub fn try_naive_date_time(&self) -> crate::Result<chrono::NaiveDateTime> {
+        AnyResolution::from_parts(self.kind(), self.to_monotonic()).map(|p| p.naive_date_time())
+    }
+    pub fn naive_date_time(&self) -> chrono::NaiveDateTime {
+        match self {
+            AnyResolution::Minute(p) => p.naive_date_time(),
+            AnyResolution::FiveMinute(p) => p.naive_date_time(),
+            AnyResolution::HalfHour(p) => p.naive_date_time(),
+            AnyResolution::Hour(p) => p.naive_date_time(),
+            AnyResolution::Date(p) => p.naive_date_time(),
+            AnyResolution::Month(p) => p.naive_date_time(),
+            AnyResolution::Quarter(p) => p.naive_date_time(),
+            AnyResolution::Year(p) => p.naive_date_time(),
+        }
+    }
+    pub fn succ(&self) -> AnyResolution {
+        self.succ_n(1)
+    }
+    pub fn pred(&self) -> AnyResolution {
+        self.pred_n(1)
+    }
+    pub fn succ_n(&self, n: u32) -> AnyResolution {
+        match self {
+            AnyResolution::Minute(p) => AnyResolution::Minute(p.succ_n(n)),
+            AnyResolution::FiveMinute(p) => AnyResolution::FiveMinute(p.succ_n(n)),
+            AnyResolution::HalfHour(p) => AnyResolution::HalfHour(p.succ_n(n)),
+            AnyResolution::Hour(p) => AnyResolution::Hour(p.succ_n(n)),
+            AnyResolution::Date(p) => AnyResolution::Date(p.succ_n(n)),
+            AnyResolution::Month(p) => AnyResolution::Month(p.succ_n(n)),
+            AnyResolution::Quarter(p) => AnyResolution::Quarter(p.succ_n(n)),
+            AnyResolution::Year(p) => AnyResolution::Year(p.succ_n(n)),
+        }
+    }
+    pub fn pred_n(&self, n: u32) -> AnyResolution {
+        match self {
+            AnyResolution::Minute(p) => AnyResolution::Minute(p.pred_n(n)),
+            AnyResolution::FiveMinute(p) => AnyResolution::FiveMinute(p.pred_n(n)),
+            AnyResolution::HalfHour(p) => AnyResolution::HalfHour(p.pred_n(n)),
+            AnyResolution::Hour(p) => AnyResolution::Hour(p.pred_n(n)),
+            AnyResolution::Date(p) => AnyResolution::Date(p.pred_n(n)),
+            AnyResolution::Month(p) => AnyResolution::Month(p.pred_n(n)),
+            AnyResolution::Quarter(p) => AnyResolution::Quarter(p.pred_n(n)),
+            AnyResolution::Year(p) => AnyResolution::Year(p.pred_n(n)),
+        }
+    }
+}
+
+// Erased periods can come from anywhere (eg: a raw index read from a database), so rather
+// than panicking, periods which don't both start and end within chrono's range are
+// shown by name and monotonic index
+fn fmt_checked<P: TimeResolution>(period: &P, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if period.checked_succ_n(1).is_ok() {
+        fmt::Display::fmt(period, f)
+    } else {
+        write!(f, "{}#{}", period.name(), period.to_monotonic())
+    }
+}
+
+impl fmt::Display for AnyResolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnyResolution::Minute(p) => fmt_checked(p, f),
+            AnyResolution::FiveMinute(p) => fmt_checked(p, f),
+            AnyResolution::HalfHour(p) => fmt_checked(p, f),
+            AnyResolution::Hour(p) => fmt_checked(p, f),
+            AnyResolution::Date(p) => fmt_checked(p, f),
+            AnyResolution::Month(p) => fmt_checked(p, f),
+            AnyResolution::Quarter(p) => fmt_checked(p, f),
+            AnyResolution::Year(p) => fmt_checked(p, f),
+        }
+    }
+}
+
+// splits the resolution name from anything following it, being careful
+// of parameterised names that contain `:`, eg: `Minutes[Length:5]`
+fn split_descriptor(s: &str) -> (&str, Option<&str>) {
+    let end = match (s.find('['), s.find(']'), s.find(':')) {
+        (Some(open), Some(close), _) if open < close => close + 1,
+        (_, _, Some(colon)) => colon,
+        _ => s.len(),
+    };
+    let (kind, rest) = s.split_at(end);
+    (kind, if rest.is_empty() { None } else { Some(rest) })
+}
+
+// The resolution is inferred from the format, which is
+// the same as the `Display` output of the wrapped type
+impl str::FromStr for AnyResolution {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parsed = if s.contains(" - ") {
+            s.parse()
+                .map(AnyResolution::FiveMinute)
+                .or_else(|_| s.parse().map(AnyResolution::HalfHour))
+                .or_else(|_| s.parse().map(AnyResolution::Hour))
+        } else {
+            s.parse()
+                .map(AnyResolution::Date)
+                .or_else(|_| s.parse().map(AnyResolution::Month))
+                .or_else(|_| s.parse().map(AnyResolution::Quarter))
+                .or_else(|_| s.parse().map(AnyResolution::Year))
+                .or_else(|_| s.parse().map(AnyResolution::Minute))
+        };
+        parsed.map_err(|_| crate::Error::ParseCustom {
+            ty_name: "AnyResolution",
+            input: s.to_string(),
+        })
+    }
+}
+
+impl From<Minute> for AnyResolution {
+    fn from(p: Minute) -> AnyResolution {
+        AnyResolution::Minute(p)
+    }
+}
+impl From<FiveMinute> for AnyResolution {
+    fn from(p: FiveMinute) -> AnyResolution {
+        AnyResolution::FiveMinute(p)
+    }
+}
+impl From<HalfHour> for AnyResolution {
+    fn from(p: HalfHour) -> AnyResolution {
+        AnyResolution::HalfHour(p)
+    }
+}
+impl From<Hour> for AnyResolution {
+    fn from(p: Hour) -> AnyResolution {
+        AnyResolution::Hour(p)
+    }
+}
+impl From<Date> for AnyResolution {
+    fn from(p: Date) -> AnyResolution {
+        AnyResolution::Date(p)
+    }
+}
+impl From<Month> for AnyResolution {
+    fn from(p: Month) -> AnyResolution {
+        AnyResolution::Month(p)
+    }
+}
+impl From<Quarter> for AnyResolution {
+    fn from(p: Quarter) -> AnyResolution {
+        AnyResolution::Quarter(p)
+    }
+}
+impl From<Year> for AnyResolution {
+    fn from(p: Year) -> AnyResolution {
+        AnyResolution::Year(p)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> de::Deserialize<'de> for AnyResolution {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<AnyResolution, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for AnyResolution {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let s = self.to_string();
+        serializer.serialize_str(&s)
+    }
+}
+
+// A `TimeRange` where the resolution is only known at runtime
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "UncheckedAnyTimeRange"))]
+pub struct AnyTimeRange {
+    kind: ResolutionKind,
+    start: i64,
+    len: u32,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct UncheckedAnyTimeRange {
+    kind: ResolutionKind,
+    start: i64,
+    len: u32,
+}
+
+#[cfg(feature = "serde")]
+impl convert::TryFrom<UncheckedAnyTimeRange> for AnyTimeRange {
+    type Error = crate::Error;
+    fn try_from(unchecked: UncheckedAnyTimeRange) -> crate::Result<AnyTimeRange> {
+        AnyTimeRange::new(unchecked.kind, unchecked.start, unchecked.len)
+    }
+}
+
+impl AnyTimeRange {
+    pub fn new(kind: ResolutionKind, start: i64, len: u32) -> crate::Result<AnyTimeRange> {
+        let start_period = AnyResolution::from_parts(kind, start)?;
+        if len == 0 {
+            return Err(crate::Error::EmptyRange {
+                start: start_period.to_string(),
+                end: start_period.to_string(),
+            });
+        }
+        if len > 1 {
+            let end = start.checked_add(i64::from(len - 1)).ok_or(
+                crate::Error::InvalidMonotonic {
+                    ty_name: "AnyTimeRange",
+                    index: start,
+                },
+            )?;
+            AnyResolution::from_parts(kind, end)?;
+        }
+        Ok(AnyTimeRange {
+            kind: start_period.kind(),
+            start,
+            len,
+        })
+    }
+    pub fn from_start_end(start: AnyResolution, end: AnyResolution) -> Option<AnyTimeRange> {
+        if start.kind() != end.kind() || start > end {
+            return None;
+        }
+        let len = u32::try_from(end.to_monotonic() - start.to_monotonic() + 1).ok()?;
+        Some(AnyTimeRange {
+            kind: start.kind(),
+            start: start.to_monotonic(),
+            len,
+        })
+    }
+    pub fn kind(&self) -> ResolutionKind {
+        self.kind
+    }
+    pub fn len(&self) -> usize {
+        usize::try_from(self.len).unwrap()
+    }
+    // always false, as a range has at least one period
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn start(&self) -> AnyResolution {
+        AnyResolution::from_parts(self.kind, self.start).expect("Validated on construction")
+    }
+    pub fn end(&self) -> AnyResolution {
+        self.start().succ_n(self.len - 1)
+    }
+    // None if the resolution of the range is not `P`
+    pub fn to_time_range<P: TimeResolution + 'static>(&self) -> Option<TimeRange<P>> {
+        if ResolutionKind::of::<P>()? == self.kind {
+            Some(TimeRange::new(P::from_monotonic(self.start), self.len))
+        } else {
+            None
+        }
+    }
+}
+
+impl<P: TimeResolution + 'static> convert::TryFrom<TimeRange<P>> for AnyTimeRange {
+    type Error = crate::Error;
+    fn try_from(range: TimeRange<P>) -> crate::Result<AnyTimeRange> {
+        let kind = ResolutionKind::of::<P>().ok_or_else(|| {
+            crate::Error::UnknownResolution(range.start().name().into_owned())
+        })?;
+        Ok(AnyTimeRange {
+            kind,
+            start: range.start().to_monotonic(),
+            len: range.len,
+        })
+    }
+}
+
+impl fmt::Display for AnyTimeRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} to {} ({})", self.start(), self.end(), self.kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnyResolution, ResolutionKind};
+    use crate::{Date, DateResolution, HalfHour, Minute, Month, Quarter, Year};
+
+    #[test]
+    fn test_from_parts() {
+        for kind in &ResolutionKind::ALL {
+            let any = AnyResolution::from_parts(*kind, 1_000).unwrap();
+            assert_eq!(any.kind(), *kind);
+            assert_eq!(any.to_monotonic(), 1_000);
+            assert_eq!(any.succ().to_monotonic(), 1_001);
+            assert_eq!(any.pred_n(10).to_monotonic(), 990);
+        }
+        assert!(AnyResolution::from_parts(ResolutionKind::Date, i64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_display_from_str() {
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        let values = vec![
+            AnyResolution::from("2021-07-15 10:31".parse::<Minute>().unwrap()),
+            AnyResolution::from_parts(ResolutionKind::FiveMinute, 5_420_545).unwrap(),
+            AnyResolution::from("2021-07-15 10:30".parse::<HalfHour>().unwrap()),
+            AnyResolution::from_parts(ResolutionKind::Hour, 451_762).unwrap(),
+            AnyResolution::from(date),
+            AnyResolution::from(Month::from_date(date.start())),
+            AnyResolution::from(Quarter::from_date(date.start())),
+            AnyResolution::from(Year::from_date(date.start())),
+        ];
+        for value in values {
+            assert_eq!(value.to_string().parse::<AnyResolution>().unwrap(), value);
+        }
+        assert!("not a period".parse::<AnyResolution>().is_err());
+    }
+
+    #[test]
+    fn test_display_out_of_range() {
+        let date = AnyResolution::Date(Date::from_monotonic(i64::MAX));
+        assert_eq!(date.to_string(), format!("Date#{}", i64::MAX));
+        let minute = AnyResolution::Minute(Minute::from_monotonic(i64::MIN));
+        assert_eq!(minute.to_string(), format!("Minutes[Length:1]#{}", i64::MIN));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let value = AnyResolution::from(Quarter::from_date(chrono::NaiveDate::from_ymd_opt(2021, 7, 1).unwrap()));
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, "\"Q3-2021\"");
+        assert_eq!(serde_json::from_str::<AnyResolution>(&json).unwrap(), value);
+    }
+
+    #[test]
+    fn test_dyn_time_resolution() {
+        use crate::DynTimeResolution;
+        use std::any::TypeId;
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        let values: Vec<Box<dyn DynTimeResolution>> = vec![
+            Box::new(date),
+            Box::new(Month::from_date(date.start())),
+            Box::new("2021-07-15 10:30".parse::<HalfHour>().unwrap()),
+        ];
+        let names = values.iter().map(|v| v.name()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["Date", "Month", "Minutes[Length:30]"]);
+        assert_eq!(values[0].type_id(), TypeId::of::<Date>());
+        assert_eq!(values[2].type_id(), TypeId::of::<HalfHour>());
+        assert_eq!(values[1].succ_boxed().to_string(), "Aug-2021");
+        assert_eq!(values[0].pred_boxed().to_monotonic(), date.to_monotonic() - 1);
+        assert_eq!(
+            values[2].succ_boxed().start_datetime(),
+            date.start().and_hms_opt(11, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_any_time_range() {
+        use crate::{AnyTimeRange, Minutes, TimeRange};
+        use std::convert::TryFrom;
+        let start = Date::from_ymd(2021, 7, 15).unwrap();
+        let typed = TimeRange::new(start, 5);
+        let any = AnyTimeRange::try_from(typed).unwrap();
+        assert_eq!(any.kind(), ResolutionKind::Date);
+        assert_eq!(any.len(), 5);
+        assert_eq!(any.start(), AnyResolution::Date(start));
+        assert_eq!(any.end().to_string(), "2021-07-19");
+        assert_eq!(any.to_string(), "2021-07-15 to 2021-07-19 (Date)");
+        assert_eq!(any.to_time_range::<Date>(), Some(typed));
+        assert_eq!(any.to_time_range::<Month>(), None);
+        assert_eq!(
+            AnyTimeRange::from_start_end(any.start(), any.end()),
+            Some(any)
+        );
+        assert_eq!(
+            AnyTimeRange::new(ResolutionKind::Date, start.to_monotonic(), 5).unwrap(),
+            any
+        );
+        assert!(AnyTimeRange::new(ResolutionKind::Date, i64::MAX, 5).is_err());
+        assert!(matches!(
+            AnyTimeRange::new(ResolutionKind::Date, start.to_monotonic(), 0),
+            Err(crate::Error::EmptyRange { .. })
+        ));
+        assert!(AnyTimeRange::try_from(TimeRange::new(Minutes::<15>::from_monotonic(0), 2)).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_any_time_range_serde() {
+        use crate::AnyTimeRange;
+        let start = Date::from_ymd(2021, 7, 15).unwrap();
+        let any = AnyTimeRange::new(ResolutionKind::Date, start.to_monotonic(), 5).unwrap();
+        let json = serde_json::to_string(&any).unwrap();
+        assert_eq!(serde_json::from_str::<AnyTimeRange>(&json).unwrap(), any);
+        let invalid = format!(r#"{{"kind":"Date","start":{},"len":1}}"#, i64::MAX);
+        assert!(serde_json::from_str::<AnyTimeRange>(&invalid).is_err());
+        let empty = format!(r#"{{"kind":"Date","start":{},"len":0}}"#, start.to_monotonic());
+        assert!(serde_json::from_str::<AnyTimeRange>(&empty).is_err());
+    }
+
+    #[test]
+    fn test_descriptors() {
+        for kind in &ResolutionKind::ALL {
+            assert_eq!(kind.to_string().parse::<ResolutionKind>().unwrap(), *kind);
+        }
+        assert_eq!("Quarter".parse::<ResolutionKind>().unwrap(), ResolutionKind::Quarter);
+        assert_eq!(
+            "Minutes[Length:5]".parse::<ResolutionKind>().unwrap(),
+            ResolutionKind::FiveMinute
+        );
+        assert!("Minutes[Length:7]".parse::<ResolutionKind>().is_err());
+        assert!("Week[StartDay:Monday]".parse::<ResolutionKind>().is_err());
+
+        let five = AnyResolution::parse_descriptor("Minutes[Length:5]:2021-01-01 10:00").unwrap();
+        assert_eq!(five, AnyResolution::FiveMinute("2021-01-01 10:00".parse().unwrap()));
+        assert_eq!(AnyResolution::parse_descriptor(&five.to_descriptor_string()).unwrap(), five);
+        let quarter = AnyResolution::parse_descriptor("Quarter:Q1-2021").unwrap();
+        assert_eq!(quarter.kind(), ResolutionKind::Quarter);
+        assert_eq!(quarter.to_descriptor_string(), "Quarter:Q1-2021");
+        assert!(AnyResolution::parse_descriptor("Quarter").is_err());
+        assert!(AnyResolution::parse_descriptor("Quarter:2021-01-01 10:00").is_err());
+        assert!(AnyResolution::parse_descriptor("Minutes[Length:5]:2021-01-01 10:03").is_err());
+    }
+
+    #[test]
+    fn test_downcast() {
+        use crate::{ResolutionMapper, TimeRange, TimeResolution};
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        let any = AnyResolution::from(date);
+        assert!(any.is::<Date>());
+        assert!(!any.is::<Month>());
+        assert_eq!(any.downcast::<Date>(), Some(date));
+        assert_eq!(any.downcast::<Month>(), None);
+
+        struct NextThree;
+        impl ResolutionMapper for NextThree {
+            type Output = Vec<String>;
+            fn map<P: TimeResolution + 'static>(self, period: P) -> crate::Result<Vec<String>> {
+                Ok(TimeRange::new(period.succ(), 3).iter().map(|p| p.to_string()).collect())
+            }
+        }
+        assert_eq!(
+            any.try_map(NextThree).unwrap(),
+            vec!["2021-07-16", "2021-07-17", "2021-07-18"]
+        );
+        let month = AnyResolution::from(Month::from_date(date.start()));
+        assert_eq!(month.try_map(NextThree).unwrap(), vec!["Aug-2021", "Sep-2021", "Oct-2021"]);
+    }
+
+    #[test]
+    fn test_rescale_to() {
+        let date = Date::from_ymd(2021, 2, 15).unwrap();
+        let month = AnyResolution::from(Month::from_date(date.start()));
+
+        let days = month.rescale_to(ResolutionKind::Date).unwrap();
+        assert_eq!(days.len(), 28);
+        assert_eq!(days.start(), AnyResolution::Date(Date::from_ymd(2021, 2, 1).unwrap()));
+        assert_eq!(days.end(), AnyResolution::Date(Date::from_ymd(2021, 2, 28).unwrap()));
+
+        let half_hours = AnyResolution::from(date).rescale_to(ResolutionKind::HalfHour).unwrap();
+        assert_eq!(half_hours.len(), 48);
+        assert_eq!(half_hours.start().to_string(), "2021-02-15 00:00:00 - 2021-02-15 00:30:00");
+
+        let year = AnyResolution::from(date).rescale_to(ResolutionKind::Year).unwrap();
+        assert_eq!(year.len(), 1);
+        assert_eq!(year.start().to_string(), "2021");
+
+        let quarter = month.rescale_to(ResolutionKind::Quarter).unwrap();
+        assert_eq!(quarter.start().to_string(), "Q1-2021");
+        assert_eq!(month.rescale_to(ResolutionKind::Month).unwrap().start(), month);
+
+        let hour = AnyResolution::parse_descriptor("Minutes[Length:60]:2021-02-15 10:00").unwrap();
+        assert_eq!(hour.rescale_to(ResolutionKind::FiveMinute).unwrap().len(), 12);
+    }
+}
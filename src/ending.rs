@@ -0,0 +1,122 @@
+use crate::{Rounding, TimeResolution};
+#[cfg(feature = "serde")]
+use serde::{de, ser};
+use chrono::Timelike;
+use std::{fmt, str};
+
+const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+// A period labelled by the time it ends rather than the time it starts,
+// as used by sources with "hour ending" or "interval ending" labels.
+// A date-time at the end of a period refers to that period, so
+// `10:05` is the `FiveMinute` period from `10:00` to `10:05`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Ending<P: TimeResolution>(P);
+
+impl<P: TimeResolution> Ending<P> {
+    pub fn new(period: P) -> Ending<P> {
+        Ending(period)
+    }
+    pub fn period(&self) -> P {
+        self.0
+    }
+    // the label, which is the end of the period
+    pub fn naive_date_time(&self) -> chrono::NaiveDateTime {
+        self.0.succ().naive_date_time()
+    }
+    // the period ending at, or containing, the date-time
+    pub fn from_datetime(dt: chrono::NaiveDateTime) -> Ending<P> {
+        let period = P::from_datetime(dt);
+        if period.naive_date_time() == dt {
+            Ending(period.pred())
+        } else {
+            Ending(period)
+        }
+    }
+    // as `from_datetime` but errors unless the date-time is exactly the end of a period
+    pub fn from_datetime_strict(dt: chrono::NaiveDateTime) -> crate::Result<Ending<P>> {
+        P::from_datetime_with(dt, Rounding::Strict).map(|p| Ending(p.pred()))
+    }
+}
+
+impl<P: TimeResolution> From<P> for Ending<P> {
+    fn from(period: P) -> Ending<P> {
+        Ending(period)
+    }
+}
+
+impl<P: TimeResolution> fmt::Display for Ending<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dt = self.naive_date_time();
+        write!(f, "{} {:02}:{:02}", dt.date(), dt.hour(), dt.minute())
+    }
+}
+
+// Parses the end of the period (eg: `2021-07-15 10:05`), which must be on a period boundary
+impl<P: TimeResolution> str::FromStr for Ending<P> {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> crate::Result<Ending<P>> {
+        let dt = chrono::NaiveDateTime::parse_from_str(s, DATE_TIME_FORMAT)?;
+        Ending::from_datetime_strict(dt)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, P: TimeResolution> serde::Deserialize<'de> for Ending<P> {
+    fn deserialize<D>(deserializer: D) -> Result<Ending<P>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<P: TimeResolution> serde::Serialize for Ending<P> {
+    fn serialize<SER>(&self, serializer: SER) -> Result<SER::Ok, SER::Error>
+    where
+        SER: ser::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ending;
+    use crate::{Date, FiveMinute, Hour, TimeResolution};
+
+    #[test]
+    fn test_from_datetime() {
+        let period = "2021-07-15 10:00".parse::<FiveMinute>().unwrap();
+        let at_end = chrono::NaiveDate::from_ymd_opt(2021, 7, 15).unwrap().and_hms_opt(10, 5, 0).unwrap();
+        assert_eq!(Ending::<FiveMinute>::from_datetime(at_end).period(), period);
+        assert_eq!(Ending::<FiveMinute>::from_datetime(at_end - chrono::Duration::seconds(1)).period(), period);
+        assert_eq!(Ending::<FiveMinute>::from_datetime(at_end + chrono::Duration::seconds(1)).period(), period.succ());
+        assert_eq!(Ending::new(period).naive_date_time(), at_end);
+        assert!(Ending::<FiveMinute>::from_datetime_strict(at_end).is_ok());
+        assert!(Ending::<FiveMinute>::from_datetime_strict(at_end - chrono::Duration::seconds(1)).is_err());
+    }
+
+    #[test]
+    fn test_parse_display() {
+        // hour ending 01 is the first hour of the day
+        let he01 = "2021-07-15 01:00".parse::<Ending<Hour>>().unwrap();
+        assert_eq!(he01.period(), "2021-07-15 00:00".parse::<Hour>().unwrap());
+        assert_eq!(he01.to_string(), "2021-07-15 01:00");
+        let day = "2021-07-16 00:00".parse::<Ending<Date>>().unwrap();
+        assert_eq!(day.period(), Date::from_ymd(2021, 7, 15).unwrap());
+        assert!("2021-07-15 01:30".parse::<Ending<Hour>>().is_err());
+        assert!("2021-07-15".parse::<Ending<Hour>>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let he = Ending::new("2021-07-15 23:00".parse::<Hour>().unwrap());
+        let json = serde_json::to_string(&he).unwrap();
+        assert_eq!(json, "\"2021-07-16 00:00\"");
+        assert_eq!(serde_json::from_str::<Ending<Hour>>(&json).unwrap(), he);
+    }
+}
@@ -1,13 +1,13 @@
-use any::TypeId;
-use chrono::Datelike;
-use serde::{
-    de,
-    ser::{self, SerializeStruct},
-};
-use std::{any, num, collections, convert::TryFrom, fmt, result};
+#![cfg_attr(feature = "step_trait", feature(step_trait))]
+#[cfg(feature = "serde")]
+use serde::de;
+use std::{any, borrow, cmp, collections, convert::TryFrom, fmt, iter, marker, mem, num, ops, str};
 
 mod minutes; 
-pub use minutes::Minutes;
+pub use minutes::{Minutes, UncheckedMinutes};
+#[cfg(feature = "rkyv")]
+pub use minutes::ArchivedMinutes;
+use minutes::DynMinutes;
 
 pub type Minute = Minutes<1>;
 pub type FiveMinute = Minutes<5>;
@@ -16,12 +16,95 @@ pub type Hour = Minutes<60>;
 
 mod date;
 pub use date::Date;
+#[cfg(feature = "rkyv")]
+pub use date::ArchivedDate;
 mod month;
-pub use month::Month;
+pub use month::{Month, MonthEndPolicy};
+#[cfg(feature = "rkyv")]
+pub use month::ArchivedMonth;
 mod quarter;
 pub use quarter::Quarter;
+#[cfg(feature = "rkyv")]
+pub use quarter::ArchivedQuarter;
 mod year;
 pub use year::Year;
+#[cfg(feature = "rkyv")]
+pub use year::ArchivedYear;
+mod calendar;
+pub use calendar::{
+    business_days_between, BusinessDays, BusinessTimeRange, HolidayCalendar, SimpleCalendar, WeekStartDay, WeekendDef,
+};
+#[cfg(feature = "step_trait")]
+mod step;
+mod dynamic;
+pub use dynamic::{
+    AnyResolution, AnyTimeRange, DynTimeResolution, ResolutionKind, ResolutionMapper,
+};
+mod registry;
+pub use registry::ResolutionRegistry;
+mod series;
+#[cfg(any(test, feature = "test_utils"))]
+pub mod test_utils;
+mod validate;
+mod open_range;
+mod parse;
+pub use parse::ParseOptions;
+pub use open_range::OpenTimeRange;
+mod diff;
+pub use diff::{diff, SeriesDiff};
+mod planner;
+pub use planner::FetchPlanner;
+mod coverage;
+pub use coverage::CoverageMap;
+pub use validate::{check_complete, gaps_in, CompletenessReport, Gaps};
+pub mod markets;
+mod ending;
+pub use ending::Ending;
+mod recurrence;
+pub use recurrence::{
+    next_occurrence_after, occurrences_between, Every, NthDayOf, NthWeekdayOf, Occurrences, Recurrence,
+};
+mod cron;
+pub use cron::Cron;
+mod rrule;
+pub use rrule::{Frequency, RRule, RRuleIter};
+pub use series::{fold_by_resolution, group_by_resolution, Aggregation, Edges, Rolling, TimeSeries};
+#[cfg(feature = "arrow")]
+mod arrow_impls;
+#[cfg(feature = "arrow")]
+pub use arrow_impls::{to_record_batch, ArrowResolution};
+#[cfg(feature = "sqlx")]
+mod sqlx_impls;
+#[cfg(feature = "postgres")]
+mod postgres_impls;
+#[cfg(feature = "postgres")]
+pub use postgres_impls::PostgresResolution;
+#[cfg(feature = "rusqlite")]
+mod rusqlite_impls;
+#[cfg(feature = "rusqlite")]
+pub use rusqlite_impls::Monotonic;
+#[cfg(feature = "diesel")]
+mod diesel_impls;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+#[cfg(feature = "quickcheck")]
+mod quickcheck_impls;
+#[cfg(feature = "rand")]
+mod rand_impls;
+#[cfg(feature = "rand")]
+pub use rand_impls::UniformPeriod;
+#[cfg(feature = "wasm")]
+mod wasm_impls;
+#[cfg(feature = "wasm")]
+pub use wasm_impls::JsDateResolution;
+#[cfg(feature = "utoipa")]
+mod utoipa_impls;
+#[cfg(feature = "defmt")]
+mod defmt_impls;
+#[cfg(feature = "serde_with")]
+mod serde_with_impls;
+#[cfg(feature = "serde_with")]
+pub use serde_with_impls::{AsMonotonic, AsPeriodString};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -37,16 +120,147 @@ pub enum Error {
     ParseDate(#[from] chrono::ParseError),
     #[error("Error parsing {ty_name} from input: {input}")]
     ParseCustom { ty_name: &'static str, input: String },
+    #[error("Monotonic index {index} is outside the representable range for {ty_name}")]
+    InvalidMonotonic { ty_name: &'static str, index: i64 },
+    #[error("The result of period arithmetic is outside of the representable range")]
+    OutOfRange,
+    #[error("Adding {0} to a period is outside of the representable range")]
+    DurationOverflow(chrono::Duration),
+    #[error("{0} is not on a period boundary")]
+    NotOnBoundary(chrono::NaiveDateTime),
+    #[error("Resolution {0} has not been registered")]
+    UnknownResolution(String),
+    #[error("Interval number {number} is outside of 1..={max}")]
+    InvalidIntervalNumber { number: u32, max: u32 },
+    #[error("{value} is not a valid {component}")]
+    InvalidComponent { component: &'static str, value: u32 },
+    #[error("{input} cannot be represented at the {resolution} resolution")]
+    IncompatibleResolution { resolution: String, input: String },
+    #[error("The range from {start} to {end} (exclusive) is empty")]
+    EmptyRange { start: String, end: String },
+    #[cfg(feature = "arrow")]
+    #[error("Arrow error: {0}")]
+    Arrow(#[from] arrow::error::ArrowError),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+// Periods compare as equal to a date or time that they contain, and are
+// otherwise ordered by whether they finish before or start after it
+fn cmp_date<P: DateResolution>(period: &P, date: &chrono::NaiveDate) -> cmp::Ordering {
+    if period.end() < *date {
+        cmp::Ordering::Less
+    } else if period.start() > *date {
+        cmp::Ordering::Greater
+    } else {
+        cmp::Ordering::Equal
+    }
+}
+
+fn cmp_date_time<P: TimeResolution>(period: &P, date_time: &chrono::NaiveDateTime) -> cmp::Ordering {
+    if period.succ().naive_date_time() <= *date_time {
+        cmp::Ordering::Less
+    } else if period.naive_date_time() > *date_time {
+        cmp::Ordering::Greater
+    } else {
+        cmp::Ordering::Equal
+    }
+}
+
+// The monotonic indexes of periods between 1900 and 2100, which is where
+// generated periods are taken from. This keeps all of the derived dates
+// and times representable.
+#[cfg(any(test, feature = "proptest", feature = "quickcheck", feature = "test_utils"))]
+fn arbitrary_bounds<P: TimeResolution>() -> (i64, i64) {
+    let start = chrono::NaiveDate::from_ymd_opt(1900, 1, 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .expect("Valid date");
+    let end = chrono::NaiveDate::from_ymd_opt(2100, 1, 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .expect("Valid date");
+    (
+        P::from_datetime(start).to_monotonic(),
+        P::from_datetime(end).to_monotonic(),
+    )
+}
+
+// Moves the start of the period by a number of months, giving the period containing the result,
+// so adding less than a whole period (eg: `Quarter + Months::new(1)`) doesn't change it.
+// Panics when the result is outside of chrono's range, as chrono does.
+macro_rules! impl_months_ops {
+    ($($ty:ty),*) => {
+        $(
+            impl ops::Add<chrono::Months> for $ty {
+                type Output = $ty;
+                fn add(self, months: chrono::Months) -> $ty {
+                    let date = self.start().checked_add_months(months).expect("Not pre/post historic");
+                    <$ty>::from_date(date)
+                }
+            }
+            impl ops::Sub<chrono::Months> for $ty {
+                type Output = $ty;
+                fn sub(self, months: chrono::Months) -> $ty {
+                    let date = self.start().checked_sub_months(months).expect("Not pre/post historic");
+                    <$ty>::from_date(date)
+                }
+            }
+        )*
+    };
+}
+
+impl_months_ops!(Month, Quarter, Year);
+
+// chrono only supports locales when formatting a `DateTime`, not a `NaiveDate`
+#[cfg(feature = "locale")]
+fn format_localized(date: chrono::NaiveDate, fmt: &str, locale: chrono::Locale) -> String {
+    let midnight = date.and_hms_opt(0, 0, 0).expect("Midnight is always valid");
+    chrono::DateTime::<chrono::Utc>::from_utc(midnight, chrono::Utc)
+        .format_localized(fmt, locale)
+        .to_string()
+}
+
+fn check_monotonic<P: TimeResolution>(
+    ty_name: &'static str,
+    idx: i64,
+    min: P,
+    max: P,
+) -> Result<P> {
+    if (min.to_monotonic()..=max.to_monotonic()).contains(&idx) {
+        Ok(P::from_monotonic(idx))
+    } else {
+        Err(Error::InvalidMonotonic { ty_name, index: idx })
+    }
+}
+
+// How to choose a period for a date-time that isn't exactly at the start of one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    // the period containing the date-time
+    Floor,
+    // the first period starting at or after the date-time
+    Ceil,
+    // whichever period start is closest, with ties going to the later period
+    Nearest,
+    // error unless the date-time is exactly the start of a period
+    Strict,
+}
+
 pub trait TimeResolutionZone<Z: chrono::TimeZone>: TimeResolution 
 {
     fn date_time(&self) -> chrono::DateTime<Z>;
     fn get_zone() -> Z;
 }
 
+// With the `serde` feature, every resolution must be serializable
+#[cfg(feature = "serde")]
+pub trait MaybeSerde: serde::Serialize + de::DeserializeOwned {}
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize + de::DeserializeOwned> MaybeSerde for T {}
+#[cfg(not(feature = "serde"))]
+pub trait MaybeSerde {}
+#[cfg(not(feature = "serde"))]
+impl<T> MaybeSerde for T {}
+
 pub trait TimeResolution:
     Send
     + Sync
@@ -59,9 +273,20 @@ pub trait TimeResolution:
     + PartialOrd
     + Ord
     + Sized
-    + serde::Serialize
-    + de::DeserializeOwned
+    + MaybeSerde
 {
+    // a description of the resolution, eg: `Minutes[Length:5]`
+    fn name(&self) -> borrow::Cow<'static, str>;
+    #[deprecated(note = "`name` now returns a `Cow`, use `name().into_owned()` if a `String` is needed")]
+    fn name_string(&self) -> String {
+        self.name().into_owned()
+    }
+
+    // writes the period as per `Display`, for reusing a buffer when rendering many periods
+    fn write_to<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        write!(w, "{}", self)
+    }
+
     fn succ(&self) -> Self {
         self.succ_n(1)
     }
@@ -71,8 +296,15 @@ pub trait TimeResolution:
 
     // we choose i64 rather than u64
     // as the behaviour on subtraction is nicer!
+    //
+    // The monotonic index counts periods since the type's
+    // `monotonic_epoch`. These mappings are stable and will
+    // never change, so it is safe to persist the raw values.
     fn to_monotonic(&self) -> i64;
     fn from_monotonic(idx: i64) -> Self;
+    // as `from_monotonic` but checks that the period is able
+    // to be represented by chrono
+    fn try_from_monotonic(idx: i64) -> Result<Self>;
 
     // the default impls are probably inefficient
     // makes sense to require just the n
@@ -80,9 +312,165 @@ pub trait TimeResolution:
     fn succ_n(&self, n: u32) -> Self;
     fn pred_n(&self, n: u32) -> Self;
 
+    // as `succ_n` and `pred_n`, but error rather than overflowing
+    // or producing a period outside of chrono's range
+    fn checked_succ_n(&self, n: u32) -> Result<Self> {
+        let idx = self.to_monotonic().checked_add(i64::from(n)).ok_or(Error::OutOfRange)?;
+        Self::try_from_monotonic(idx).map_err(|_| Error::OutOfRange)
+    }
+    fn checked_pred_n(&self, n: u32) -> Result<Self> {
+        let idx = self.to_monotonic().checked_sub(i64::from(n)).ok_or(Error::OutOfRange)?;
+        Self::try_from_monotonic(idx).map_err(|_| Error::OutOfRange)
+    }
+    // as `succ_n` and `pred_n`, but stopping at the last (or first)
+    // period which chrono can represent
+    fn saturating_succ_n(&self, n: u32) -> Self {
+        self.checked_succ_n(n).unwrap_or_else(|_| Self::max_representable())
+    }
+    fn saturating_pred_n(&self, n: u32) -> Self {
+        self.checked_pred_n(n).unwrap_or_else(|_| Self::min_representable())
+    }
+    // the first and last periods whose start chrono can represent
+    fn min_representable() -> Self {
+        let first = Self::from_datetime(chrono::NaiveDateTime::MIN);
+        if first.try_naive_date_time().is_ok() {
+            first
+        } else {
+            first.succ()
+        }
+    }
+    fn max_representable() -> Self {
+        Self::from_datetime(chrono::NaiveDateTime::MAX)
+    }
+    fn checked_between(&self, other: Self) -> Result<i64> {
+        other
+            .to_monotonic()
+            .checked_sub(self.to_monotonic())
+            .ok_or(Error::OutOfRange)
+    }
+
+    // never-ending iterators starting from (and including) this period,
+    // to be bounded by the caller (eg: with `take_while`)
+    fn iter_from(self) -> iter::Successors<Self, fn(&Self) -> Option<Self>> {
+        iter::successors(Some(self), |p| Some(p.succ()))
+    }
+    fn iter_back_from(self) -> iter::Successors<Self, fn(&Self) -> Option<Self>> {
+        iter::successors(Some(self), |p| Some(p.pred()))
+    }
+
+    // the first later period satisfying `f`, if one is representable
+    // (eg: `month.next_whose(|m| m.month_num() == 7)`)
+    fn next_whose<F: Fn(Self) -> bool>(&self, f: F) -> Option<Self> {
+        next_occurrence_after(&f, *self)
+    }
+
+    // `other` minus `self` in periods, so positive when `other` is later.
+    // `periods_until` and `periods_since` are clearer at call sites.
     fn between(&self, other: Self) -> i64;
+    // the number of periods from this one until `later`, negative if `later` is actually earlier
+    fn periods_until(&self, later: Self) -> i64 {
+        self.between(later)
+    }
+    // the number of periods from `earlier` until this one, negative if `earlier` is actually later
+    fn periods_since(&self, earlier: Self) -> i64 {
+        earlier.between(*self)
+    }
+    // the distance between the periods, regardless of their order
+    fn abs_between(&self, other: Self) -> u64 {
+        self.between(other).unsigned_abs()
+    }
+
+    // the coarser period which this one is entirely within, erroring
+    // when this period crosses a boundary between `C` periods
+    fn containing<C: TimeResolution>(&self) -> Result<C> {
+        let coarse = C::from_datetime(self.naive_date_time());
+        let end = self.succ().naive_date_time();
+        if end <= coarse.succ().naive_date_time() {
+            Ok(coarse)
+        } else {
+            Err(Error::NotOnBoundary(end))
+        }
+    }
+
+    // a stable shard number in `0..num_shards`, from the monotonic index,
+    // so it can be reproduced in other languages (as a non-negative modulo)
+    fn shard(&self, num_shards: num::NonZeroU32) -> u32 {
+        u32::try_from(self.to_monotonic().rem_euclid(i64::from(num_shards.get())))
+            .expect("Always less than num_shards")
+    }
+
+    // a hash which is the same across processes, platforms and releases, for cache keys
+    // and the like. This is 64-bit FNV-1a over the bytes of `name`, a zero byte, and the
+    // little-endian bytes of the monotonic index, so it can be reproduced in other languages
+    fn stable_hash_u64(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+        self.name()
+            .bytes()
+            .chain(iter::once(0))
+            .chain(self.to_monotonic().to_le_bytes())
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+            })
+    }
 
     fn naive_date_time(&self) -> chrono::NaiveDateTime;
+    // the period containing the date-time
+    fn from_datetime(dt: chrono::NaiveDateTime) -> Self;
+    // the period containing a unix timestamp (seconds since 1970-01-01 00:00 UTC), panics when
+    // the timestamp is outside of chrono's range
+    fn from_utc_timestamp(secs: i64) -> Self {
+        let dt = chrono::NaiveDateTime::from_timestamp_opt(secs, 0).expect("Timestamp within chrono's range");
+        Self::from_datetime(dt)
+    }
+    // the unix timestamp of the start of the period
+    fn start_timestamp(&self) -> i64 {
+        self.naive_date_time().timestamp()
+    }
+    // parses the same formats as `FromStr`, with `options` deciding what else is accepted
+    fn parse_with(s: &str, options: ParseOptions) -> Result<Self>
+    where
+        Self: str::FromStr<Err = Error>,
+    {
+        parse::parse_with(s, options)
+    }
+    fn from_datetime_with(dt: chrono::NaiveDateTime, rounding: Rounding) -> Result<Self> {
+        let floor = Self::from_datetime(dt);
+        let start = floor.naive_date_time();
+        if start == dt {
+            return Ok(floor);
+        }
+        match rounding {
+            Rounding::Floor => Ok(floor),
+            Rounding::Ceil => Ok(floor.succ()),
+            Rounding::Nearest => {
+                let next = floor.succ();
+                if dt - start < next.naive_date_time() - dt {
+                    Ok(floor)
+                } else {
+                    Ok(next)
+                }
+            }
+            Rounding::Strict => Err(Error::NotOnBoundary(dt)),
+        }
+    }
+    // as `naive_date_time` but returns an error rather than
+    // panicking when the period is outside of chrono's range
+    fn try_naive_date_time(&self) -> Result<chrono::NaiveDateTime> {
+        Self::try_from_monotonic(self.to_monotonic()).map(|p| p.naive_date_time())
+    }
+    // offsets the start of the period by `duration`, then uses `rounding`
+    // to choose a period if it is no longer on a period boundary
+    // (eg: a `HalfHour` plus 75 minutes with `Rounding::Floor`)
+    fn add_duration(&self, duration: chrono::Duration, rounding: Rounding) -> Result<Self> {
+        let dt = self
+            .try_naive_date_time()?
+            .checked_add_signed(duration)
+            .ok_or(Error::DurationOverflow(duration))?;
+        let period = Self::from_datetime_with(dt, rounding)?;
+        period.try_naive_date_time()?;
+        Ok(period)
+    }
 }
 
 // This trait exists to be able to provide a trait
@@ -94,6 +482,49 @@ pub trait SubDateResolution: TimeResolution {
     fn last_on_day(day: chrono::NaiveDate) -> Self {
         Self::first_on_day(day + chrono::Duration::days(1)).pred()
     }
+    // how many of the resolutions units occur in a single day
+    fn periods_per_day() -> u32;
+    // 0-based index of this period within the day it occurs on
+    fn sub_index_on_day(&self) -> u32 {
+        u32::try_from(Self::first_on_day(self.occurs_on_date()).between(*self))
+            .expect("Always between 0 and the number of periods in a day")
+    }
+    fn periods_on_day(day: chrono::NaiveDate) -> TimeRange<Self> {
+        TimeRange::from_start_end(Self::first_on_day(day), Self::last_on_day(day))
+            .expect("Will always have at least one within the day")
+    }
+    fn time_of_day(&self) -> chrono::NaiveTime {
+        self.naive_date_time().time()
+    }
+    // exclusive, so this is midnight for the last period of a day
+    fn end_time_of_day(&self) -> chrono::NaiveTime {
+        self.succ().naive_date_time().time()
+    }
+    // the period starting at `time` on `date`, erroring unless `time` is the start of a period
+    // (eg: 10:15 for a `HalfHour`)
+    fn on_date_at(date: chrono::NaiveDate, time: chrono::NaiveTime) -> Result<Self> {
+        Self::from_datetime_with(date.and_time(time), Rounding::Strict)
+    }
+
+    // As above, for conventions where the day starts at `offset` from midnight, eg: a gas day
+    // starting at 06:00. A negative offset starts the day on the previous calendar date.
+    fn occurs_on_date_with_offset(&self, offset: chrono::Duration) -> chrono::NaiveDate {
+        (self.naive_date_time() - offset).date()
+    }
+    fn first_on_day_with_offset(day: chrono::NaiveDate, offset: chrono::Duration) -> Self {
+        let midnight = day.and_hms_opt(0, 0, 0).expect("Midnight is always valid");
+        Self::from_datetime(midnight + offset)
+    }
+    fn last_on_day_with_offset(day: chrono::NaiveDate, offset: chrono::Duration) -> Self {
+        Self::first_on_day_with_offset(day + chrono::Duration::days(1), offset).pred()
+    }
+    fn periods_on_day_with_offset(day: chrono::NaiveDate, offset: chrono::Duration) -> TimeRange<Self> {
+        TimeRange::from_start_end(
+            Self::first_on_day_with_offset(day, offset),
+            Self::last_on_day_with_offset(day, offset),
+        )
+        .expect("Will always have at least one within the day")
+    }
 }
 
 // This trait exists to be able to provide a trait
@@ -101,6 +532,29 @@ pub trait SubDateResolution: TimeResolution {
 // Due to this it can have a number of useful methods
 pub trait DateResolution: TimeResolution {
     fn start(&self) -> chrono::NaiveDate;
+    // the period starting on `date`, erroring unless `date` is the first day of a period
+    // (eg: the 1st of a month), whereas `from_date` gives the period containing it
+    fn from_start(date: chrono::NaiveDate) -> Result<Self> {
+        let midnight = date.and_hms_opt(0, 0, 0).expect("Midnight is always valid");
+        Self::from_datetime_with(midnight, Rounding::Strict)
+    }
+    // as `start` but returns an error rather than
+    // panicking when the period is outside of chrono's range
+    fn try_start(&self) -> Result<chrono::NaiveDate> {
+        Self::try_from_monotonic(self.to_monotonic()).map(|p| p.start())
+    }
+
+    // the components of the start of the period, so the same code can
+    // describe any date resolution (eg: a quarter's month is its first month)
+    fn year_num(&self) -> i32 {
+        chrono::Datelike::year(&self.start())
+    }
+    fn month_num(&self) -> u32 {
+        chrono::Datelike::month(&self.start())
+    }
+    fn day_num(&self) -> u32 {
+        chrono::Datelike::day(&self.start())
+    }
 
     // free
     fn format<'a>(
@@ -121,32 +575,54 @@ pub trait DateResolution: TimeResolution {
     fn days(&self) -> collections::BTreeSet<chrono::NaiveDate> {
         (0..)
             .map(|n| self.start() + chrono::Duration::days(n))
-            .filter(|d| d <= &self.end())
+            .take_while(|d| d <= &self.end())
             .collect()
     }
-    fn business_days(
-        &self,
-        weekend: collections::HashSet<chrono::Weekday>,
-        holidays: collections::BTreeSet<chrono::NaiveDate>,
-    ) -> collections::BTreeSet<chrono::NaiveDate> {
-        let base_days = (0..)
-            .map(|n| self.start() + chrono::Duration::days(n))
-            .filter(|d| d <= &self.end())
-            .filter(|d| !weekend.contains(&d.weekday()))
-            .collect::<collections::BTreeSet<_>>();
-        base_days.difference(&holidays).copied().collect()
+    fn business_days<'a, C: HolidayCalendar>(&self, calendar: &'a C) -> BusinessDays<'a, C> {
+        let days = TimeRange::from_start_end(Date::from(self.start()), Date::from(self.end()))
+            .expect("Start is always before end");
+        BusinessDays::new(days.iter(), calendar)
+    }
+    fn num_business_days<C: HolidayCalendar>(&self, calendar: &C) -> usize {
+        self.business_days(calendar).count()
     }
 }
 
 
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+// Always has at least one period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "UncheckedTimeRange<P>", bound(deserialize = "P: de::DeserializeOwned")))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes, compare(PartialEq)))]
 pub struct TimeRange<P: TimeResolution> {
+    start: P,
+    len: u32,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct UncheckedTimeRange<P: TimeResolution> {
     #[serde(bound(deserialize = "P: de::DeserializeOwned"))]
     start: P,
     len: u32,
 }
 
+#[cfg(feature = "serde")]
+impl<P: TimeResolution> TryFrom<UncheckedTimeRange<P>> for TimeRange<P> {
+    type Error = Error;
+    fn try_from(unchecked: UncheckedTimeRange<P>) -> Result<TimeRange<P>> {
+        if unchecked.len == 0 {
+            return Err(Error::EmptyRange {
+                start: unchecked.start.to_string(),
+                end: unchecked.start.to_string(),
+            });
+        }
+        Ok(TimeRange::new(unchecked.start, unchecked.len))
+    }
+}
+
 pub trait AsDateRange {
     fn as_date_range(&self) -> TimeRange<Date>;
 }
@@ -232,14 +708,16 @@ impl<P: DateResolution> TimeRange<P> {
 
 impl<P: TimeResolution> TimeRange<P> {
     // use with the cacheresponse!
-    pub fn from_indexes(idx: &[i64]) -> Result<TimeRange<P>> {
+    pub fn from_indexes(_idx: &[i64]) -> Result<TimeRange<P>> {
         todo!()
     }
     pub fn to_indexes(&self) -> collections::BTreeSet<i64> {
         self.iter().map(|p| p.to_monotonic()).collect()
     }
 
+    // panics if `len` is zero, see `from_start_end_exclusive` for a fallible alternative
     pub fn new(start: P, len: u32) -> TimeRange<P> {
+        assert!(len > 0, "A TimeRange must have at least one period");
         TimeRange { start, len }
     }
     pub fn index_of(&self, point: P) -> Option<usize> {
@@ -260,17 +738,54 @@ impl<P: TimeResolution> TimeRange<P> {
             None
         }
     }
+    // `end` is one past the last period of the range, as in a half-open interval
+    pub fn from_start_end_exclusive(start: P, end: P) -> Result<TimeRange<P>> {
+        if end <= start {
+            return Err(Error::EmptyRange {
+                start: start.to_string(),
+                end: end.to_string(),
+            });
+        }
+        Ok(TimeRange {
+            start,
+            len: u32::try_from(start.between(end)).map_err(|_| Error::OutOfRange)?,
+        })
+    }
+    // the periods covering `duration` from the start of `start`, with `rounding` deciding
+    // whether a partial period at the end is included (`Ceil`), dropped (`Floor`) or an error (`Strict`).
+    // Errors when no periods are left, eg: 10 minutes of half hours with `Floor`.
+    pub fn from_start_duration(start: P, duration: chrono::Duration, rounding: Rounding) -> Result<TimeRange<P>> {
+        let end = start
+            .try_naive_date_time()?
+            .checked_add_signed(duration)
+            .ok_or(Error::DurationOverflow(duration))?;
+        TimeRange::from_start_end_exclusive(start, P::from_datetime_with(end, rounding)?)
+    }
     pub fn len(&self) -> usize {
         usize::try_from(self.len).unwrap()
     }
+    // always false, as a range has at least one period
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
 
     pub fn intersect(&self, other: TimeRange<P>) -> Option<TimeRange<P>> {
         let max_start = self.start().max(other.start());
         let min_end = self.end().min(other.end());
         TimeRange::from_start_end(max_start, min_end)
     }
+    // the periods of `other` which overlap with the time covered by this range,
+    // eg: the half hours of a range which fall within a range of days
+    pub fn intersect_with<F: TimeResolution>(&self, other: &TimeRange<F>) -> Option<TimeRange<F>> {
+        if self.is_empty() || other.is_empty() {
+            return None;
+        }
+        let first = F::from_datetime(self.start.naive_date_time());
+        let last = F::from_datetime(self.end().succ().naive_date_time() - chrono::Duration::nanoseconds(1));
+        TimeRange::from_start_end(first, last)?.intersect(*other)
+    }
     pub fn union(&self, other: TimeRange<P>) -> Option<TimeRange<P>> {
-        if let Some(_) = self.intersect(other) {
+        if self.intersect(other).is_some() {
             let min_start = self.start().min(other.start());
             let max_end = self.end().max(other.end());
             TimeRange::from_start_end(min_start, max_end)
@@ -279,7 +794,7 @@ impl<P: TimeResolution> TimeRange<P> {
         }
     }
 
-    pub fn difference(&self, other: TimeRange<P>) -> (Option<TimeRange<P>>, Option<TimeRange<P>>) {
+    pub fn difference(&self, _other: TimeRange<P>) -> (Option<TimeRange<P>>, Option<TimeRange<P>>) {
         todo!()
     }
     pub fn compare(&self, other: TimeRange<P>) -> TimeRangeComparison {
@@ -302,95 +817,416 @@ impl<P: TimeResolution> TimeRange<P> {
             len: u32::try_from(set.len()).ok()?,
         })
     }
+    // the smallest range containing every period, in any order, ignoring any gaps.
+    // `None` when there are no periods or the span is more than `u32::MAX` periods.
+    pub fn envelope(periods: impl IntoIterator<Item = P>) -> Option<TimeRange<P>> {
+        let mut periods = periods.into_iter();
+        let first = periods.next()?;
+        let (start, end) = periods.fold((first, first), |(start, end), p| (start.min(p), end.max(p)));
+        let len = u32::try_from(start.between(end)).ok()?.checked_add(1)?;
+        Some(TimeRange::new(start, len))
+    }
+    // as `envelope`, along with the runs of periods within it which weren't seen
+    pub fn envelope_with_gaps(periods: impl IntoIterator<Item = P>) -> Option<(TimeRange<P>, Vec<TimeRange<P>>)> {
+        let periods = periods.into_iter().collect::<collections::BTreeSet<P>>();
+        let envelope = TimeRange::envelope(periods.iter().copied())?;
+        Some((envelope, gaps_in(periods.into_iter()).collect()))
+    }
     pub fn start(&self) -> P {
         self.start
     }
     pub fn end(&self) -> P {
-        self.start.succ_n(self.len)
+        self.start.succ_n(self.len - 1)
     }
     pub fn set(&self) -> collections::BTreeSet<P> {
         self.iter().collect()
     }
+    // the periods of the range for each shard, indexed by shard number
+    pub fn split_by_shard(&self, num_shards: num::NonZeroU32) -> Vec<Vec<P>> {
+        let mut shards = vec![Vec::new(); num_shards.get() as usize];
+        if !self.is_empty() {
+            for period in self.iter() {
+                shards[period.shard(num_shards) as usize].push(period);
+            }
+        }
+        shards
+    }
+    // every period from the start of the range onwards, ignoring its end
+    pub fn iter_from_start_unbounded(&self) -> iter::Successors<P, fn(&P) -> Option<P>> {
+        self.start.iter_from()
+    }
+    // the same time as a range of coarser periods, erroring unless the range starts
+    // and ends on `C` boundaries and none of its periods cross a `C` boundary.
+    pub fn try_rescale<C: TimeResolution>(&self) -> Result<TimeRange<C>> {
+        let start = self.start.naive_date_time();
+        let end = self.end().succ().naive_date_time();
+        let first = C::from_datetime(start);
+        if first.naive_date_time() != start {
+            return Err(Error::NotOnBoundary(start));
+        }
+        let mut last = first;
+        for period in self.iter() {
+            last = period.containing::<C>()?;
+        }
+        if last.succ().naive_date_time() != end {
+            return Err(Error::NotOnBoundary(end));
+        }
+        Ok(TimeRange::from_start_end(first, last).expect("Periods of the range are in order"))
+    }
+    // every `step`th period, starting with the first
+    pub fn iter_step_by(&self, step: num::NonZeroU32) -> iter::StepBy<TimeRangeIter<P>> {
+        self.iter().step_by(step.get() as usize)
+    }
+    // one period of the range for each `C` period that it overlaps
+    pub fn subsample<C: TimeResolution>(&self, representative: Representative) -> Subsample<P, C> {
+        Subsample {
+            periods: self.iter().peekable(),
+            representative,
+            previous: None,
+        }
+    }
+    // for each period, the index of the `C` containing it, counting from the `C` containing the start of
+    // the range. Used for aggregating values stored in a `Vec` in the same order as the range.
+    pub fn coarse_index_map<C: TimeResolution>(&self) -> Vec<usize> {
+        let first = C::from_datetime(self.start.naive_date_time());
+        self.iter()
+            .map(|p| {
+                let index = first.between(C::from_datetime(p.naive_date_time()));
+                usize::try_from(index).expect("Periods are in order, so always at or after the first")
+            })
+            .collect()
+    }
+    // the start and exclusive end of each period, eg: for plotting as bars
+    pub fn to_bounds_vec(&self) -> Vec<(chrono::NaiveDateTime, chrono::NaiveDateTime)> {
+        self.iter()
+            .map(|p| (p.naive_date_time(), p.succ().naive_date_time()))
+            .collect()
+    }
+    // the start of each period followed by the exclusive end of the last, eg: for histogram bins.
+    pub fn to_break_points(&self) -> Vec<chrono::NaiveDateTime> {
+        self.iter()
+            .map(|p| p.naive_date_time())
+            .chain(iter::once(self.end().succ().naive_date_time()))
+            .collect()
+    }
     pub fn iter(&self) -> TimeRangeIter<P> {
+        let start = self.start.to_monotonic();
         TimeRangeIter {
-            current: self.start(),
-            end: self.end(),
+            current: start,
+            end: start + i64::from(self.len),
+            resolution: marker::PhantomData,
         }
     }
 }
 
+// Walks the monotonic indexes, only converting to `P` for the items returned
+#[derive(Debug, Clone)]
 pub struct TimeRangeIter<P: TimeResolution> {
-    current: P,
-    end: P,
+    current: i64,
+    // exclusive
+    end: i64,
+    resolution: marker::PhantomData<P>,
+}
+
+impl<P: TimeResolution> TimeRangeIter<P> {
+    fn remaining(&self) -> usize {
+        usize::try_from(self.end - self.current).expect("Never more than u32::MAX remaining")
+    }
 }
 
 impl<P: TimeResolution> Iterator for TimeRangeIter<P> {
     type Item = P;
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current <= self.end {
-            let ret = self.current.clone();
-            self.current = self.current.succ();
+        if self.current < self.end {
+            let ret = P::from_monotonic(self.current);
+            self.current += 1;
             Some(ret)
         } else {
             None
         }
     }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining();
+        (remaining, Some(remaining))
+    }
+    fn count(self) -> usize {
+        self.remaining()
+    }
+    fn last(self) -> Option<Self::Item> {
+        if self.current < self.end {
+            Some(P::from_monotonic(self.end - 1))
+        } else {
+            None
+        }
+    }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if n < self.remaining() {
+            self.current += n as i64;
+            self.next()
+        } else {
+            self.current = self.end;
+            None
+        }
+    }
+}
+
+impl<P: TimeResolution> DoubleEndedIterator for TimeRangeIter<P> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.current < self.end {
+            self.end -= 1;
+            Some(P::from_monotonic(self.end))
+        } else {
+            None
+        }
+    }
+}
+
+impl<P: TimeResolution> ExactSizeIterator for TimeRangeIter<P> {}
+
+impl<P: TimeResolution> iter::FusedIterator for TimeRangeIter<P> {}
+
+// Which period to pick from each group when subsampling
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Representative {
+    First,
+    Last,
+}
+
+#[derive(Debug, Clone)]
+pub struct Subsample<P: TimeResolution, C: TimeResolution> {
+    periods: iter::Peekable<TimeRangeIter<P>>,
+    representative: Representative,
+    // the coarse period of the last period returned
+    previous: Option<C>,
+}
+
+impl<P: TimeResolution, C: TimeResolution> Iterator for Subsample<P, C> {
+    type Item = P;
+    fn next(&mut self) -> Option<Self::Item> {
+        let coarse = |period: &P| C::from_datetime(period.naive_date_time());
+        loop {
+            let period = self.periods.next()?;
+            let found = match self.representative {
+                Representative::First => self.previous != Some(coarse(&period)),
+                Representative::Last => self.periods.peek().is_none_or(|next| coarse(next) != coarse(&period)),
+            };
+            if found {
+                self.previous = Some(coarse(&period));
+                return Some(period);
+            }
+        }
+    }
+}
+
+impl<P: TimeResolution, C: TimeResolution> iter::FusedIterator for Subsample<P, C> {}
+
+// Keeps track of which keys have been requested. A `CoverageMap`
+// uses much less memory than a `BTreeSet` when coverage is dense.
+pub trait RequestTracker<K>: Default {
+    fn contains(&self, key: &K) -> bool;
+    fn insert(&mut self, key: K);
+    // an estimate of the heap memory used
+    fn approx_memory_bytes(&self) -> usize;
+}
+
+impl<K: Ord> RequestTracker<K> for collections::BTreeSet<K> {
+    fn contains(&self, key: &K) -> bool {
+        collections::BTreeSet::contains(self, key)
+    }
+    fn insert(&mut self, key: K) {
+        collections::BTreeSet::insert(self, key);
+    }
+    fn approx_memory_bytes(&self) -> usize {
+        btree_memory_bytes::<K, ()>(self.len())
+    }
 }
 
-pub struct Cache<K: Ord + fmt::Debug + Copy, T: Send + fmt::Debug + Eq + Copy> {
+// An estimate of the heap memory used by a `BTreeMap` with `len` entries, assuming
+// that its nodes are full. This ignores the internal nodes, which hold about 1 in 12
+// of the entries, as well as any memory owned by the keys and values themselves.
+pub(crate) fn btree_memory_bytes<K, V>(len: usize) -> usize {
+    // the maximum number of entries in a node of the std `BTreeMap`
+    const CAPACITY: usize = 11;
+    // the parent pointer, index in the parent and length of each node
+    const HEADER: usize = mem::size_of::<usize>() + 2 * mem::size_of::<u16>();
+    let node = HEADER + CAPACITY * (mem::size_of::<K>() + mem::size_of::<V>());
+    len.div_ceil(CAPACITY) * node
+}
+
+pub struct Cache<
+    K: Ord + fmt::Debug + Copy,
+    T: Send + fmt::Debug + Eq + Copy,
+    R: RequestTracker<K> = collections::BTreeSet<K>,
+> {
     // The actual data in the cache
     data: collections::BTreeMap<K, T>,
     // The requests for data which has been cached
-    requests: collections::BTreeSet<K>,
+    requests: R,
+    // Where the data for each requested key came from, as an index into `sources`
+    provenance: collections::BTreeMap<K, usize>,
+    // Each source, with the number of keys in `provenance` which refer to it. Sources are
+    // dropped once all their keys have been overwritten
+    sources: collections::BTreeMap<usize, (Provenance, usize)>,
+    // The index for the next source added, so that sources stay in the order they were added
+    next_source: usize,
 }
 
-// merge a request into a set of requests, grouping contigious on the way
-fn missing_pieces<K: Ord + fmt::Debug + Copy>(
-    _request: collections::BTreeSet<K>,
-    _requests: &collections::BTreeSet<K>,
+// Where the data added to a `Cache` came from, eg: for answering
+// "which file did these numbers come from?"
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Provenance {
+    // eg: the name of the feed or provider
+    pub source: String,
+    // eg: a file name or request id
+    pub id: Option<String>,
+    pub fetched_at: chrono::DateTime<chrono::Utc>,
+}
+
+// merge a request into a set of requests, grouping contigious on the way.
+// `adjacent` decides whether two consecutive missing keys belong to the same piece
+fn missing_pieces<K: Ord + fmt::Debug + Copy, R: RequestTracker<K>>(
+    request: collections::BTreeSet<K>,
+    requests: &R,
+    adjacent: impl Fn(&K, &K) -> bool,
 ) -> Vec<collections::BTreeSet<K>> {
-    todo!()
+    let mut pieces = Vec::new();
+    let mut current = collections::BTreeSet::new();
+    for key in request {
+        if requests.contains(&key) {
+            if !current.is_empty() {
+                pieces.push(mem::take(&mut current));
+            }
+        } else {
+            if current.iter().next_back().is_some_and(|prev| !adjacent(prev, &key)) {
+                pieces.push(mem::take(&mut current));
+            }
+            current.insert(key);
+        }
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    pieces
 }
 
 // No concept of partial, becuse we will simply request the missing data, then ask the cache again.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CacheResponse<K: Ord + fmt::Debug + Copy, T: Send + fmt::Debug + Eq + Copy> {
     Hit(collections::BTreeMap<K, T>), // means the whole request as able to be replied, doesn't necessarily mean the whole range of data is filled
     Miss(Vec<collections::BTreeSet<K>>), // will be a minimal reasonable set of time ranges to request from the provider
 }
 
 impl<K: Ord + fmt::Debug + Copy, T: Send + fmt::Debug + Eq + Copy> Cache<K, T> {
+    pub fn empty() -> Cache<K, T> {
+        Cache::with_tracker()
+    }
+}
+
+impl<K: Ord + fmt::Debug + Copy, T: Send + fmt::Debug + Eq + Copy, R: RequestTracker<K>> Cache<K, T, R> {
+    // an empty cache, using `R` to track requests (eg: `Cache::<Minute, f64, CoverageMap<Minute>>::with_tracker()`)
+    pub fn with_tracker() -> Cache<K, T, R> {
+        Cache {
+            data: collections::BTreeMap::new(),
+            requests: R::default(),
+            provenance: collections::BTreeMap::new(),
+            sources: collections::BTreeMap::new(),
+            next_source: 0,
+        }
+    }
+    // keys which are consecutive in the request are grouped into the same piece of a `Miss`,
+    // see `missing_ranges` for a split that respects gaps between periods
     pub fn get(&self, request: collections::BTreeSet<K>) -> CacheResponse<K, T> {
+        self.get_split(request, |_, _| true)
+    }
+    fn get_split(
+        &self,
+        request: collections::BTreeSet<K>,
+        adjacent: impl Fn(&K, &K) -> bool,
+    ) -> CacheResponse<K, T> {
         if request.is_empty() {
             CacheResponse::Hit(collections::BTreeMap::new())
-        } else if self.requests.is_superset(&request) {
+        } else if request.iter().all(|k| self.requests.contains(k)) {
+            // only the requested keys, so a request for disjoint ranges doesn't include the gaps between them
             CacheResponse::Hit(
-                self.data
+                request
                     .iter()
-                    // mustn't be empty othewise we would have returned out of the first arm of the `if`
-                    .filter(|(k, _)| request.iter().next().unwrap() <= *k)
-                    .filter(|(k, _)| request.iter().rev().next().unwrap() >= *k)
-                    .map(|(k, v)| (*k, *v))
+                    .filter_map(|k| self.data.get(k).map(|v| (*k, *v)))
                     .collect(),
             )
         } else {
-            CacheResponse::Miss(missing_pieces(request, &self.requests))
+            CacheResponse::Miss(missing_pieces(request, &self.requests, adjacent))
         }
     }
-    pub fn empty() -> Cache<K, T> {
-        Cache {
-            data: collections::BTreeMap::new(),
-            requests: collections::BTreeSet::new(),
-        }
+    // an estimate of the heap memory used by the data and requests, for metrics or deciding when
+    // to evict. Memory owned by the values themselves isn't counted.
+    pub fn approx_memory_bytes(&self) -> usize {
+        btree_memory_bytes::<K, T>(self.data.len())
+            + self.requests.approx_memory_bytes()
+            + btree_memory_bytes::<K, usize>(self.provenance.len())
+            + btree_memory_bytes::<usize, (Provenance, usize)>(self.sources.len())
     }
     // could also store versioned data, with a DateTIme<Utc> associated with each T at each P?
     // or allow overwriting, etc
     // but this default seems better for now
     pub fn add(
         &mut self,
-        mut request_range: collections::BTreeSet<K>,
+        request_range: collections::BTreeSet<K>,
+        data: collections::BTreeMap<K, T>,
+    ) {
+        self.add_from(request_range, data, None)
+    }
+    // as for `add`, also recording where the data came from. Later adds of the same keys replace this.
+    pub fn add_with_provenance(
+        &mut self,
+        request_range: collections::BTreeSet<K>,
+        data: collections::BTreeMap<K, T>,
+        provenance: Provenance,
+    ) {
+        let source = self.next_source;
+        self.next_source += 1;
+        self.sources.insert(source, (provenance, 0));
+        self.add_from(request_range, data, Some(source));
+        if self.sources.get(&source).is_some_and(|(_, count)| *count == 0) {
+            self.sources.remove(&source);
+        }
+    }
+    // the sources of the requested keys, in the order they were added, with the keys that each provided.
+    // Keys which weren't added with a provenance are left out.
+    pub fn provenance(&self, request: collections::BTreeSet<K>) -> Vec<(&Provenance, collections::BTreeSet<K>)> {
+        let mut by_source = collections::BTreeMap::<usize, collections::BTreeSet<K>>::new();
+        for key in request {
+            if let Some(source) = self.provenance.get(&key) {
+                by_source.entry(*source).or_default().insert(key);
+            }
+        }
+        by_source
+            .into_iter()
+            .map(|(source, keys)| (&self.sources[&source].0, keys))
+            .collect()
+    }
+    fn add_from(
+        &mut self,
+        request_range: collections::BTreeSet<K>,
         data: collections::BTreeMap<K, T>,
+        source: Option<usize>,
     ) {
-        self.requests.append(&mut request_range);
+        for key in request_range {
+            let previous = match source {
+                Some(source) => {
+                    if let Some((_, count)) = self.sources.get_mut(&source) {
+                        *count += 1;
+                    }
+                    self.provenance.insert(key, source)
+                }
+                None if !self.provenance.is_empty() => self.provenance.remove(&key),
+                None => None,
+            };
+            if let Some(previous) = previous {
+                self.release_source(previous);
+            }
+            self.requests.insert(key);
+        }
         for (point, datum) in data {
             // should we check if the data point already exists?
             // if it does exist, what should we do?
@@ -399,5 +1235,770 @@ impl<K: Ord + fmt::Debug + Copy, T: Send + fmt::Debug + Eq + Copy> Cache<K, T> {
             self.data.insert(point, datum);
         }
     }
+    fn release_source(&mut self, source: usize) {
+        if let Some((_, count)) = self.sources.get_mut(&source) {
+            *count -= 1;
+            if *count == 0 {
+                self.sources.remove(&source);
+            }
+        }
+    }
+}
+
+impl<K: TimeResolution, T: Send + fmt::Debug + Eq + Copy, R: RequestTracker<K>> Cache<K, T, R> {
+    // the parts of the request which haven't been added, as contiguous ranges
+    pub fn missing_ranges(&self, request: collections::BTreeSet<K>) -> Vec<TimeRange<K>> {
+        missing_pieces(request, &self.requests, |prev, next| prev.between(*next) == 1)
+            .into_iter()
+            .map(|piece| {
+                let len = u32::try_from(piece.len()).expect("Pieces are contiguous ranges of periods");
+                // pieces are never empty
+                TimeRange::new(*piece.iter().next().unwrap(), len)
+            })
+            .collect()
+    }
+}
+
+// A cache which can hold data for any number of resolutions at once,
+// keyed by the resolution's `TypeId` and the monotonic index of the period
+pub struct ErasedCache<T: Send + fmt::Debug + Eq + Copy> {
+    caches: collections::HashMap<any::TypeId, Cache<i64, T>>,
+}
+
+impl<T: Send + fmt::Debug + Eq + Copy> ErasedCache<T> {
+    pub fn empty() -> ErasedCache<T> {
+        ErasedCache {
+            caches: collections::HashMap::new(),
+        }
+    }
+    pub fn get<P: TimeResolution + 'static>(
+        &self,
+        request: collections::BTreeSet<P>,
+    ) -> CacheResponse<P, T> {
+        let request = request.iter().map(|p| p.to_monotonic()).collect();
+        match self.get_erased(any::TypeId::of::<P>(), request) {
+            CacheResponse::Hit(data) => CacheResponse::Hit(
+                data.into_iter()
+                    .map(|(k, v)| (P::from_monotonic(k), v))
+                    .collect(),
+            ),
+            CacheResponse::Miss(pieces) => CacheResponse::Miss(
+                pieces
+                    .into_iter()
+                    .map(|piece| piece.into_iter().map(P::from_monotonic).collect())
+                    .collect(),
+            ),
+        }
+    }
+    pub fn add<P: TimeResolution + 'static>(
+        &mut self,
+        request_range: collections::BTreeSet<P>,
+        data: collections::BTreeMap<P, T>,
+    ) {
+        self.add_erased(
+            any::TypeId::of::<P>(),
+            request_range.iter().map(|p| p.to_monotonic()).collect(),
+            data.into_iter().map(|(k, v)| (k.to_monotonic(), v)).collect(),
+        )
+    }
+    pub fn get_erased(
+        &self,
+        resolution: any::TypeId,
+        request: collections::BTreeSet<i64>,
+    ) -> CacheResponse<i64, T> {
+        // the keys are monotonic indexes, so only consecutive indexes are contiguous
+        let adjacent = |prev: &i64, next: &i64| next - prev == 1;
+        match self.caches.get(&resolution) {
+            Some(cache) => cache.get_split(request, adjacent),
+            None => Cache::empty().get_split(request, adjacent),
+        }
+    }
+    pub fn add_erased(
+        &mut self,
+        resolution: any::TypeId,
+        request_range: collections::BTreeSet<i64>,
+        data: collections::BTreeMap<i64, T>,
+    ) {
+        self.caches
+            .entry(resolution)
+            .or_insert_with(Cache::empty)
+            .add(request_range, data)
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use std::{collections::{BTreeMap, BTreeSet}, num};
+    use crate::{
+        Cache, CacheResponse, CoverageMap, Date, DateResolution, ErasedCache, Error, FiveMinute, HalfHour, Hour, Minute, Minutes, Provenance,
+        Month, Quarter, Representative, Rounding, TimeRange, TimeResolution, Year,
+    };
+
+    #[test]
+    #[should_panic(expected = "at least one period")]
+    fn test_new_empty() {
+        TimeRange::new(Date::from_ymd(2021, 7, 1).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_from_start_end_exclusive() {
+        let start = Date::from_ymd(2021, 7, 1).unwrap();
+        let end = Date::from_ymd(2021, 8, 1).unwrap();
+        let range = TimeRange::from_start_end_exclusive(start, end).unwrap();
+        assert_eq!(range.len(), 31);
+        assert_eq!(range.end(), end.pred());
+        assert_eq!(Some(range), TimeRange::from_start_end(start, end.pred()));
+        assert_eq!(
+            TimeRange::from_start_end_exclusive(start, start.succ()).unwrap(),
+            TimeRange::new(start, 1)
+        );
+        assert!(matches!(
+            TimeRange::from_start_end_exclusive(start, start),
+            Err(Error::EmptyRange { .. })
+        ));
+        assert!(TimeRange::from_start_end_exclusive(end, start).is_err());
+        let minute = "2021-07-01 00:00".parse::<Minute>().unwrap();
+        assert!(matches!(
+            TimeRange::from_start_end_exclusive(minute, minute.succ_n(u32::MAX).succ()),
+            Err(Error::OutOfRange)
+        ));
+    }
+
+    #[test]
+    fn test_from_start_duration() {
+        let start = "2021-07-01 00:00".parse::<HalfHour>().unwrap();
+        let range = TimeRange::from_start_duration(start, chrono::Duration::days(90), Rounding::Strict).unwrap();
+        assert_eq!(range.len(), 90 * 48);
+        assert_eq!(range.start(), start);
+
+        let partial = chrono::Duration::minutes(75);
+        assert_eq!(
+            TimeRange::from_start_duration(start, partial, Rounding::Floor).unwrap(),
+            TimeRange::new(start, 2)
+        );
+        assert_eq!(
+            TimeRange::from_start_duration(start, partial, Rounding::Ceil).unwrap(),
+            TimeRange::new(start, 3)
+        );
+        assert_eq!(
+            TimeRange::from_start_duration(start, partial, Rounding::Nearest).unwrap(),
+            TimeRange::new(start, 3)
+        );
+        assert!(matches!(
+            TimeRange::from_start_duration(start, partial, Rounding::Strict),
+            Err(Error::NotOnBoundary(_))
+        ));
+        assert!(matches!(
+            TimeRange::from_start_duration(start, chrono::Duration::minutes(10), Rounding::Floor),
+            Err(Error::EmptyRange { .. })
+        ));
+        assert!(matches!(
+            TimeRange::from_start_duration(start, chrono::Duration::zero(), Rounding::Strict),
+            Err(Error::EmptyRange { .. })
+        ));
+        assert!(matches!(
+            TimeRange::from_start_duration(start, chrono::Duration::hours(-1), Rounding::Floor),
+            Err(Error::EmptyRange { .. })
+        ));
+        let date = Date::from_ymd(2021, 7, 1).unwrap();
+        assert_eq!(
+            TimeRange::from_start_duration(date, chrono::Duration::hours(36), Rounding::Ceil).unwrap(),
+            TimeRange::new(date, 2)
+        );
+    }
+
+    #[test]
+    fn test_iter_step_by() {
+        let start = "2021-07-01 00:00".parse::<FiveMinute>().unwrap();
+        let range = TimeRange::new(start, 10);
+        let step = num::NonZeroU32::new(3).unwrap();
+        assert_eq!(
+            range.iter_step_by(step).collect::<Vec<_>>(),
+            vec![start, start.succ_n(3), start.succ_n(6), start.succ_n(9)]
+        );
+        assert_eq!(range.iter_step_by(num::NonZeroU32::new(1).unwrap()).count(), 10);
+    }
+
+    #[test]
+    fn test_subsample() {
+        let start = "2021-07-01 22:00".parse::<HalfHour>().unwrap();
+        // 22:00 on the 1st to 01:30 on the 3rd
+        let range = TimeRange::new(start, 56);
+        assert_eq!(
+            range.subsample::<Date>(Representative::First).collect::<Vec<_>>(),
+            vec![start, start.succ_n(4), start.succ_n(52)]
+        );
+        assert_eq!(
+            range.subsample::<Date>(Representative::Last).collect::<Vec<_>>(),
+            vec![start.succ_n(3), start.succ_n(51), start.succ_n(55)]
+        );
+        assert_eq!(
+            range.subsample::<Hour>(Representative::First).count(),
+            28
+        );
+        // a coarser period than the target only appears once
+        let months = TimeRange::new(Month::from_date(chrono::NaiveDate::from_ymd_opt(2021, 1, 1).unwrap()), 3);
+        assert_eq!(
+            months.subsample::<Date>(Representative::First).collect::<Vec<_>>(),
+            months.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_intersect_with() {
+        let days = TimeRange::new(Date::from_ymd(2021, 7, 1).unwrap(), 2);
+        let start = "2021-06-30 20:00".parse::<HalfHour>().unwrap();
+        let intervals = TimeRange::new(start, 20);
+        assert_eq!(
+            days.intersect_with(&intervals),
+            Some(TimeRange::new("2021-07-01 00:00".parse().unwrap(), 12))
+        );
+        let later = TimeRange::new("2021-07-02 22:00".parse::<HalfHour>().unwrap(), 10);
+        assert_eq!(
+            days.intersect_with(&later),
+            Some(TimeRange::new("2021-07-02 22:00".parse().unwrap(), 4))
+        );
+        assert_eq!(days.intersect_with(&TimeRange::new(start, 8)), None);
+        // fine to coarse gives the coarse periods which overlap
+        assert_eq!(intervals.intersect_with(&days), Some(TimeRange::new(days.start(), 1)));
+        let months = TimeRange::new(Month::from_date(chrono::NaiveDate::from_ymd_opt(2021, 6, 1).unwrap()), 2);
+        assert_eq!(months.intersect_with(&days), Some(days));
+    }
+
+    #[test]
+    fn test_containing() {
+        let five = "2021-07-01 10:25".parse::<FiveMinute>().unwrap();
+        assert_eq!(five.containing::<HalfHour>().unwrap(), "2021-07-01 10:00".parse::<HalfHour>().unwrap());
+        assert_eq!(five.containing::<Date>().unwrap(), Date::from_ymd(2021, 7, 1).unwrap());
+        let straddles = "2021-07-01 09:45".parse::<Minutes<45>>().unwrap();
+        assert!(matches!(straddles.containing::<HalfHour>(), Err(Error::NotOnBoundary(_))));
+        assert!(Date::from_ymd(2021, 7, 1).unwrap().containing::<Hour>().is_err());
+    }
+
+    #[test]
+    fn test_try_rescale() {
+        let start = "2021-07-01 10:00".parse::<FiveMinute>().unwrap();
+        assert_eq!(
+            TimeRange::new(start, 12).try_rescale::<HalfHour>().unwrap(),
+            TimeRange::new("2021-07-01 10:00".parse().unwrap(), 2)
+        );
+        // partial periods at either end
+        assert!(matches!(
+            TimeRange::new(start, 11).try_rescale::<HalfHour>(),
+            Err(Error::NotOnBoundary(_))
+        ));
+        assert!(TimeRange::new(start.succ(), 11).try_rescale::<HalfHour>().is_err());
+        // aligned at both ends, but the 45 minute periods cross half hours
+        let straddles = "2021-07-01 10:30".parse::<Minutes<45>>().unwrap();
+        assert!(TimeRange::new(straddles, 2).try_rescale::<HalfHour>().is_err());
+        let days = TimeRange::new(Date::from_ymd(2021, 7, 1).unwrap(), 31);
+        assert_eq!(
+            days.try_rescale::<Month>().unwrap(),
+            TimeRange::new(Month::from_date(chrono::NaiveDate::from_ymd_opt(2021, 7, 1).unwrap()), 1)
+        );
+    }
+
+    // These are relied on to be the same everywhere, so must never change
+    #[test]
+    fn test_stable_hash_u64() {
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        assert_eq!(date.stable_hash_u64(), 0x37c7d8dea5868a0b);
+        assert_eq!(Month::from_date(date.start()).stable_hash_u64(), 0x594127d328d2d783);
+        assert_eq!("2021-07-15 10:30".parse::<HalfHour>().unwrap().stable_hash_u64(), 0x674abbb2369e778a);
+        assert_eq!(Minutes::<30>::from_monotonic(-1).stable_hash_u64(), 0x10390fbee35855a1);
+        // same index, different resolution
+        assert_ne!(
+            Minute::from_monotonic(date.to_monotonic()).stable_hash_u64(),
+            date.stable_hash_u64()
+        );
+    }
+
+    fn check_timestamps<P: TimeResolution>(period: P) {
+        let start = period.naive_date_time().timestamp();
+        assert_eq!(period.start_timestamp(), start);
+        assert_eq!(P::from_utc_timestamp(start), period);
+        assert_eq!(P::from_utc_timestamp(period.succ().start_timestamp() - 1), period);
+        assert_eq!(P::from_utc_timestamp(start - 1), period.pred());
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let dt = chrono::NaiveDate::from_ymd_opt(2021, 7, 15).unwrap().and_hms_opt(10, 7, 0).unwrap();
+        let before = chrono::NaiveDate::from_ymd_opt(1969, 12, 31).unwrap().and_hms_opt(23, 59, 0).unwrap();
+        for dt in [dt, before].iter().copied() {
+            check_timestamps(Minute::from_datetime(dt));
+            check_timestamps(FiveMinute::from_datetime(dt));
+            check_timestamps(Hour::from_datetime(dt));
+            check_timestamps(Date::from_datetime(dt));
+            check_timestamps(Month::from_datetime(dt));
+            check_timestamps(Quarter::from_datetime(dt));
+            check_timestamps(Year::from_datetime(dt));
+        }
+        assert_eq!(Date::UNIX_EPOCH.start_timestamp(), 0);
+        assert_eq!(Date::from_utc_timestamp(-1), Date::UNIX_EPOCH.pred());
+        assert_eq!(HalfHour::from_utc_timestamp(1_626_343_200).to_string(), "2021-07-15 10:00:00 - 2021-07-15 10:30:00");
+    }
+
+    #[test]
+    fn test_envelope() {
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        let periods = vec![date.succ_n(5), date, date.succ_n(2), date.succ(), date.succ_n(2), date.succ_n(9)];
+        assert_eq!(TimeRange::envelope(periods.clone()), Some(TimeRange::new(date, 10)));
+        let (envelope, gaps) = TimeRange::envelope_with_gaps(periods).unwrap();
+        assert_eq!(envelope, TimeRange::new(date, 10));
+        assert_eq!(gaps, vec![TimeRange::new(date.succ_n(3), 2), TimeRange::new(date.succ_n(6), 3)]);
+        assert_eq!(TimeRange::envelope(vec![date]), Some(TimeRange::new(date, 1)));
+        assert_eq!(TimeRange::envelope_with_gaps(TimeRange::new(date, 3).iter()), Some((TimeRange::new(date, 3), Vec::new())));
+        assert_eq!(TimeRange::<Date>::envelope(Vec::new()), None);
+        assert_eq!(TimeRange::<Date>::envelope_with_gaps(Vec::new()), None);
+        let far = vec![Minute::from_monotonic(0), Minute::from_monotonic(i64::from(u32::MAX))];
+        assert_eq!(TimeRange::envelope(far), None);
+    }
+
+    #[test]
+    fn test_coarse_index_map() {
+        let hours = TimeRange::new("2021-07-15 22:00".parse::<Hour>().unwrap(), 28);
+        let map = hours.coarse_index_map::<Date>();
+        assert_eq!(map.len(), 28);
+        assert_eq!(&map[..3], &[0, 0, 1]);
+        assert_eq!(map[25], 1);
+        assert_eq!(map[26], 2);
+        let values = (0..28).map(f64::from).collect::<Vec<f64>>();
+        let mut sums = vec![0.0; map.last().unwrap() + 1];
+        for (index, value) in map.iter().zip(values.iter()) {
+            sums[*index] += value;
+        }
+        assert_eq!(sums, vec![1.0, (2..26).sum::<i32>().into(), 53.0]);
+        for (hour, index) in hours.iter().zip(map.iter()) {
+            assert_eq!(Date::from_monotonic(hours.start().day().to_monotonic() + *index as i64), hour.day());
+        }
+        assert_eq!(TimeRange::new(Month::from_date(chrono::NaiveDate::from_ymd_opt(2021, 11, 1).unwrap()), 5).coarse_index_map::<Year>(), vec![0, 0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_bounds_and_break_points() {
+        let months = TimeRange::new(Month::from_date(chrono::NaiveDate::from_ymd_opt(2021, 11, 1).unwrap()), 3);
+        let dt = |y, m| chrono::NaiveDate::from_ymd_opt(y, m, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        assert_eq!(
+            months.to_bounds_vec(),
+            vec![(dt(2021, 11), dt(2021, 12)), (dt(2021, 12), dt(2022, 1)), (dt(2022, 1), dt(2022, 2))]
+        );
+        assert_eq!(months.to_break_points(), vec![dt(2021, 11), dt(2021, 12), dt(2022, 1), dt(2022, 2)]);
+        let hours = TimeRange::new("2021-07-15 10:00".parse::<Hour>().unwrap(), 24);
+        let points = hours.to_break_points();
+        assert_eq!(points.len(), hours.len() + 1);
+        assert_eq!(hours.to_bounds_vec().last().unwrap().1, *points.last().unwrap());
+        assert!(points.windows(2).all(|w| w[1] - w[0] == chrono::Duration::hours(1)));
+    }
+
+    #[test]
+    fn test_cache_provenance() {
+        let fetched_at = chrono::DateTime::<chrono::Utc>::from_utc(
+            chrono::NaiveDate::from_ymd_opt(2021, 7, 15).unwrap().and_hms_opt(12, 0, 0).unwrap(),
+            chrono::Utc,
+        );
+        let feed = |id: &str| Provenance {
+            source: "feed".to_string(),
+            id: Some(id.to_string()),
+            fetched_at,
+        };
+        let mut cache = Cache::<Date, i64>::empty();
+        let days = TimeRange::new(Date::from_ymd(2021, 7, 1).unwrap(), 10);
+        assert!(cache.provenance(days.set()).is_empty());
+        cache.add_with_provenance(days.set(), days.iter().map(|d| (d, 1)).collect(), feed("a.csv"));
+        let later = TimeRange::new(days.end().pred(), 5);
+        cache.add_with_provenance(later.set(), later.iter().map(|d| (d, 2)).collect(), feed("b.csv"));
+
+        let sources = cache.provenance(TimeRange::new(days.end().pred_n(2), 4).set());
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].0, &feed("a.csv"));
+        assert_eq!(sources[0].1, vec![days.end().pred_n(2)].into_iter().collect::<BTreeSet<_>>());
+        assert_eq!(sources[1].0, &feed("b.csv"));
+        assert_eq!(sources[1].1, TimeRange::new(days.end().pred(), 3).set());
+
+        // data added without a provenance replaces it
+        cache.add(days.set(), BTreeMap::new());
+        let sources = cache.provenance(days.set().union(&later.set()).copied().collect());
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].0, &feed("b.csv"));
+        assert_eq!(sources[0].1, TimeRange::new(days.end().succ(), 3).set());
+        assert_eq!(cache.sources.len(), 1);
+
+        // sources are dropped once nothing refers to them
+        for _ in 0..3 {
+            cache.add_with_provenance(later.set(), BTreeMap::new(), feed("c.csv"));
+        }
+        assert_eq!(cache.sources.len(), 1);
+        cache.add_with_provenance(BTreeSet::new(), BTreeMap::new(), feed("d.csv"));
+        assert_eq!(cache.sources.len(), 1);
+        cache.add(later.set(), BTreeMap::new());
+        assert!(cache.sources.is_empty());
+        assert!(cache.provenance.is_empty());
+    }
+
+    #[test]
+    fn test_cache_approx_memory_bytes() {
+        let mut cache = Cache::<Minute, i64>::empty();
+        assert_eq!(cache.approx_memory_bytes(), 0);
+        let range = TimeRange::new("2021-07-01 00:00".parse::<Minute>().unwrap(), 10_000);
+        cache.add(range.set(), range.iter().map(|p| (p, 1)).collect());
+        let bytes = cache.approx_memory_bytes();
+        // at least the keys and values, for both the data and the requests
+        assert!(bytes >= 10_000 * (8 + 8 + 8));
+        assert!(bytes < 10_000 * (8 + 8 + 8) * 2);
+
+        let mut dense = Cache::<Minute, i64, CoverageMap<Minute>>::with_tracker();
+        dense.add(range.set(), range.iter().map(|p| (p, 1)).collect());
+        assert!(dense.approx_memory_bytes() < bytes);
+    }
+
+    #[test]
+    fn test_chrono_months() {
+        let month = Month::from_year_month(2021, 11).unwrap();
+        assert_eq!(month + chrono::Months::new(3), Month::from_year_month(2022, 2).unwrap());
+        assert_eq!(month - chrono::Months::new(11), Month::from_year_month(2020, 12).unwrap());
+        let quarter = Quarter::from_year_quarter(2021, 4).unwrap();
+        assert_eq!(quarter + chrono::Months::new(3), Quarter::from_year_quarter(2022, 1).unwrap());
+        assert_eq!(quarter - chrono::Months::new(12), Quarter::from_year_quarter(2020, 4).unwrap());
+        // less than a whole quarter
+        assert_eq!(quarter + chrono::Months::new(2), quarter);
+        assert_eq!(quarter - chrono::Months::new(1), Quarter::from_year_quarter(2021, 3).unwrap());
+        let year = Year::from_num(2021);
+        assert_eq!(year + chrono::Months::new(24), Year::from_num(2023));
+        assert_eq!(year - chrono::Months::new(1), Year::from_num(2020));
+    }
+
+    #[test]
+    fn test_from_start() {
+        let first = chrono::NaiveDate::from_ymd_opt(2021, 10, 1).unwrap();
+        let second = chrono::NaiveDate::from_ymd_opt(2021, 10, 2).unwrap();
+        assert_eq!(Month::from_start(first).unwrap(), Month::from_date(first));
+        assert!(matches!(Month::from_start(second), Err(Error::NotOnBoundary(_))));
+        assert_eq!(Quarter::from_start(first).unwrap(), Quarter::from_year_quarter(2021, 4).unwrap());
+        assert!(Quarter::from_start(chrono::NaiveDate::from_ymd_opt(2021, 11, 1).unwrap()).is_err());
+        assert!(Year::from_start(first).is_err());
+        assert_eq!(Year::from_start(chrono::NaiveDate::from_ymd_opt(2021, 1, 1).unwrap()).unwrap(), Year::from_num(2021));
+        // every date starts a `Date`
+        assert_eq!(Date::from_start(second).unwrap(), Date::from(second));
+    }
+
+    #[test]
+    fn test_date_parts() {
+        fn parts<P: DateResolution>(period: P) -> (i32, u32, u32) {
+            (period.year_num(), period.month_num(), period.day_num())
+        }
+        let date = chrono::NaiveDate::from_ymd_opt(2021, 11, 15).unwrap();
+        assert_eq!(parts(Date::from(date)), (2021, 11, 15));
+        assert_eq!(parts(Month::from_date(date)), (2021, 11, 1));
+        assert_eq!(parts(Quarter::from_date(date)), (2021, 10, 1));
+        assert_eq!(parts(Year::from_date(date)), (2021, 1, 1));
+        assert_eq!(parts(Date::from_ymd(-1, 12, 31).unwrap()), (-1, 12, 31));
+    }
+
+    #[test]
+    fn test_between_direction() {
+        let jan = Month::from_year_month(2021, 1).unwrap();
+        let apr = Month::from_year_month(2021, 4).unwrap();
+        // `between` is the second minus the first
+        assert_eq!(jan.between(apr), 3);
+        assert_eq!(apr.between(jan), -3);
+        assert_eq!(jan.periods_until(apr), 3);
+        assert_eq!(apr.periods_since(jan), 3);
+        assert_eq!(apr.periods_until(jan), -3);
+        assert_eq!(jan.periods_since(apr), -3);
+        assert_eq!(jan.abs_between(apr), 3);
+        assert_eq!(apr.abs_between(jan), 3);
+        assert_eq!(jan.abs_between(jan), 0);
+        assert_eq!(jan.succ_n(3), apr);
+        let start = "2021-07-15 10:00".parse::<HalfHour>().unwrap();
+        assert_eq!(start.periods_until(start.succ_n(48)), 48);
+    }
+
+    #[test]
+    fn test_cache_disjoint_request() {
+        let mut cache = Cache::<Date, i64>::empty();
+        let days = TimeRange::new(Date::from_ymd(2021, 7, 1).unwrap(), 10);
+        cache.add(days.set(), days.iter().map(|d| (d, d.to_monotonic())).collect());
+
+        let first = TimeRange::new(days.start(), 2);
+        let last = TimeRange::new(days.end().pred(), 2);
+        let request = first.iter().chain(last.iter()).collect::<BTreeSet<_>>();
+        assert_eq!(
+            cache.get(request.clone()),
+            CacheResponse::Hit(request.iter().map(|d| (*d, d.to_monotonic())).collect())
+        );
+        // requested keys without data are simply absent
+        let mut cache = Cache::<Date, i64>::empty();
+        cache.add(days.set(), vec![(days.start(), 1), (days.end(), 10)].into_iter().collect());
+        assert_eq!(
+            cache.get(days.set()),
+            CacheResponse::Hit(vec![(days.start(), 1), (days.end(), 10)].into_iter().collect())
+        );
+        assert_eq!(
+            cache.get(vec![days.start().succ(), days.end().pred()].into_iter().collect()),
+            CacheResponse::Hit(BTreeMap::new())
+        );
+    }
+
+    #[test]
+    fn test_cache_missing_ranges() {
+        let mut cache = Cache::<Date, i64>::empty();
+        let days = TimeRange::new(Date::from_ymd(2021, 7, 1).unwrap(), 10);
+        assert_eq!(cache.missing_ranges(days.set()), vec![days]);
+        cache.add(
+            TimeRange::new(days.start().succ_n(4), 2).set(),
+            BTreeMap::new(),
+        );
+        assert_eq!(
+            cache.missing_ranges(days.set()),
+            vec![TimeRange::new(days.start(), 4), TimeRange::new(days.start().succ_n(6), 4)]
+        );
+        // a gap in the request splits the missing pieces, even if nothing in the gap has been added
+        let first = TimeRange::new(days.start(), 2);
+        let last = TimeRange::new(days.end().pred(), 2);
+        let request = first.iter().chain(last.iter()).collect::<BTreeSet<_>>();
+        assert_eq!(cache.missing_ranges(request.clone()), vec![first, last]);
+        // `get` only knows the order of the keys
+        assert_eq!(cache.get(request.clone()), CacheResponse::Miss(vec![request]));
+        assert!(cache.missing_ranges(BTreeSet::new()).is_empty());
+    }
+
+    #[test]
+    fn test_erased_cache() {
+        let mut cache = ErasedCache::<i32>::empty();
+        let days = TimeRange::new(Date::from_ymd(2021, 7, 1).unwrap(), 10);
+        let intervals = TimeRange::new("2021-07-01 00:00".parse::<FiveMinute>().unwrap(), 10);
+
+        assert_eq!(
+            cache.get(days.set()),
+            CacheResponse::Miss(vec![days.set()])
+        );
+        cache.add(days.set(), days.iter().map(|d| (d, 1)).collect());
+        assert_eq!(
+            cache.get(days.set()),
+            CacheResponse::Hit(days.iter().map(|d| (d, 1)).collect())
+        );
+        // same monotonic indexes, different resolution
+        let same_indexes = TimeRange::new(FiveMinute::from_monotonic(days.start().to_monotonic()), 10);
+        assert_eq!(
+            cache.get(same_indexes.set()),
+            CacheResponse::Miss(vec![same_indexes.set()])
+        );
+
+        cache.add(intervals.set(), intervals.iter().map(|p| (p, 2)).collect());
+        let wider = TimeRange::new(intervals.start(), 15);
+        assert_eq!(
+            cache.get(wider.set()),
+            CacheResponse::Miss(vec![TimeRange::new(intervals.end().succ(), 5).set()])
+        );
+        assert_eq!(
+            cache.get(intervals.set()),
+            CacheResponse::Hit(intervals.iter().map(|p| (p, 2)).collect())
+        );
+        // missing pieces are split at gaps in the request
+        let later = TimeRange::new(intervals.end().succ_n(10), 2);
+        let disjoint = wider.iter().chain(later.iter()).collect();
+        assert_eq!(
+            cache.get(disjoint),
+            CacheResponse::Miss(vec![TimeRange::new(intervals.end().succ(), 5).set(), later.set()])
+        );
+    }
+
+    // We persist monotonic indexes, so these must never change
+    #[test]
+    fn test_monotonic_epochs_are_stable() {
+        let zero = chrono::NaiveDate::from_ymd_opt(0, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        assert_eq!(Date::monotonic_epoch(), zero);
+        assert_eq!(Month::monotonic_epoch(), zero);
+        assert_eq!(Quarter::monotonic_epoch(), zero);
+        assert_eq!(Year::monotonic_epoch(), zero);
+        let unix = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        assert_eq!(Minutes::<1>::monotonic_epoch(), unix);
+        assert_eq!(Minutes::<30>::monotonic_epoch(), unix);
+
+        assert_eq!(Date::from_monotonic(0).naive_date_time(), Date::monotonic_epoch());
+        assert_eq!(Month::from_monotonic(0).naive_date_time(), Month::monotonic_epoch());
+        assert_eq!(Quarter::from_monotonic(0).naive_date_time(), Quarter::monotonic_epoch());
+        assert_eq!(Year::from_monotonic(0).naive_date_time(), Year::monotonic_epoch());
+        assert_eq!(Minutes::<5>::from_monotonic(0).naive_date_time(), Minutes::<5>::monotonic_epoch());
+
+        let date = chrono::NaiveDate::from_ymd_opt(2021, 7, 15).unwrap();
+        assert_eq!(Date::from(date).to_monotonic(), 738_351);
+        assert_eq!(Month::from_date(date).to_monotonic(), 24_258);
+        assert_eq!(Quarter::from_date(date).to_monotonic(), 8_086);
+        assert_eq!(Year::from_date(date).to_monotonic(), 2021);
+        let date_time = date.and_hms_opt(10, 30, 0).unwrap();
+        assert_eq!(
+            "2021-07-15 10:30".parse::<Minutes<1>>().unwrap().to_monotonic(),
+            date_time.timestamp() / 60
+        );
+        assert_eq!("2021-07-15 10:30".parse::<Minutes<30>>().unwrap().to_monotonic(), 903_525);
+        assert_eq!(Date::from(date).start(), date);
+    }
+
+    #[test]
+    fn test_add_duration() {
+        let period = "2021-07-15 10:30".parse::<HalfHour>().unwrap();
+        let minutes = |n| chrono::Duration::minutes(n);
+        assert_eq!(period.add_duration(minutes(60), Rounding::Strict).unwrap(), period.succ_n(2));
+        assert_eq!(period.add_duration(minutes(75), Rounding::Floor).unwrap(), period.succ_n(2));
+        assert_eq!(period.add_duration(minutes(75), Rounding::Ceil).unwrap(), period.succ_n(3));
+        assert_eq!(period.add_duration(minutes(-45), Rounding::Nearest).unwrap(), period.pred());
+        assert!(period.add_duration(minutes(75), Rounding::Strict).is_err());
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        assert_eq!(date.add_duration(chrono::Duration::hours(36), Rounding::Floor).unwrap(), date.succ());
+        assert_eq!(date.add_duration(chrono::Duration::hours(-1), Rounding::Floor).unwrap(), date.pred());
+        assert!(date.add_duration(chrono::Duration::days(i64::from(i32::MAX)), Rounding::Floor).is_err());
+        assert!(Date::from_monotonic(i64::MAX).add_duration(minutes(1), Rounding::Floor).is_err());
+    }
+
+    #[test]
+    fn test_write_to() {
+        let mut buf = String::new();
+        let month = Month::from_year_month(2021, 7).unwrap();
+        month.write_to(&mut buf).unwrap();
+        buf.push(',');
+        Month::from_year_month(-1, 12).unwrap().write_to(&mut buf).unwrap();
+        buf.push(',');
+        Quarter::from_date(chrono::NaiveDate::from_ymd_opt(2021, 7, 15).unwrap()).write_to(&mut buf).unwrap();
+        assert_eq!(buf, "Jul-2021,Dec--0001,Q3-2021");
+        for year in [-10_001, -1, 0, 999, 2021, 10_000] {
+            let month = Month::from_year_month(year, 2).unwrap();
+            assert_eq!(month.to_string(), month.start().format("%b-%Y").to_string());
+        }
+    }
+
+    #[test]
+    fn test_iter() {
+        let start = Date::from_ymd(2021, 7, 1).unwrap();
+        let range = TimeRange::new(start, 10);
+        assert_eq!(range.iter().count(), 10);
+        assert_eq!(range.iter().len(), 10);
+        assert_eq!(range.iter().last(), Some(start.succ_n(9)));
+        assert_eq!(range.iter().rev().take(2).collect::<Vec<_>>(), vec![start.succ_n(9), start.succ_n(8)]);
+        let mut iter = range.iter();
+        assert_eq!(iter.nth(3), Some(start.succ_n(3)));
+        assert_eq!(iter.next(), Some(start.succ_n(4)));
+        assert_eq!(iter.next_back(), Some(start.succ_n(9)));
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.nth(4), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        assert_eq!(date.checked_succ_n(3).unwrap(), date.succ_n(3));
+        assert_eq!(date.checked_pred_n(3).unwrap(), date.pred_n(3));
+        let max = Date::from(chrono::NaiveDate::MAX);
+        assert!(matches!(max.checked_succ_n(1), Err(crate::Error::OutOfRange)));
+        assert!(Date::from_monotonic(i64::MAX).checked_succ_n(1).is_err());
+        assert!(Date::from_monotonic(i64::MIN).checked_pred_n(1).is_err());
+        assert_eq!(max.saturating_succ_n(10), max);
+        assert_eq!(date.saturating_succ_n(10), date.succ_n(10));
+        assert_eq!(Date::from(chrono::NaiveDate::MIN).saturating_pred_n(1), Date::from(chrono::NaiveDate::MIN));
+        let minute = "2021-07-15 10:30".parse::<Minutes<1>>().unwrap();
+        assert!(minute.saturating_succ_n(u32::MAX).saturating_succ_n(u32::MAX).try_naive_date_time().is_ok());
+        assert!(Year::from_monotonic(0).saturating_pred_n(u32::MAX).try_naive_date_time().is_ok());
+        assert!(Date::from_monotonic(i64::MIN).checked_between(Date::from_monotonic(i64::MAX)).is_err());
+        assert_eq!(date.checked_between(date.succ_n(5)).unwrap(), 5);
+    }
+
+    #[test]
+    fn test_saturating_arithmetic_bounds() {
+        fn check<P: TimeResolution>() {
+            let (min, max) = (P::min_representable(), P::max_representable());
+            assert!(min.try_naive_date_time().is_ok(), "{}", min.name());
+            assert!(max.try_naive_date_time().is_ok(), "{}", max.name());
+            assert!(max.checked_succ_n(1).is_err(), "{}", max.name());
+            assert!(min.checked_pred_n(1).is_err(), "{}", min.name());
+            assert_eq!(max.saturating_succ_n(1), max);
+            assert_eq!(min.saturating_pred_n(u32::MAX), min);
+            // never moves backwards when close to the end
+            let near_max = max.pred_n(5);
+            assert_eq!(near_max.saturating_succ_n(3), max.pred_n(2));
+            assert_eq!(near_max.saturating_succ_n(u32::MAX), max);
+            assert_eq!(min.succ_n(5).saturating_pred_n(u32::MAX), min);
+        }
+        check::<Minute>();
+        check::<FiveMinute>();
+        check::<HalfHour>();
+        check::<Hour>();
+        check::<Date>();
+        check::<Month>();
+        check::<Quarter>();
+        check::<Year>();
+    }
+
+    #[test]
+    fn test_shard() {
+        let shards = std::num::NonZeroU32::new(4).unwrap();
+        assert_eq!(Date::from_monotonic(9).shard(shards), 1);
+        assert_eq!(Date::from_monotonic(-1).shard(shards), 3);
+        assert_eq!(Date::from_monotonic(-4).shard(shards), 0);
+        let range = TimeRange::new(Date::from_monotonic(-2), 10);
+        let split = range.split_by_shard(shards);
+        assert_eq!(split.len(), 4);
+        assert_eq!(split[2], vec![Date::from_monotonic(-2), Date::from_monotonic(2), Date::from_monotonic(6)]);
+        assert_eq!(split.iter().map(|s| s.len()).sum::<usize>(), 10);
+    }
+
+    #[test]
+    fn test_unbounded_iterators() {
+        let month = Month::from_year_month(2021, 11).unwrap();
+        let next = month.iter_from().take(3).collect::<Vec<_>>();
+        assert_eq!(next, vec![month, month.succ(), month.succ_n(2)]);
+        let prev = month.iter_back_from().take(3).collect::<Vec<_>>();
+        assert_eq!(prev, vec![month, month.pred(), month.pred_n(2)]);
+        let range = TimeRange::new(month, 1);
+        let until_march = range
+            .iter_from_start_unbounded()
+            .take_while(|m| m.month_num() != 3)
+            .count();
+        assert_eq!(until_march, 4);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_roundtrip() {
+        fn roundtrip<P: TimeResolution>(period: P) {
+            let json = serde_json::to_string(&period).unwrap();
+            assert_eq!(serde_json::from_str::<P>(&json).unwrap(), period);
+            let range = TimeRange::new(period, 3);
+            let json = serde_json::to_string(&range).unwrap();
+            assert_eq!(serde_json::from_str::<TimeRange<P>>(&json).unwrap(), range);
+        }
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        roundtrip(date);
+        roundtrip(Month::from_date(date.start()));
+        roundtrip(Quarter::from_date(date.start()));
+        roundtrip(Year::from_date(date.start()));
+        roundtrip("2021-07-15 10:30".parse::<Minutes<1>>().unwrap());
+        roundtrip("2021-07-15 10:30".parse::<FiveMinute>().unwrap());
+        assert_eq!(
+            serde_json::to_string(&Quarter::from_date(date.start())).unwrap(),
+            "\"Q3-2021\""
+        );
+        let json = serde_json::to_string(&TimeRange::new(date, 1)).unwrap().replace("\"len\":1", "\"len\":0");
+        assert!(serde_json::from_str::<TimeRange<Date>>(&json).is_err());
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn test_rkyv() {
+        use rkyv::Deserialize;
+        let range = TimeRange::new(Date::from_ymd(2021, 7, 15).unwrap(), 3);
+        let bytes = rkyv::to_bytes::<_, 64>(&range).unwrap();
+        let archived = rkyv::check_archived_root::<TimeRange<Date>>(&bytes).unwrap();
+        assert!(*archived == range);
+        assert_eq!(archived.start, range.start());
+        let deserialized: TimeRange<Date> = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, range);
+
+        let period = "2021-07-15 10:30".parse::<Minutes<30>>().unwrap();
+        let bytes = rkyv::to_bytes::<_, 16>(&period).unwrap();
+        let archived = rkyv::check_archived_root::<Minutes<30>>(&bytes).unwrap();
+        assert!(*archived == period);
+        assert!(*archived < period.succ());
+    }
+}
@@ -0,0 +1,92 @@
+// Interval numbering used by the NEM (Australia's National Electricity Market),
+// where the trading day runs from 04:00 to 04:00 on the following day, and
+// intervals are numbered from 1 (eg: dispatch interval 1 is 04:00 to 04:05).
+use crate::{Date, DateResolution, FiveMinute, HalfHour, SubDateResolution};
+use std::convert::TryFrom;
+
+pub const DISPATCH_INTERVALS_PER_DAY: u32 = 288;
+pub const TRADING_INTERVALS_PER_DAY: u32 = 48;
+
+fn trading_day_offset() -> chrono::Duration {
+    chrono::Duration::hours(4)
+}
+
+// the trading day that a period falls within
+pub fn trading_day<P: SubDateResolution>(period: P) -> Date {
+    Date::from(period.occurs_on_date_with_offset(trading_day_offset()))
+}
+
+fn first_of_trading_day<P: SubDateResolution>(day: Date) -> P {
+    P::first_on_day_with_offset(day.start(), trading_day_offset())
+}
+
+fn interval_number<P: SubDateResolution>(period: P) -> (Date, u32) {
+    let day = trading_day(period);
+    let index = first_of_trading_day::<P>(day).between(period);
+    (day, u32::try_from(index).expect("Within a single trading day") + 1)
+}
+
+fn from_interval_number<P: SubDateResolution>(day: Date, number: u32) -> crate::Result<P> {
+    let max = P::periods_per_day();
+    if !(1..=max).contains(&number) {
+        return Err(crate::Error::InvalidIntervalNumber { number, max });
+    }
+    Ok(first_of_trading_day::<P>(day).succ_n(number - 1))
+}
+
+// the trading day and dispatch interval number (1..=288) of a 5 minute period
+pub fn dispatch_interval(period: FiveMinute) -> (Date, u32) {
+    interval_number(period)
+}
+
+pub fn from_dispatch_interval(day: Date, number: u32) -> crate::Result<FiveMinute> {
+    from_interval_number(day, number)
+}
+
+// the trading day and trading interval number (1..=48) of a 30 minute period
+pub fn trading_interval(period: HalfHour) -> (Date, u32) {
+    interval_number(period)
+}
+
+pub fn from_trading_interval(day: Date, number: u32) -> crate::Result<HalfHour> {
+    from_interval_number(day, number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        dispatch_interval, from_dispatch_interval, from_trading_interval, trading_day, trading_interval,
+        DISPATCH_INTERVALS_PER_DAY, TRADING_INTERVALS_PER_DAY,
+    };
+    use crate::{Date, FiveMinute, HalfHour, SubDateResolution, TimeRange, TimeResolution};
+
+    #[test]
+    fn test_dispatch_interval() {
+        let day = Date::from_ymd(2021, 7, 15).unwrap();
+        let first = "2021-07-15 04:00".parse::<FiveMinute>().unwrap();
+        assert_eq!(dispatch_interval(first), (day, 1));
+        assert_eq!(dispatch_interval(first.pred()), (day.pred(), 288));
+        let last = "2021-07-16 03:55".parse::<FiveMinute>().unwrap();
+        assert_eq!(dispatch_interval(last), (day, 288));
+        assert_eq!(from_dispatch_interval(day, 1).unwrap(), first);
+        assert_eq!(from_dispatch_interval(day, 288).unwrap(), last);
+        assert!(from_dispatch_interval(day, 0).is_err());
+        assert!(from_dispatch_interval(day, 289).is_err());
+        for period in TimeRange::new(first.pred_n(10), 300).iter() {
+            let (day, number) = dispatch_interval(period);
+            assert_eq!(from_dispatch_interval(day, number).unwrap(), period);
+        }
+    }
+
+    #[test]
+    fn test_trading_interval() {
+        let day = Date::from_ymd(2021, 7, 15).unwrap();
+        let period = "2021-07-15 12:30".parse::<HalfHour>().unwrap();
+        assert_eq!(trading_interval(period), (day, 18));
+        assert_eq!(from_trading_interval(day, 18).unwrap(), period);
+        assert_eq!(trading_day(period), day);
+        assert!(from_trading_interval(day, 49).is_err());
+        assert_eq!(TRADING_INTERVALS_PER_DAY, HalfHour::periods_per_day());
+        assert_eq!(DISPATCH_INTERVALS_PER_DAY, FiveMinute::periods_per_day());
+    }
+}
@@ -1,17 +1,36 @@
-use crate::TimeResolution;
-use serde::{
-    de,
-    ser::{self, SerializeStruct},
-};
-use std::{cmp, fmt};
+use crate::{Date, DateResolution, SubDateResolution, TimeRange, TimeResolution};
+use chrono::Timelike;
+#[cfg(feature = "serde")]
+use serde::{de, ser};
+use std::{borrow, cmp, convert, fmt, str};
 
 const NUM_SECS: i64 = 60;
+// `%.f` also accepts no fraction at all, eg: "2021-07-15T10:00:00"
+const PARSE_FORMATS: [&str; 4] = ["%Y-%m-%d %H:%M", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S%.f"];
 
+/// A period of `N` minutes, where `N` must divide a day, so that periods line up
+/// with midnight. Other lengths fail to build once a period is created:
+///
+/// ```compile_fail
+/// let period = resolution::Minutes::<7>::from_monotonic(1);
+/// ```
+///
+/// [`UncheckedMinutes`] allows any length:
+///
+/// ```
+/// let period = resolution::UncheckedMinutes::<7>::from_monotonic(1);
+/// ```
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes, compare(PartialEq, PartialOrd)))]
+#[cfg_attr(feature = "rkyv", archive_attr(derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)))]
 pub struct Minutes<const N: u32> {
     index: i64,
 }
 
+// the index is always relative to UTC, so comparisons
+// don't need to consider the zone
+#[allow(dead_code)]
 #[derive(Clone, Copy, Debug)]
 pub struct MinutesZ<Z: chrono::TimeZone, const N: u32> {
     index: i64,
@@ -20,19 +39,19 @@ pub struct MinutesZ<Z: chrono::TimeZone, const N: u32> {
 
 impl<Z: chrono::TimeZone, const N: u32> PartialEq for MinutesZ<Z, N> {
     fn eq(&self, other: &MinutesZ<Z, N>) -> bool {
-        todo!()
+        self.index == other.index
     }
 }
 impl<Z: chrono::TimeZone, const N: u32> Eq for MinutesZ<Z, N> {
 }
 impl<Z: chrono::TimeZone, const N: u32> PartialOrd for MinutesZ<Z, N> {
     fn partial_cmp(&self, other: &MinutesZ<Z, N>) -> Option<cmp::Ordering> {
-        todo!()
+        Some(self.cmp(other))
     }
 }
 impl<Z: chrono::TimeZone, const N: u32> Ord for MinutesZ<Z, N> {
     fn cmp(&self, other: &Self) -> cmp::Ordering {
-        todo!()
+        self.index.cmp(&other.index)
     }
 }
 
@@ -44,6 +63,7 @@ impl<Z: chrono::TimeZone, const N: u32> crate::TimeResolutionZone<Z> for Minutes
 */
 
 
+#[allow(dead_code)]
 #[derive(Clone, Copy, Debug)]
 pub struct MinutesTZ<const N: u32> {
     index: i64,
@@ -52,79 +72,722 @@ pub struct MinutesTZ<const N: u32> {
 
 impl<const N: u32> PartialEq for MinutesTZ<N> {
     fn eq(&self, other: &MinutesTZ<N>) -> bool {
-        todo!()
+        self.index == other.index
     }
 }
 impl<const N: u32> Eq for MinutesTZ<N> {
 }
 impl<const N: u32> PartialOrd for MinutesTZ<N> {
     fn partial_cmp(&self, other: &MinutesTZ<N>) -> Option<cmp::Ordering> {
-        todo!()
+        Some(self.cmp(other))
     }
 }
 impl<const N: u32> Ord for MinutesTZ<N> {
     fn cmp(&self, other: &Self) -> cmp::Ordering {
-        todo!()
+        self.index.cmp(&other.index)
     }
 }
 
 
-impl<const N: u32> fmt::Display for Minutes<N> {
+impl<const N: u32> PartialEq<chrono::NaiveDateTime> for Minutes<N> {
+    fn eq(&self, other: &chrono::NaiveDateTime) -> bool {
+        crate::cmp_date_time(self, other) == cmp::Ordering::Equal
+    }
+}
+
+impl<const N: u32> PartialOrd<chrono::NaiveDateTime> for Minutes<N> {
+    fn partial_cmp(&self, other: &chrono::NaiveDateTime) -> Option<cmp::Ordering> {
+        Some(crate::cmp_date_time(self, other))
+    }
+}
+
+// A `Minutes` period where the length is only known at runtime,
+// which allows type-erased formatting and parsing of any length
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct DynMinutes {
+    length: u32,
+    index: i64,
+}
+
+impl DynMinutes {
+    pub(crate) fn new(length: u32, index: i64) -> crate::Result<DynMinutes> {
+        let secs = i64::from(length) * NUM_SECS;
+        let min = chrono::NaiveDateTime::MIN.timestamp() / secs;
+        let max = chrono::NaiveDateTime::MAX.timestamp() / secs;
+        if length > 0 && (min..=max).contains(&index) {
+            Ok(DynMinutes { length, index })
+        } else {
+            Err(crate::Error::InvalidMonotonic {
+                ty_name: "Minutes",
+                index,
+            })
+        }
+    }
+    // Accepts the start of the period, or the full `Display` output
+    // of the period. The start must be aligned to the period length.
+    pub(crate) fn parse(length: u32, s: &str) -> crate::Result<DynMinutes> {
+        DynMinutes::parse_rounded(length, s, crate::Rounding::Strict)
+    }
+    // as `parse`, using `rounding` to choose the period when the start isn't aligned
+    pub(crate) fn parse_rounded(length: u32, s: &str, rounding: crate::Rounding) -> crate::Result<DynMinutes> {
+        let err = || crate::Error::ParseCustom {
+            ty_name: "Minutes",
+            input: s.to_string(),
+        };
+        if length == 0 {
+            return Err(err());
+        }
+        let secs = i64::from(length) * NUM_SECS;
+        let mut parts = s.split(" - ");
+        let start = parse_date_time(parts.next().ok_or_else(err)?)?;
+        let offset = start.timestamp().rem_euclid(secs);
+        let floor = start.timestamp().div_euclid(secs);
+        let index = match (offset == 0 && start.nanosecond() == 0, rounding) {
+            (true, _) | (false, crate::Rounding::Floor) => floor,
+            (false, crate::Rounding::Ceil) => floor + 1,
+            (false, crate::Rounding::Nearest) => floor + i64::from(2 * offset >= secs),
+            (false, crate::Rounding::Strict) => return Err(err()),
+        };
+        let parsed = DynMinutes::new(length, index)?;
+        match (parts.next(), parts.next()) {
+            (None, None) => Ok(parsed),
+            (Some(end), None) if parse_date_time(end)? == parsed.end() => Ok(parsed),
+            _ => Err(err()),
+        }
+    }
+    pub(crate) fn index(&self) -> i64 {
+        self.index
+    }
+    fn start(&self) -> chrono::NaiveDateTime {
+        date_time_at(self.length, self.index)
+    }
+    fn end(&self) -> chrono::NaiveDateTime {
+        date_time_at(self.length, self.index + 1)
+    }
+}
+
+fn date_time_at(length: u32, index: i64) -> chrono::NaiveDateTime {
+    chrono::NaiveDateTime::from_timestamp_opt(index * NUM_SECS * i64::from(length), 0)
+        .expect("Not pre/post historic")
+}
+
+impl fmt::Display for DynMinutes {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if N == 1 {
-            write!(f, "{}", self.naive_date_time())
+        if self.length == 1 {
+            write!(f, "{}", self.start())
         } else {
-            write!(f, "{} - {}", self.naive_date_time(), self.succ().naive_date_time())
+            write!(f, "{} - {}", self.start(), self.end())
         }
     }
 }
 
+impl<const N: u32> fmt::Display for Minutes<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        DynMinutes { length: N, index: self.index }.fmt(f)
+    }
+}
+
+impl<const N: u32> str::FromStr for Minutes<N> {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DynMinutes::parse(N, s).map(|p| Minutes::from_monotonic(p.index))
+    }
+}
+
+fn parse_date_time(s: &str) -> crate::Result<chrono::NaiveDateTime> {
+    let mut parsed = chrono::NaiveDateTime::parse_from_str(s, PARSE_FORMATS[0]);
+    for format in &PARSE_FORMATS[1..] {
+        parsed = parsed.or_else(|_| chrono::NaiveDateTime::parse_from_str(s, format));
+    }
+    Ok(parsed?)
+}
+
+impl<'a, const N: u32> convert::TryFrom<&'a str> for Minutes<N> {
+    type Error = crate::Error;
+    fn try_from(s: &'a str) -> crate::Result<Minutes<N>> {
+        s.parse()
+    }
+}
+
+impl<const N: u32> convert::TryFrom<i64> for Minutes<N> {
+    type Error = crate::Error;
+    fn try_from(idx: i64) -> crate::Result<Minutes<N>> {
+        <Minutes<N> as crate::TimeResolution>::try_from_monotonic(idx)
+    }
+}
+
 impl<const N: u32> crate::TimeResolution for Minutes<N> {
+    // an unaligned start in the usual format is rounded down unless `strict_alignment` is set
+    fn parse_with(s: &str, options: crate::ParseOptions) -> crate::Result<Self> {
+        DynMinutes::parse_rounded(N, options.trim(s, "Minutes")?, options.rounding())
+            .map(|p| Minutes::from_monotonic(p.index))
+            .or_else(|_| crate::parse::parse_with(s, options))
+    }
+    fn name(&self) -> borrow::Cow<'static, str> {
+        // the common lengths don't need to allocate
+        match N {
+            1 => borrow::Cow::Borrowed("Minutes[Length:1]"),
+            5 => borrow::Cow::Borrowed("Minutes[Length:5]"),
+            15 => borrow::Cow::Borrowed("Minutes[Length:15]"),
+            30 => borrow::Cow::Borrowed("Minutes[Length:30]"),
+            60 => borrow::Cow::Borrowed("Minutes[Length:60]"),
+            _ => borrow::Cow::Owned(format!("Minutes[Length:{}]", N)),
+        }
+    }
     fn between(&self, other: Self) -> i64 {
         other.index - self.index
     }
     fn succ_n(&self, n: u32) -> Minutes<N> {
-        Minutes { index: self.index + i64::from(n)}
+        Minutes::succ_n(self, n)
     }
     fn pred_n(&self, n: u32) -> Minutes<N> {
-        Minutes { index: self.index - i64::from(n)}
+        Minutes::pred_n(self, n)
     }
     fn naive_date_time(&self) -> chrono::NaiveDateTime {
-        chrono::NaiveDateTime::from_timestamp(self.index * NUM_SECS * i64::from(N), 0)
+        date_time_at(N, self.index)
+    }
+    fn from_datetime(dt: chrono::NaiveDateTime) -> Self {
+        Minutes::from_monotonic(dt.timestamp().div_euclid(i64::from(N) * NUM_SECS))
+    }
+    fn from_utc_timestamp(secs: i64) -> Self {
+        Minutes::from_monotonic(secs.div_euclid(i64::from(N) * NUM_SECS))
+    }
+    fn start_timestamp(&self) -> i64 {
+        self.index * i64::from(N) * NUM_SECS
     }
     fn to_monotonic(&self) -> i64 {
-        self.index
+        Minutes::to_monotonic(self)
     }
     fn from_monotonic(index: i64) -> Self {
-        Minutes { index }
+        Minutes::from_monotonic(index)
+    }
+    fn try_from_monotonic(idx: i64) -> crate::Result<Self> {
+        DynMinutes::new(N, idx).map(|p| Minutes::from_monotonic(p.index))
     }
 }
 
-impl<const N: u32> Minutes<N> {}
+impl<const N: u32> Minutes<N> {
+    // the start of the period with monotonic index 0, the unix epoch
+    pub fn monotonic_epoch() -> chrono::NaiveDateTime {
+        chrono::NaiveDateTime::from_timestamp_opt(0, 0).expect("Unix epoch is always valid")
+    }
+    // Every period is created through `from_monotonic`, so referencing this there means that
+    // creating a period with a length which doesn't divide a day (eg: `Minutes<7>`) fails to build.
+    // This is only evaluated when generating code, so `cargo check` doesn't report it.
+    const VALID: () = assert!(N > 0 && 24 * 60 % N == 0, "Minutes<N> requires N to divide 1440, the minutes in a day");
+
+    pub const fn from_monotonic(index: i64) -> Minutes<N> {
+        let () = Self::VALID;
+        Minutes { index }
+    }
+    pub const fn to_monotonic(&self) -> i64 {
+        self.index
+    }
+    pub const fn succ_n(&self, n: u32) -> Minutes<N> {
+        Minutes { index: self.index + n as i64 }
+    }
+    pub const fn pred_n(&self, n: u32) -> Minutes<N> {
+        Minutes { index: self.index - n as i64 }
+    }
+    // errors unless the time is the start of a period, as when parsing
+    pub fn from_date_hm(date: chrono::NaiveDate, hour: u32, minute: u32) -> crate::Result<Minutes<N>> {
+        if hour >= 24 {
+            return Err(crate::Error::InvalidComponent { component: "hour", value: hour });
+        }
+        if minute >= 60 {
+            return Err(crate::Error::InvalidComponent { component: "minute", value: minute });
+        }
+        let date_time = date.and_hms_opt(hour, minute, 0).expect("Hour and minute have been checked");
+        Self::from_datetime_with(date_time, crate::Rounding::Strict)
+    }
+    // the same time of day `n` years later (or earlier), with `policy` deciding
+    // how periods on the 29th of February are handled. Errors when out of chrono's range.
+    pub fn offset_years(&self, n: i32, policy: crate::MonthEndPolicy) -> crate::Result<Minutes<N>> {
+        let date_time = self.naive_date_time();
+        let date = Date::from(date_time.date()).offset_years(n, policy)?;
+        Ok(Self::from_datetime(date.start().and_time(date_time.time())))
+    }
+    pub fn year_over_year(&self, policy: crate::MonthEndPolicy) -> crate::Result<Minutes<N>> {
+        self.offset_years(-1, policy)
+    }
+    pub fn hour(&self) -> u32 {
+        self.naive_date_time().hour()
+    }
+    pub fn minute(&self) -> u32 {
+        self.naive_date_time().minute()
+    }
+    pub fn day(&self) -> Date {
+        Date::from(self.occurs_on_date())
+    }
+    // parses the start of the period with a chrono format string, eg: "%d/%m/%Y %H:%M".
+    // The start must be aligned to the period length.
+    pub fn parse_from_str(s: &str, format: &str) -> crate::Result<Minutes<N>> {
+        let dt = chrono::NaiveDateTime::parse_from_str(s, format)?;
+        <Minutes<N> as TimeResolution>::from_datetime_with(dt, crate::Rounding::Strict)
+    }
+    // the days which the period is at least partly within, always a single day
+    // as the length divides a day (see `UncheckedMinutes` for when it doesn't)
+    pub fn occurs_on_dates(&self) -> TimeRange<Date> {
+        occurs_on_dates(N, self.index)
+    }
+    pub fn days_spanned(&self) -> u32 {
+        convert::TryFrom::try_from(self.occurs_on_dates().len()).expect("Periods are shorter than a day")
+    }
+    // `index` is the 0-based period within the day
+    pub fn from_day_and_index(day: Date, index: u32) -> Option<Self> {
+        if index < Self::periods_per_day() {
+            Some(Self::first_on_day(day.start()).succ_n(index))
+        } else {
+            None
+        }
+    }
+}
 
 impl<const N: u32> crate::SubDateResolution for Minutes<N> {
     fn occurs_on_date(&self) -> chrono::NaiveDate {
         self.naive_date_time().date()
     }
     fn first_on_day(day: chrono::NaiveDate) -> Self {
-        Self::from_monotonic(day.and_hms(0, 0, 0).timestamp() / (i64::from(N)*NUM_SECS))
+        Self::from_monotonic(first_index_on_day(N, day))
+    }
+    fn periods_per_day() -> u32 {
+        24 * 60 / N
+    }
+}
+
+// the index of the first period of `length` minutes starting at or after midnight
+fn first_index_on_day(length: u32, day: chrono::NaiveDate) -> i64 {
+    let midnight = day.and_hms_opt(0, 0, 0).expect("Midnight is always valid").timestamp();
+    let secs = i64::from(length) * NUM_SECS;
+    let partial = if midnight.rem_euclid(secs) == 0 { 0 } else { 1 };
+    midnight.div_euclid(secs) + partial
+}
+
+fn occurs_on_dates(length: u32, index: i64) -> TimeRange<Date> {
+    let last = date_time_at(length, index + 1) - chrono::Duration::nanoseconds(1);
+    TimeRange::from_start_end(Date::from(date_time_at(length, index).date()), Date::from(last.date()))
+        .expect("Periods end after they start")
+}
+
+// A period of `N` minutes, where `N` needn't divide a day (eg: 7 or 90 minutes). Periods are
+// counted from the unix epoch, so they don't line up with midnight, and may span it. A period
+// belongs to the day that it starts on, so days can have different numbers of periods.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub struct UncheckedMinutes<const N: u32> {
+    index: i64,
+}
+
+impl<const N: u32> UncheckedMinutes<N> {
+    const VALID: () = assert!(N > 0, "UncheckedMinutes<N> requires N to be greater than zero");
+
+    pub const fn from_monotonic(index: i64) -> UncheckedMinutes<N> {
+        let () = Self::VALID;
+        UncheckedMinutes { index }
+    }
+    pub const fn to_monotonic(&self) -> i64 {
+        self.index
+    }
+    // the days which the period is at least partly within
+    pub fn occurs_on_dates(&self) -> TimeRange<Date> {
+        occurs_on_dates(N, self.index)
+    }
+    // 1 unless the period spans midnight
+    pub fn days_spanned(&self) -> u32 {
+        convert::TryFrom::try_from(self.occurs_on_dates().len()).expect("Periods are shorter than a day")
+    }
+    // `index` is the 0-based period within the day, the inverse of `sub_index_on_day`.
+    // Days can have different numbers of periods, so this checks against the day itself.
+    pub fn from_day_and_index(day: Date, index: u32) -> Option<Self> {
+        let periods = Self::periods_on_day(day.start());
+        if index < periods.len {
+            Some(periods.start().succ_n(index))
+        } else {
+            None
+        }
+    }
+}
+
+impl<const N: u32> fmt::Display for UncheckedMinutes<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        DynMinutes { length: N, index: self.index }.fmt(f)
+    }
+}
+
+impl<const N: u32> str::FromStr for UncheckedMinutes<N> {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DynMinutes::parse(N, s).map(|p| UncheckedMinutes::from_monotonic(p.index))
+    }
+}
+
+impl<const N: u32> crate::TimeResolution for UncheckedMinutes<N> {
+    fn parse_with(s: &str, options: crate::ParseOptions) -> crate::Result<Self> {
+        DynMinutes::parse_rounded(N, options.trim(s, "UncheckedMinutes")?, options.rounding())
+            .map(|p| UncheckedMinutes::from_monotonic(p.index))
+            .or_else(|_| crate::parse::parse_with(s, options))
+    }
+    fn name(&self) -> borrow::Cow<'static, str> {
+        borrow::Cow::Owned(format!("UncheckedMinutes[Length:{}]", N))
+    }
+    fn between(&self, other: Self) -> i64 {
+        other.index - self.index
+    }
+    fn succ_n(&self, n: u32) -> UncheckedMinutes<N> {
+        UncheckedMinutes::from_monotonic(self.index + i64::from(n))
+    }
+    fn pred_n(&self, n: u32) -> UncheckedMinutes<N> {
+        UncheckedMinutes::from_monotonic(self.index - i64::from(n))
+    }
+    fn naive_date_time(&self) -> chrono::NaiveDateTime {
+        date_time_at(N, self.index)
+    }
+    fn from_datetime(dt: chrono::NaiveDateTime) -> Self {
+        UncheckedMinutes::from_monotonic(dt.timestamp().div_euclid(i64::from(N) * NUM_SECS))
+    }
+    fn from_utc_timestamp(secs: i64) -> Self {
+        UncheckedMinutes::from_monotonic(secs.div_euclid(i64::from(N) * NUM_SECS))
+    }
+    fn start_timestamp(&self) -> i64 {
+        self.index * i64::from(N) * NUM_SECS
+    }
+    fn to_monotonic(&self) -> i64 {
+        self.index
+    }
+    fn from_monotonic(index: i64) -> Self {
+        UncheckedMinutes::from_monotonic(index)
+    }
+    fn try_from_monotonic(idx: i64) -> crate::Result<Self> {
+        DynMinutes::new(N, idx).map(|p| UncheckedMinutes::from_monotonic(p.index))
+    }
+}
+
+// Periods belong to the day that they start on
+impl<const N: u32> crate::SubDateResolution for UncheckedMinutes<N> {
+    fn occurs_on_date(&self) -> chrono::NaiveDate {
+        self.naive_date_time().date()
+    }
+    // the first period starting at or after midnight
+    fn first_on_day(day: chrono::NaiveDate) -> Self {
+        UncheckedMinutes::from_monotonic(first_index_on_day(N, day))
+    }
+    // the fewest that start on any day
+    fn periods_per_day() -> u32 {
+        24 * 60 / N
     }
 }
 
+#[cfg(feature = "serde")]
 impl<'de, const N: u32> serde::Deserialize<'de> for Minutes<N> {
     fn deserialize<D>(deserializer: D) -> Result<Minutes<N>, D::Error>
     where
         D: de::Deserializer<'de>,
     {
-        todo!()
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
     }
 }
 
+#[cfg(feature = "serde")]
 impl<const N: u32> serde::Serialize for Minutes<N> {
     fn serialize<SER>(&self, serializer: SER) -> Result<SER::Ok, SER::Error>
     where
         SER: ser::Serializer,
     {
-        todo!()
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: u32> serde::Deserialize<'de> for UncheckedMinutes<N> {
+    fn deserialize<D>(deserializer: D) -> Result<UncheckedMinutes<N>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const N: u32> serde::Serialize for UncheckedMinutes<N> {
+    fn serialize<SER>(&self, serializer: SER) -> Result<SER::Ok, SER::Error>
+    where
+        SER: ser::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Minutes, UncheckedMinutes};
+    use crate::{Date, DateResolution, Error, MonthEndPolicy, SubDateResolution, TimeRange, TimeResolution};
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_offset_years() {
+        let period = "2021-07-15 10:30".parse::<Minutes<30>>().unwrap();
+        assert_eq!(
+            period.year_over_year(MonthEndPolicy::Clamp).unwrap(),
+            "2020-07-15 10:30".parse::<Minutes<30>>().unwrap()
+        );
+        let leap_day = "2020-02-29 23:55".parse::<Minutes<5>>().unwrap();
+        assert_eq!(
+            leap_day.offset_years(1, MonthEndPolicy::Clamp).unwrap(),
+            "2021-02-28 23:55".parse::<Minutes<5>>().unwrap()
+        );
+        assert_eq!(
+            "2021-02-28 00:00".parse::<Minutes<5>>().unwrap().year_over_year(MonthEndPolicy::Preserve).unwrap(),
+            "2020-02-29 00:00".parse::<Minutes<5>>().unwrap()
+        );
+        assert!(period.offset_years(1_000_000, MonthEndPolicy::Clamp).is_err());
+    }
+
+    #[test]
+    fn test_from_date_hm() {
+        let date = chrono::NaiveDate::from_ymd_opt(2021, 7, 15).unwrap();
+        assert_eq!(
+            Minutes::<30>::from_date_hm(date, 10, 30).unwrap(),
+            "2021-07-15 10:30".parse::<Minutes<30>>().unwrap()
+        );
+        assert_eq!(
+            Minutes::<5>::from_date_hm(date, 23, 55).unwrap(),
+            "2021-07-15 23:55".parse::<Minutes<5>>().unwrap()
+        );
+        assert!(matches!(
+            Minutes::<30>::from_date_hm(date, 10, 15),
+            Err(crate::Error::NotOnBoundary(_))
+        ));
+        // the same inputs are rejected when parsing
+        assert!("2021-07-15 10:15".parse::<Minutes<30>>().is_err());
+        assert!(matches!(
+            Minutes::<1>::from_date_hm(date, 24, 0),
+            Err(crate::Error::InvalidComponent { component: "hour", value: 24 })
+        ));
+        assert!(Minutes::<1>::from_date_hm(date, 0, 60).is_err());
+    }
+
+    #[test]
+    fn test_unchecked_minutes() {
+        let period = UncheckedMinutes::<7>::from_monotonic(1);
+        assert_eq!(period.name(), "UncheckedMinutes[Length:7]");
+        assert_eq!("1970-01-01 00:07".parse::<UncheckedMinutes<7>>().unwrap(), period);
+        assert_eq!(period.to_string(), "1970-01-01 00:07:00 - 1970-01-01 00:14:00");
+        assert_eq!(period.naive_date_time(), chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap().and_hms_opt(0, 7, 0).unwrap());
+
+        // 23:55 on the 1st to 00:02 on the 2nd
+        let day = chrono::NaiveDate::from_ymd_opt(1970, 1, 2).unwrap();
+        let spanning = UncheckedMinutes::<7>::from_monotonic(205);
+        assert_eq!(spanning.days_spanned(), 2);
+        assert_eq!(spanning.occurs_on_dates(), TimeRange::new(Date::from(day.pred_opt().unwrap()), 2));
+        assert_eq!(spanning.occurs_on_date(), day.pred_opt().unwrap());
+        // belongs to the day it starts on
+        let first = UncheckedMinutes::<7>::first_on_day(day);
+        assert_eq!(first, spanning.succ());
+        assert_eq!(first.naive_date_time(), day.and_hms_opt(0, 2, 0).unwrap());
+        assert_eq!(UncheckedMinutes::<7>::last_on_day(day.pred_opt().unwrap()), spanning);
+        // and before the unix epoch
+        let before = chrono::NaiveDate::from_ymd_opt(1969, 12, 31).unwrap();
+        assert_eq!(UncheckedMinutes::<7>::first_on_day(before).naive_date_time(), before.and_hms_opt(0, 5, 0).unwrap());
+        for day in [before, day] {
+            for period in UncheckedMinutes::<7>::periods_on_day(day).iter() {
+                assert_eq!(period.occurs_on_date(), day);
+            }
+        }
+
+        // days have either 205 or 206 periods, and the sub index round trips for all of them
+        let week = TimeRange::new(Date::from_ymd(2021, 7, 15).unwrap(), 7);
+        let mut lengths = Vec::new();
+        for day in week.iter() {
+            let periods = UncheckedMinutes::<7>::periods_on_day(day.start());
+            lengths.push(periods.len());
+            for period in periods.iter() {
+                assert_eq!(UncheckedMinutes::<7>::from_day_and_index(day, period.sub_index_on_day()), Some(period));
+            }
+            assert_eq!(UncheckedMinutes::<7>::from_day_and_index(day, periods.len), None);
+        }
+        assert_eq!(lengths, vec![206, 206, 206, 205, 206, 206, 205]);
+    }
+
+    #[test]
+    fn test_name() {
+        let half_hour = Minutes::<30>::from_monotonic(0);
+        assert!(matches!(half_hour.name(), Cow::Borrowed("Minutes[Length:30]")));
+        let odd = Minutes::<45>::from_monotonic(0);
+        assert_eq!(odd.name(), "Minutes[Length:45]");
+        #[allow(deprecated)]
+        let name = half_hour.name_string();
+        assert_eq!(name, "Minutes[Length:30]");
+    }
+
+    #[test]
+    fn test_periods_on_day() {
+        let day = chrono::NaiveDate::from_ymd_opt(2021, 3, 4).unwrap();
+        assert_eq!(Minutes::<30>::periods_per_day(), 48);
+        assert_eq!(Minutes::<5>::periods_per_day(), 288);
+
+        let periods = Minutes::<30>::periods_on_day(day);
+        assert_eq!(periods.len(), 48);
+        assert_eq!(periods.start().naive_date_time(), day.and_hms_opt(0, 0, 0).unwrap());
+        assert_eq!(periods.end().naive_date_time(), day.and_hms_opt(23, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn test_time_of_day() {
+        let time = |h, m| chrono::NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        let day = chrono::NaiveDate::from_ymd_opt(2021, 3, 4).unwrap();
+        let period = "2021-03-04 10:30".parse::<Minutes<30>>().unwrap();
+        assert_eq!(period.time_of_day(), time(10, 30));
+        assert_eq!(period.end_time_of_day(), time(11, 0));
+        assert_eq!(Minutes::<30>::last_on_day(day).end_time_of_day(), time(0, 0));
+        assert_eq!(Minutes::<30>::on_date_at(day, time(10, 30)).unwrap(), period);
+        assert!(matches!(Minutes::<30>::on_date_at(day, time(10, 15)), Err(Error::NotOnBoundary(_))));
+        assert!(Minutes::<5>::on_date_at(day, chrono::NaiveTime::from_hms_opt(10, 30, 1).unwrap()).is_err());
+
+        // a tariff window from 07:00 to 22:00
+        let peak = TimeRange::from_start_end_exclusive(
+            Minutes::<30>::on_date_at(day, time(7, 0)).unwrap(),
+            Minutes::<30>::on_date_at(day, time(22, 0)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(peak.len(), 30);
+        assert!(peak.iter().all(|p| p.time_of_day() >= time(7, 0) && p.end_time_of_day() <= time(22, 0)));
+        for period in Minutes::<15>::periods_on_day(day).iter() {
+            assert_eq!(Minutes::<15>::on_date_at(day, period.time_of_day()).unwrap(), period);
+        }
+    }
+
+    #[test]
+    fn test_occurs_on_dates() {
+        let period = "2021-03-04 23:30".parse::<Minutes<30>>().unwrap();
+        assert_eq!(period.days_spanned(), 1);
+        assert_eq!(period.occurs_on_dates(), TimeRange::new(period.day(), 1));
+        let period = "1969-12-31 23:15".parse::<Minutes<45>>().unwrap();
+        assert_eq!(period.occurs_on_dates(), TimeRange::new(period.day(), 1));
+    }
+
+    #[test]
+    fn test_periods_on_day_with_offset() {
+        let day = chrono::NaiveDate::from_ymd_opt(2021, 3, 4).unwrap();
+        let gas_day = chrono::Duration::hours(6);
+        let periods = Minutes::<30>::periods_on_day_with_offset(day, gas_day);
+        assert_eq!(periods.len(), 48);
+        assert_eq!(periods.start().naive_date_time(), day.and_hms_opt(6, 0, 0).unwrap());
+        assert_eq!(periods.end().naive_date_time(), day.succ_opt().unwrap().and_hms_opt(5, 30, 0).unwrap());
+        assert_eq!(periods.start().occurs_on_date_with_offset(gas_day), day);
+        assert_eq!(periods.end().occurs_on_date_with_offset(gas_day), day);
+        assert_eq!(periods.start().pred().occurs_on_date_with_offset(gas_day), day.pred_opt().unwrap());
+
+        // a day which starts the evening before
+        let evening = chrono::Duration::hours(-2);
+        let first = Minutes::<60>::first_on_day_with_offset(day, evening);
+        assert_eq!(first.naive_date_time(), day.pred_opt().unwrap().and_hms_opt(22, 0, 0).unwrap());
+        assert_eq!(first.occurs_on_date_with_offset(evening), day);
+        assert_eq!(
+            Minutes::<60>::first_on_day_with_offset(day, chrono::Duration::zero()),
+            Minutes::<60>::first_on_day(day)
+        );
+    }
+
+    #[test]
+    fn test_sub_index_on_day() {
+        let day = chrono::NaiveDate::from_ymd_opt(2021, 3, 4).unwrap();
+        let first = Minutes::<30>::first_on_day(day);
+        assert_eq!(first.sub_index_on_day(), 0);
+        assert_eq!(first.succ_n(13).sub_index_on_day(), 13);
+        assert_eq!(Minutes::<30>::last_on_day(day).sub_index_on_day(), 47);
+        assert_eq!(Minutes::<5>::last_on_day(day).sub_index_on_day(), 287);
+    }
+
+    #[test]
+    fn test_intra_day_accessors() {
+        let day = Date::from_ymd(2021, 3, 4).unwrap();
+        let period = Minutes::<30>::from_day_and_index(day, 21).unwrap();
+        assert_eq!(period.hour(), 10);
+        assert_eq!(period.minute(), 30);
+        assert_eq!(period.time_of_day(), chrono::NaiveTime::from_hms_opt(10, 30, 0).unwrap());
+        assert_eq!(period.day(), day);
+        assert_eq!(Minutes::<30>::from_day_and_index(day, 47).unwrap().minute(), 30);
+        assert_eq!(Minutes::<30>::from_day_and_index(day, 48), None);
+    }
+
+    #[test]
+    fn test_try_from_monotonic() {
+        let p = Minutes::<5>::first_on_day(chrono::NaiveDate::from_ymd_opt(2021, 3, 4).unwrap());
+        assert_eq!(Minutes::<5>::try_from_monotonic(p.to_monotonic()).unwrap(), p);
+        assert!(Minutes::<5>::try_from_monotonic(i64::MAX).is_err());
+        assert!(Minutes::<5>::try_from_monotonic(i64::MIN).is_err());
+    }
+
+    #[test]
+    fn test_from_str() {
+        let p = "2021-03-04 10:30".parse::<Minutes<30>>().unwrap();
+        assert_eq!(p.hour(), 10);
+        assert_eq!(p.minute(), 30);
+        assert_eq!(p.to_string().parse::<Minutes<30>>().unwrap(), p);
+        assert_eq!("2021-03-04 10:30:00".parse::<Minutes<30>>().unwrap(), p);
+        assert!("2021-03-04 10:35".parse::<Minutes<30>>().is_err());
+        assert!("2021-03-04 10:30:00 - 2021-03-04 11:30:00".parse::<Minutes<30>>().is_err());
+        assert!("2021-03-04".parse::<Minutes<30>>().is_err());
+    }
+
+    #[test]
+    fn test_from_str_formats() {
+        let p = "2021-03-04 10:30".parse::<Minutes<30>>().unwrap();
+        assert_eq!("2021-03-04T10:30".parse::<Minutes<30>>().unwrap(), p);
+        assert_eq!("2021-03-04T10:30:00".parse::<Minutes<30>>().unwrap(), p);
+        assert_eq!("2021-03-04 10:30:00.000".parse::<Minutes<30>>().unwrap(), p);
+        assert_eq!("2021-03-04T10:30:00 - 2021-03-04T11:00:00".parse::<Minutes<30>>().unwrap(), p);
+        assert!("2021-03-04T10:30:01".parse::<Minutes<30>>().is_err());
+        assert!("2021-03-04 10:30:00.5".parse::<Minutes<30>>().is_err());
+        assert!("2021-03-04T10:35".parse::<Minutes<30>>().is_err());
+
+        assert_eq!(Minutes::<30>::parse_from_str("04/03/2021 10:30", "%d/%m/%Y %H:%M").unwrap(), p);
+        assert!(matches!(
+            Minutes::<30>::parse_from_str("04/03/2021 10:35", "%d/%m/%Y %H:%M"),
+            Err(Error::NotOnBoundary(_))
+        ));
+        assert!(matches!(
+            Minutes::<30>::parse_from_str("2021-03-04 10:30", "%d/%m/%Y %H:%M"),
+            Err(Error::ParseDate(_))
+        ));
+    }
+
+    #[test]
+    fn test_try_from() {
+        use std::convert::TryFrom;
+        let p = Minutes::<5>::try_from("2021-03-04 10:35").unwrap();
+        assert_eq!(Minutes::<5>::try_from(p.to_monotonic()).unwrap(), p);
+        assert!(Minutes::<5>::try_from(i64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_cmp_date_time() {
+        let p = "2021-03-04 10:30".parse::<Minutes<30>>().unwrap();
+        let day = chrono::NaiveDate::from_ymd_opt(2021, 3, 4).unwrap();
+        assert_eq!(p, day.and_hms_opt(10, 30, 0).unwrap());
+        assert_eq!(p, day.and_hms_opt(10, 59, 59).unwrap());
+        assert!(p < day.and_hms_opt(11, 0, 0).unwrap());
+        assert!(p > day.and_hms_opt(10, 29, 59).unwrap());
+    }
+
+    #[test]
+    fn test_from_datetime_with() {
+        use crate::Rounding;
+        let day = chrono::NaiveDate::from_ymd_opt(2021, 3, 4).unwrap();
+        let p = "2021-03-04 10:30".parse::<Minutes<30>>().unwrap();
+        let on = day.and_hms_opt(10, 30, 0).unwrap();
+        for rounding in &[Rounding::Floor, Rounding::Ceil, Rounding::Nearest, Rounding::Strict] {
+            assert_eq!(Minutes::<30>::from_datetime_with(on, *rounding).unwrap(), p);
+        }
+        let early = day.and_hms_opt(10, 40, 0).unwrap();
+        assert_eq!(Minutes::<30>::from_datetime_with(early, Rounding::Floor).unwrap(), p);
+        assert_eq!(Minutes::<30>::from_datetime_with(early, Rounding::Ceil).unwrap(), p.succ());
+        assert_eq!(Minutes::<30>::from_datetime_with(early, Rounding::Nearest).unwrap(), p);
+        assert!(Minutes::<30>::from_datetime_with(early, Rounding::Strict).is_err());
+        let middle = day.and_hms_opt(10, 45, 0).unwrap();
+        assert_eq!(Minutes::<30>::from_datetime_with(middle, Rounding::Nearest).unwrap(), p.succ());
+        let before_epoch = chrono::NaiveDate::from_ymd_opt(1969, 12, 31).unwrap().and_hms_opt(23, 50, 0).unwrap();
+        assert_eq!(Minutes::<30>::from_datetime(before_epoch).to_monotonic(), -1);
     }
 }
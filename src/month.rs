@@ -1,13 +1,17 @@
-use crate::{quarter, year, DateResolution};
+use crate::{date, quarter, year, DateResolution, TimeRange};
 use chrono::Datelike;
-use serde::{
-    de,
-    ser::{self, SerializeStruct},
-};
-use std::{str, cmp, convert::TryFrom, fmt};
+#[cfg(feature = "serde")]
+use serde::de;
+use std::{borrow, cmp, convert::{self, TryFrom}, fmt, str};
 
-const DATE_FORMAT: &str = "%b-%Y";
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
 
+// chrono can't parse a date without a day, so one is added
+const PARSE_FORMAT: &str = "%d-%b-%Y";
+
+#[cfg(feature = "serde")]
 impl<'de> de::Deserialize<'de> for Month 
 {
     fn deserialize<D>(
@@ -17,12 +21,11 @@ impl<'de> de::Deserialize<'de> for Month
         D: de::Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        let date = chrono::NaiveDate::parse_from_str(&s, DATE_FORMAT)
-            .map_err(serde::de::Error::custom)?;
-        Ok(Month::from_date(date))
+        s.parse().map_err(serde::de::Error::custom)
     }
 }
 
+#[cfg(feature = "serde")]
 impl serde::Serialize for Month {
     fn serialize<S>(
         &self,
@@ -40,34 +43,65 @@ impl serde::Serialize for Month {
 impl str::FromStr for Month {
     type Err = crate::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let date = chrono::NaiveDate::parse_from_str(s, DATE_FORMAT)?;
+        let date = chrono::NaiveDate::parse_from_str(&format!("01-{}", s), PARSE_FORMAT)?;
         Ok(Month::from_date(date))
     }
 }
 
+impl<'a> convert::TryFrom<&'a str> for Month {
+    type Error = crate::Error;
+    fn try_from(s: &'a str) -> crate::Result<Month> {
+        s.parse()
+    }
+}
+
+impl convert::TryFrom<i64> for Month {
+    type Error = crate::Error;
+    fn try_from(idx: i64) -> crate::Result<Month> {
+        <Month as crate::TimeResolution>::try_from_monotonic(idx)
+    }
+}
+
 
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes, compare(PartialEq, PartialOrd)))]
+#[cfg_attr(feature = "rkyv", archive_attr(derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)))]
 pub struct Month(i64); // number of months +- since 0AD
 
 impl crate::TimeResolution for Month {
+    fn name(&self) -> borrow::Cow<'static, str> {
+        borrow::Cow::Borrowed("Month")
+    }
     fn between(&self, other: Self) -> i64 {
-        i64::from(other.0 - self.0)
+        other.0 - self.0
     }
     fn succ_n(&self, n: u32) -> Self {
-        Month(self.0 + i64::from(n))
+        Month::succ_n(self, n)
     }
     fn pred_n(&self, n: u32) -> Self {
-        Month(self.0 - i64::from(n))
+        Month::pred_n(self, n)
     }
     fn naive_date_time(&self) -> chrono::NaiveDateTime {
-        self.start().and_hms(0, 0, 0)
+        self.start().and_hms_opt(0, 0, 0).expect("Midnight is always valid")
+    }
+    fn from_datetime(dt: chrono::NaiveDateTime) -> Self {
+        Month::from_date(dt.date())
     }
     fn to_monotonic(&self) -> i64 {
-        self.0
+        Month::to_monotonic(self)
     }
     fn from_monotonic(idx: i64) -> Self {
-        Month(idx)
+        Month::from_monotonic(idx)
+    }
+    fn try_from_monotonic(idx: i64) -> crate::Result<Self> {
+        crate::check_monotonic(
+            "Month",
+            idx,
+            Month::from_date(chrono::NaiveDate::MIN),
+            Month::from_date(chrono::NaiveDate::MAX),
+        )
     }
 }
 
@@ -76,12 +110,38 @@ impl crate::DateResolution for Month {
     fn start(&self) -> chrono::NaiveDate {
         let years = i32::try_from(self.0.div_euclid(12)).expect("Not pre/post historic");
         let months = u32::try_from(1 + self.0.rem_euclid(12)).unwrap();
-        dbg!(months);
-        chrono::NaiveDate::from_ymd(years, months, 1)
+        chrono::NaiveDate::from_ymd_opt(years, months, 1).expect("Not pre/post historic")
     }
 }
 
+// How to move a date to another month when its day doesn't exist there
+// (eg: the 31st of January, moved to February)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MonthEndPolicy {
+    // use the last day of the month when the day doesn't exist
+    Clamp,
+    // as `Clamp`, but the last day of a month always maps to the last day,
+    // so the 28th of February maps to the 31st of March
+    Preserve,
+}
+
 impl Month {
+    // the start of the period with monotonic index 0
+    pub fn monotonic_epoch() -> chrono::NaiveDateTime {
+        Month(0).start().and_hms_opt(0, 0, 0).expect("Midnight is always valid")
+    }
+    pub const fn from_monotonic(idx: i64) -> Month {
+        Month(idx)
+    }
+    pub const fn to_monotonic(&self) -> i64 {
+        self.0
+    }
+    pub const fn succ_n(&self, n: u32) -> Month {
+        Month(self.0 + n as i64)
+    }
+    pub const fn pred_n(&self, n: u32) -> Month {
+        Month(self.0 - n as i64)
+    }
     pub fn year(&self) -> year::Year {
         year::Year::from_date(self.start())
     }
@@ -95,37 +155,186 @@ impl Month {
         self.start().month()
     }
     pub fn from_date(d: chrono::NaiveDate) -> Self {
-        todo!()
+        Month(i64::from(d.year()) * 12 + i64::from(d.month0()))
+    }
+    pub const fn from_year_month(year: i32, month: u32) -> crate::Result<Self> {
+        if matches!(month, 1..=12) {
+            Ok(Month(year as i64 * 12 + (month - 1) as i64))
+        } else {
+            Err(crate::Error::InvalidComponent { component: "month", value: month })
+        }
+    }
+    // the same month `n` years later (or earlier), erroring when out of chrono's range
+    pub fn offset_years(&self, n: i32) -> crate::Result<Month> {
+        crate::TimeResolution::try_from_monotonic(self.0 + i64::from(n) * 12)
+    }
+    pub fn year_over_year(&self) -> crate::Result<Month> {
+        self.offset_years(-1)
+    }
+    pub fn days(&self) -> TimeRange<date::Date> {
+        TimeRange::new(date::Date::from(self.start()), self.num_days())
+    }
+    pub fn num_days(&self) -> u32 {
+        self.last_day().start().day()
+    }
+    pub fn last_day(&self) -> date::Date {
+        // rather than `self.end()`, which overflows for the last month chrono can represent
+        (28..=31).rev().find_map(|day| self.with_day(day)).expect("Every month has at least 28 days")
+    }
+    pub fn with_day(&self, day: u32) -> Option<date::Date> {
+        chrono::NaiveDate::from_ymd_opt(self.year_num(), self.month_num(), day).map(date::Date::from)
+    }
+    // the date in this month with the same day of the month as `date`
+    pub fn with_same_day_as(&self, date: date::Date, policy: MonthEndPolicy) -> date::Date {
+        let day = date.start().day();
+        let is_month_end = Month::from_date(date.start()).num_days() == day;
+        if policy == MonthEndPolicy::Preserve && is_month_end {
+            return self.last_day();
+        }
+        self.with_day(day).unwrap_or_else(|| self.last_day())
+    }
+}
+
+impl PartialEq<chrono::NaiveDate> for Month {
+    fn eq(&self, other: &chrono::NaiveDate) -> bool {
+        crate::cmp_date(self, other) == cmp::Ordering::Equal
+    }
+}
+
+impl PartialOrd<chrono::NaiveDate> for Month {
+    fn partial_cmp(&self, other: &chrono::NaiveDate) -> Option<cmp::Ordering> {
+        Some(crate::cmp_date(self, other))
+    }
+}
+
+#[cfg(feature = "locale")]
+impl Month {
+    // eg: `déc. 2021` for `fr_FR`
+    pub fn format_localized(&self, locale: chrono::Locale) -> String {
+        crate::format_localized(self.start(), "%b %Y", locale)
     }
 }
 
 impl fmt::Display for Month {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.start().format("%b-%Y"))
+        // equivalent to the `%b-%Y` format, without the intermediate `String`
+        let name = MONTH_NAMES[self.start().month0() as usize];
+        match self.year_num() {
+            year @ 0..=9999 => write!(f, "{}-{:04}", name, year),
+            year => write!(f, "{}-{:+05}", name, year),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Month;
-    use crate::resolution::DateResolution;
+    use super::{Month, MonthEndPolicy};
+    use crate::{Date, DateResolution};
+
+    #[test]
+    fn test_offset_years() {
+        let jul = Month::from_year_month(2021, 7).unwrap();
+        assert_eq!(jul.year_over_year().unwrap(), Month::from_year_month(2020, 7).unwrap());
+        assert_eq!(jul.offset_years(2).unwrap(), Month::from_year_month(2023, 7).unwrap());
+        assert_eq!(Month::from_year_month(1, 1).unwrap().offset_years(-2).unwrap(), Month::from_year_month(-1, 1).unwrap());
+        assert!(jul.offset_years(1_000_000).is_err());
+        assert!(jul.offset_years(i32::MIN).is_err());
+    }
 
     #[test]
     fn test_start() {
         assert_eq!(
             Month(24240).start(),
-            chrono::NaiveDate::from_ymd(2020, 1, 1)
+            chrono::NaiveDate::from_ymd_opt(2020, 1, 1).unwrap()
         );
         assert_eq!(
             Month(24249).start(),
-            chrono::NaiveDate::from_ymd(2020, 10, 1)
+            chrono::NaiveDate::from_ymd_opt(2020, 10, 1).unwrap()
         );
-        assert_eq!(Month(15).start(), chrono::NaiveDate::from_ymd(1, 4, 1));
-        assert_eq!(Month(2).start(), chrono::NaiveDate::from_ymd(0, 3, 1));
-        assert_eq!(Month(1).start(), chrono::NaiveDate::from_ymd(0, 2, 1));
-        assert_eq!(Month(0).start(), chrono::NaiveDate::from_ymd(0, 1, 1));
-        assert_eq!(Month(-1).start(), chrono::NaiveDate::from_ymd(-1, 12, 1));
-        assert_eq!(Month(-2).start(), chrono::NaiveDate::from_ymd(-1, 11, 1));
-        assert_eq!(Month(-15).start(), chrono::NaiveDate::from_ymd(-2, 10, 1));
+        assert_eq!(Month(15).start(), chrono::NaiveDate::from_ymd_opt(1, 4, 1).unwrap());
+        assert_eq!(Month(2).start(), chrono::NaiveDate::from_ymd_opt(0, 3, 1).unwrap());
+        assert_eq!(Month(1).start(), chrono::NaiveDate::from_ymd_opt(0, 2, 1).unwrap());
+        assert_eq!(Month(0).start(), chrono::NaiveDate::from_ymd_opt(0, 1, 1).unwrap());
+        assert_eq!(Month(-1).start(), chrono::NaiveDate::from_ymd_opt(-1, 12, 1).unwrap());
+        assert_eq!(Month(-2).start(), chrono::NaiveDate::from_ymd_opt(-1, 11, 1).unwrap());
+        assert_eq!(Month(-15).start(), chrono::NaiveDate::from_ymd_opt(-2, 10, 1).unwrap());
+    }
+
+    #[test]
+    fn test_days() {
+        let feb = Month::from_date(chrono::NaiveDate::from_ymd_opt(2020, 2, 1).unwrap());
+        let days = feb.days();
+        assert_eq!(days.len(), 29);
+        assert_eq!(days.start(), Date::from(chrono::NaiveDate::from_ymd_opt(2020, 2, 1).unwrap()));
+        assert_eq!(days.end(), Date::from(chrono::NaiveDate::from_ymd_opt(2020, 2, 29).unwrap()));
+    }
+
+    #[test]
+    fn test_helpers() {
+        let feb = Month::from_year_month(2021, 2).unwrap();
+        assert_eq!(feb, Month::from_date(chrono::NaiveDate::from_ymd_opt(2021, 2, 14).unwrap()));
+        assert_eq!(feb.num_days(), 28);
+        assert_eq!(Month::from_year_month(2020, 2).unwrap().num_days(), 29);
+        assert_eq!(Month::from_year_month(2020, 12).unwrap().num_days(), 31);
+        assert_eq!(feb.last_day(), Date::from(chrono::NaiveDate::from_ymd_opt(2021, 2, 28).unwrap()));
+        assert_eq!(feb.with_day(3), Some(Date::from(chrono::NaiveDate::from_ymd_opt(2021, 2, 3).unwrap())));
+        assert_eq!(feb.with_day(29), None);
+        assert_eq!(feb.with_day(0), None);
+        assert!(matches!(
+            Month::from_year_month(2021, 0),
+            Err(crate::Error::InvalidComponent { component: "month", value: 0 })
+        ));
+        assert!(Month::from_year_month(2021, 13).is_err());
+    }
+
+    #[test]
+    fn test_with_same_day_as() {
+        let jan_31 = Date::from_ymd(2021, 1, 31).unwrap();
+        let feb_28 = Date::from_ymd(2021, 2, 28).unwrap();
+        let feb = Month::from_year_month(2021, 2).unwrap();
+        let mar = Month::from_year_month(2021, 3).unwrap();
+        assert_eq!(feb.with_same_day_as(jan_31, MonthEndPolicy::Clamp), feb_28);
+        assert_eq!(feb.with_same_day_as(jan_31, MonthEndPolicy::Preserve), feb_28);
+        assert_eq!(mar.with_same_day_as(feb_28, MonthEndPolicy::Clamp), Date::from_ymd(2021, 3, 28).unwrap());
+        assert_eq!(mar.with_same_day_as(feb_28, MonthEndPolicy::Preserve), Date::from_ymd(2021, 3, 31).unwrap());
+        let leap_feb = Month::from_year_month(2020, 2).unwrap();
+        assert_eq!(leap_feb.with_same_day_as(feb_28, MonthEndPolicy::Preserve), Date::from_ymd(2020, 2, 29).unwrap());
+        assert_eq!(leap_feb.with_same_day_as(feb_28, MonthEndPolicy::Clamp), Date::from_ymd(2020, 2, 28).unwrap());
+    }
+
+    #[test]
+    fn test_cmp_date() {
+        let feb = Month::from_year_month(2021, 2).unwrap();
+        assert_eq!(feb, chrono::NaiveDate::from_ymd_opt(2021, 2, 1).unwrap());
+        assert_eq!(feb, chrono::NaiveDate::from_ymd_opt(2021, 2, 28).unwrap());
+        assert!(feb < chrono::NaiveDate::from_ymd_opt(2021, 3, 1).unwrap());
+        assert!(feb > chrono::NaiveDate::from_ymd_opt(2021, 1, 31).unwrap());
+        assert!(feb <= chrono::NaiveDate::from_ymd_opt(2021, 2, 14).unwrap());
+        assert!(feb >= chrono::NaiveDate::from_ymd_opt(2021, 2, 14).unwrap());
+    }
+
+    #[test]
+    fn test_from_str() {
+        let jul = Month::from_year_month(2021, 7).unwrap();
+        assert_eq!(jul.to_string(), "Jul-2021");
+        assert_eq!("Jul-2021".parse::<Month>().unwrap(), jul);
+        assert!("2021-07".parse::<Month>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let jul = Month::from_year_month(2021, 7).unwrap();
+        assert_eq!(serde_json::from_str::<Month>("\"Jul-2021\"").unwrap(), jul);
+        assert_eq!(serde_json::to_string(&jul).unwrap(), "\"Jul-2021\"");
+    }
+
+    #[cfg(feature = "locale")]
+    #[test]
+    fn test_format_localized() {
+        let month = Month::from_date(chrono::NaiveDate::from_ymd_opt(2021, 12, 15).unwrap());
+        assert_eq!(month.format_localized(chrono::Locale::fr_FR), "déc. 2021");
+        assert_eq!(month.format_localized(chrono::Locale::de_DE), "Dez 2021");
+        assert_eq!(month.format_localized(chrono::Locale::en_US), "Dec 2021");
     }
 }
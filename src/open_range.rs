@@ -0,0 +1,153 @@
+use crate::{TimeRange, TimeResolution};
+use std::ops;
+
+// A range of periods where either end may be unbounded, eg: a retention policy
+// or a subscription from a date onwards. Both ends are inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct OpenTimeRange<P: TimeResolution> {
+    #[cfg_attr(feature = "serde", serde(bound(deserialize = "P: serde::de::DeserializeOwned")))]
+    start: Option<P>,
+    #[cfg_attr(feature = "serde", serde(bound(deserialize = "P: serde::de::DeserializeOwned")))]
+    end: Option<P>,
+}
+
+impl<P: TimeResolution> OpenTimeRange<P> {
+    pub fn from_start(start: P) -> OpenTimeRange<P> {
+        OpenTimeRange {
+            start: Some(start),
+            end: None,
+        }
+    }
+    pub fn until(end: P) -> OpenTimeRange<P> {
+        OpenTimeRange {
+            start: None,
+            end: Some(end),
+        }
+    }
+    pub fn all() -> OpenTimeRange<P> {
+        OpenTimeRange {
+            start: None,
+            end: None,
+        }
+    }
+    // `None` when `end` is earlier than `start`
+    pub fn new(start: Option<P>, end: Option<P>) -> Option<OpenTimeRange<P>> {
+        match (start, end) {
+            (Some(start), Some(end)) if end < start => None,
+            _ => Some(OpenTimeRange { start, end }),
+        }
+    }
+    pub fn start(&self) -> Option<P> {
+        self.start
+    }
+    pub fn end(&self) -> Option<P> {
+        self.end
+    }
+    pub fn is_bounded(&self) -> bool {
+        self.start.is_some() && self.end.is_some()
+    }
+    // when both ends are known
+    pub fn to_bounded(&self) -> Option<TimeRange<P>> {
+        TimeRange::from_start_end(self.start?, self.end?)
+    }
+    pub fn contains(&self, period: P) -> bool {
+        self.start.is_none_or(|start| start <= period) && self.end.is_none_or(|end| period <= end)
+    }
+    // whether every period of `range` is within this range
+    pub fn contains_range(&self, range: TimeRange<P>) -> bool {
+        range.is_empty() || (self.contains(range.start()) && self.contains(range.end()))
+    }
+    // the part of `range` which is within this range
+    pub fn intersect(&self, range: TimeRange<P>) -> Option<TimeRange<P>> {
+        if range.is_empty() {
+            return None;
+        }
+        let start = self.start.map_or(range.start(), |start| start.max(range.start()));
+        let end = self.end.map_or(range.end(), |end| end.min(range.end()));
+        TimeRange::from_start_end(start, end)
+    }
+    pub fn intersect_open(&self, other: OpenTimeRange<P>) -> Option<OpenTimeRange<P>> {
+        let start = match (self.start, other.start) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        let end = match (self.end, other.end) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        OpenTimeRange::new(start, end)
+    }
+}
+
+impl<P: TimeResolution> From<ops::RangeFrom<P>> for OpenTimeRange<P> {
+    fn from(range: ops::RangeFrom<P>) -> OpenTimeRange<P> {
+        OpenTimeRange::from_start(range.start)
+    }
+}
+
+impl<P: TimeResolution> From<ops::RangeToInclusive<P>> for OpenTimeRange<P> {
+    fn from(range: ops::RangeToInclusive<P>) -> OpenTimeRange<P> {
+        OpenTimeRange::until(range.end)
+    }
+}
+
+impl<P: TimeResolution> From<ops::RangeFull> for OpenTimeRange<P> {
+    fn from(_: ops::RangeFull) -> OpenTimeRange<P> {
+        OpenTimeRange::all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OpenTimeRange;
+    use crate::{Date, TimeRange, TimeResolution};
+
+    #[test]
+    fn test_contains() {
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        let from = OpenTimeRange::from(..).intersect_open((date..).into()).unwrap();
+        assert_eq!(from, OpenTimeRange::from_start(date));
+        assert!(from.contains(date));
+        assert!(from.contains(date.succ_n(10_000)));
+        assert!(!from.contains(date.pred()));
+
+        let until = OpenTimeRange::from(..=date);
+        assert!(until.contains(date));
+        assert!(until.contains(date.pred_n(10_000)));
+        assert!(!until.contains(date.succ()));
+
+        assert!(OpenTimeRange::all().contains(date));
+        assert!(from.contains_range(TimeRange::new(date, 10)));
+        assert!(!until.contains_range(TimeRange::new(date, 10)));
+    }
+
+    #[test]
+    fn test_intersect() {
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        let range = TimeRange::new(date.pred_n(5), 10);
+        assert_eq!(
+            OpenTimeRange::from_start(date).intersect(range),
+            Some(TimeRange::new(date, 5))
+        );
+        assert_eq!(
+            OpenTimeRange::until(date).intersect(range),
+            Some(TimeRange::new(date.pred_n(5), 6))
+        );
+        assert_eq!(OpenTimeRange::all().intersect(range), Some(range));
+        assert_eq!(OpenTimeRange::from_start(date.succ_n(5)).intersect(range), None);
+
+        let open = OpenTimeRange::from_start(date).intersect_open(OpenTimeRange::until(date.succ_n(3)));
+        assert_eq!(open.and_then(|r| r.to_bounded()), Some(TimeRange::new(date, 4)));
+        assert_eq!(
+            OpenTimeRange::from_start(date).intersect_open(OpenTimeRange::until(date.pred())),
+            None
+        );
+        assert_eq!(OpenTimeRange::new(Some(date), Some(date.pred())), None);
+        assert_eq!(
+            OpenTimeRange::new(Some(range.start()), Some(range.end())).and_then(|r| r.to_bounded()),
+            Some(range)
+        );
+        assert!(!OpenTimeRange::from_start(date).is_bounded());
+    }
+}
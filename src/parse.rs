@@ -0,0 +1,162 @@
+use crate::{Rounding, TimeResolution};
+use std::{any, str};
+
+// The date-time formats accepted by `allow_alternate_formats`, as well as
+// a date like "2021-07-15" and a year and month like "2021-07"
+const ALTERNATE_FORMATS: [&str; 4] = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"];
+
+// How forgiving `TimeResolution::parse_with` is of unusual input. `STRICT` suits
+// ingest pipelines, while `LENIENT` suits user input in interactive tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParseOptions {
+    // reject a date or date-time which isn't the start of a period, rather than
+    // using the period containing it (eg: "2021-08-15" for a `Quarter`)
+    pub strict_alignment: bool,
+    // ignore leading and trailing whitespace, rather than rejecting it
+    pub allow_whitespace: bool,
+    // also accept an ISO 8601 date or date-time, for any resolution
+    pub allow_alternate_formats: bool,
+}
+
+impl ParseOptions {
+    pub const STRICT: ParseOptions = ParseOptions {
+        strict_alignment: true,
+        allow_whitespace: false,
+        allow_alternate_formats: false,
+    };
+    pub const LENIENT: ParseOptions = ParseOptions {
+        strict_alignment: false,
+        allow_whitespace: true,
+        allow_alternate_formats: true,
+    };
+    // chrono skips some whitespace itself, so this checks for it up front
+    pub(crate) fn trim<'a>(&self, s: &'a str, ty_name: &'static str) -> crate::Result<&'a str> {
+        let trimmed = s.trim();
+        if self.allow_whitespace || trimmed.len() == s.len() {
+            Ok(trimmed)
+        } else {
+            Err(crate::Error::ParseCustom {
+                ty_name,
+                input: s.to_string(),
+            })
+        }
+    }
+    pub(crate) fn rounding(&self) -> Rounding {
+        if self.strict_alignment {
+            Rounding::Strict
+        } else {
+            Rounding::Floor
+        }
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions::STRICT
+    }
+}
+
+fn parse_instant(s: &str) -> Option<chrono::NaiveDateTime> {
+    ALTERNATE_FORMATS
+        .iter()
+        .find_map(|format| chrono::NaiveDateTime::parse_from_str(s, format).ok())
+        .or_else(|| {
+            let date = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .or_else(|_| chrono::NaiveDate::parse_from_str(&format!("{}-01", s), "%Y-%m-%d"))
+                .ok()?;
+            date.and_hms_opt(0, 0, 0)
+        })
+}
+
+pub(crate) fn parse_with<P>(s: &str, options: ParseOptions) -> crate::Result<P>
+where
+    P: TimeResolution + str::FromStr<Err = crate::Error>,
+{
+    let s = options.trim(s, any::type_name::<P>())?;
+    let instant = parse_instant(s);
+    match s.parse::<P>() {
+        Ok(parsed) => match instant {
+            // a date within the period, eg: for a `Quarter`
+            Some(dt) if options.strict_alignment && parsed.naive_date_time() != dt => {
+                Err(crate::Error::NotOnBoundary(dt))
+            }
+            _ => Ok(parsed),
+        },
+        Err(e) => match instant {
+            Some(dt) if options.allow_alternate_formats => P::from_datetime_with(dt, options.rounding()),
+            _ => Err(e),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParseOptions;
+    use crate::{Date, Error, FiveMinute, Hour, Month, Quarter, TimeResolution, Year};
+
+    #[test]
+    fn test_strict() {
+        let strict = ParseOptions::STRICT;
+        assert_eq!(Date::parse_with("2021-07-15", strict).unwrap(), "2021-07-15".parse::<Date>().unwrap());
+        assert_eq!(Month::parse_with("Jul-2021", strict).unwrap(), "Jul-2021".parse::<Month>().unwrap());
+        assert_eq!(Year::parse_with("2021", strict).unwrap(), Year::from_num(2021));
+        assert_eq!(Quarter::parse_with("Q3-2021", strict).unwrap(), "2021-07-01".parse::<Quarter>().unwrap());
+        assert_eq!(Quarter::parse_with("2021-07-01", strict).unwrap(), "Q3-2021".parse::<Quarter>().unwrap());
+        assert!(matches!(Quarter::parse_with("2021-08-15", strict), Err(Error::NotOnBoundary(_))));
+        assert!(FiveMinute::parse_with("2021-07-15 10:07", strict).is_err());
+        assert!(Date::parse_with(" 2021-07-15", strict).is_err());
+        assert!(FiveMinute::parse_with("2021-07-15 10:05 ", strict).is_err());
+        assert!(Month::parse_with("2021-07", strict).is_err());
+        assert!(Hour::parse_with("2021-07-15", strict).is_err());
+        assert_eq!(ParseOptions::default(), strict);
+    }
+
+    #[test]
+    fn test_lenient() {
+        let lenient = ParseOptions::LENIENT;
+        let month = "Jul-2021".parse::<Month>().unwrap();
+        assert_eq!(Month::parse_with(" Jul-2021\n", lenient).unwrap(), month);
+        assert_eq!(Month::parse_with("2021-07", lenient).unwrap(), month);
+        assert_eq!(Month::parse_with("2021-07-15", lenient).unwrap(), month);
+        assert_eq!(Year::parse_with("2021-07-15T10:00", lenient).unwrap(), Year::from_num(2021));
+        assert_eq!(Quarter::parse_with("2021-08-15", lenient).unwrap(), "Q3-2021".parse::<Quarter>().unwrap());
+        assert_eq!(
+            FiveMinute::parse_with("2021-07-15T10:07:30", lenient).unwrap(),
+            "2021-07-15 10:05".parse::<FiveMinute>().unwrap()
+        );
+        assert_eq!(
+            FiveMinute::parse_with("2021-07-15 10:07", lenient).unwrap(),
+            "2021-07-15 10:05".parse::<FiveMinute>().unwrap()
+        );
+        assert_eq!(Date::parse_with("2021-07-15 23:59", lenient).unwrap(), "2021-07-15".parse::<Date>().unwrap());
+        assert!(Date::parse_with("15/07/2021", lenient).is_err());
+    }
+
+    #[test]
+    fn test_mixed() {
+        // alternate formats, but still aligned
+        let options = ParseOptions {
+            allow_alternate_formats: true,
+            ..ParseOptions::STRICT
+        };
+        assert_eq!(Month::parse_with("2021-07-01", options).unwrap(), "Jul-2021".parse::<Month>().unwrap());
+        assert!(matches!(Month::parse_with("2021-07-02", options), Err(Error::NotOnBoundary(_))));
+        assert_eq!(
+            Hour::parse_with("2021-07-15T10:00:00", options).unwrap(),
+            "2021-07-15 10:00".parse::<Hour>().unwrap()
+        );
+        assert!(matches!(FiveMinute::parse_with("2021-07-15 10:07", options), Err(Error::NotOnBoundary(_))));
+
+        // only the canonical formats, rounding down when not aligned
+        let options = ParseOptions {
+            strict_alignment: false,
+            ..ParseOptions::STRICT
+        };
+        assert_eq!(
+            FiveMinute::parse_with("2021-07-15 10:07", options).unwrap(),
+            "2021-07-15 10:05".parse::<FiveMinute>().unwrap()
+        );
+        assert_eq!(Quarter::parse_with("2021-08-15", options).unwrap(), "Q3-2021".parse::<Quarter>().unwrap());
+        assert!(Month::parse_with("2021-07-15", options).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use crate::{TimeRange, TimeResolution};
+use std::{cmp, convert::TryFrom, num};
+
+// Turns missing ranges (eg: from a `CacheResponse::Miss`, using `TimeRange::from_set`)
+// into an ordered list of ranges to request from a data provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FetchPlanner {
+    max_span: Option<num::NonZeroU32>,
+    max_requests: Option<num::NonZeroUsize>,
+}
+
+impl FetchPlanner {
+    pub fn new() -> FetchPlanner {
+        FetchPlanner::default()
+    }
+    // the most periods to ask for in a single request
+    pub fn max_span(self, max_span: num::NonZeroU32) -> FetchPlanner {
+        FetchPlanner {
+            max_span: Some(max_span),
+            ..self
+        }
+    }
+    // the most requests to plan, with the earliest ranges planned first.
+    // Anything not planned will still be missing when the cache is next checked.
+    pub fn max_requests(self, max_requests: num::NonZeroUsize) -> FetchPlanner {
+        FetchPlanner {
+            max_requests: Some(max_requests),
+            ..self
+        }
+    }
+
+    // Merges overlapping or adjacent ranges, then splits them to fit `max_span`
+    pub fn plan<P: TimeResolution>(&self, missing: &[TimeRange<P>]) -> Vec<TimeRange<P>> {
+        let mut plan = Vec::new();
+        for range in merge(missing.iter().copied()) {
+            self.split(range, &mut plan);
+        }
+        self.truncate(plan)
+    }
+
+    // As `plan`, but widens each range to whole periods of `C` (eg: whole days), and
+    // where possible only splits ranges at the boundaries of `C`
+    pub fn plan_aligned<P: TimeResolution, C: TimeResolution>(&self, missing: &[TimeRange<P>]) -> Vec<TimeRange<P>> {
+        let widened = missing.iter().filter(|r| !r.is_empty()).map(|r| {
+            let start = periods_of::<P, C>(C::from_datetime(r.start().naive_date_time())).start();
+            let end = periods_of::<P, C>(C::from_datetime(r.end().naive_date_time())).end();
+            TimeRange::from_start_end(start, end).expect("Start is before end")
+        });
+        let mut plan = Vec::new();
+        for range in merge(widened) {
+            let max_span = match self.max_span {
+                Some(max_span) => max_span.get(),
+                None => {
+                    plan.push(range);
+                    continue;
+                }
+            };
+            // gather whole `C` periods until the next one won't fit
+            let mut current: Option<TimeRange<P>> = None;
+            let mut unit = C::from_datetime(range.start().naive_date_time());
+            while unit.naive_date_time() <= range.end().naive_date_time() {
+                let periods = periods_of::<P, C>(unit);
+                current = match current {
+                    Some(c) if c.len + periods.len <= max_span => Some(TimeRange::new(c.start, c.len + periods.len)),
+                    Some(c) => {
+                        self.split(c, &mut plan);
+                        Some(periods)
+                    }
+                    None => Some(periods),
+                };
+                unit = unit.succ();
+            }
+            if let Some(c) = current {
+                self.split(c, &mut plan);
+            }
+        }
+        self.truncate(plan)
+    }
+
+    fn split<P: TimeResolution>(&self, range: TimeRange<P>, plan: &mut Vec<TimeRange<P>>) {
+        let max_span = self.max_span.map_or(u32::MAX, |m| m.get());
+        let mut start = range.start;
+        let mut remaining = range.len;
+        while remaining > 0 {
+            let len = cmp::min(remaining, max_span);
+            plan.push(TimeRange::new(start, len));
+            start = start.succ_n(len);
+            remaining -= len;
+        }
+    }
+
+    fn truncate<P: TimeResolution>(&self, mut plan: Vec<TimeRange<P>>) -> Vec<TimeRange<P>> {
+        if let Some(max_requests) = self.max_requests {
+            plan.truncate(max_requests.get());
+        }
+        plan
+    }
+}
+
+// the `P` periods making up a `C` period
+fn periods_of<P: TimeResolution, C: TimeResolution>(unit: C) -> TimeRange<P> {
+    let start = P::from_datetime(unit.naive_date_time());
+    let end = P::from_datetime(unit.succ().naive_date_time()).pred();
+    TimeRange::from_start_end(start, end).expect("P is finer than C")
+}
+
+fn merge<P: TimeResolution>(ranges: impl Iterator<Item = TimeRange<P>>) -> Vec<TimeRange<P>> {
+    let mut ranges = ranges.filter(|r| !r.is_empty()).collect::<Vec<_>>();
+    ranges.sort_by_key(|r| r.start);
+    let mut merged = Vec::<TimeRange<P>>::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if last.end().succ() >= range.start => {
+                let end = cmp::max(last.end(), range.end());
+                let len = u32::try_from(last.start.between(end) + 1).expect("Merged ranges fit in a TimeRange");
+                *last = TimeRange::new(last.start, len);
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FetchPlanner;
+    use crate::{Date, HalfHour, TimeRange};
+    use std::num;
+
+    fn span(n: u32) -> num::NonZeroU32 {
+        num::NonZeroU32::new(n).unwrap()
+    }
+
+    #[test]
+    fn test_plan() {
+        let start = Date::from_ymd(2021, 7, 1).unwrap();
+        let missing = vec![
+            TimeRange::new(start.succ_n(20), 3),
+            TimeRange::new(start, 5),
+            TimeRange::new(start.succ_n(3), 4),
+            TimeRange::new(start.succ_n(7), 1),
+        ];
+        assert_eq!(
+            FetchPlanner::new().plan(&missing),
+            vec![TimeRange::new(start, 8), TimeRange::new(start.succ_n(20), 3)]
+        );
+        let planner = FetchPlanner::new().max_span(span(3));
+        assert_eq!(
+            planner.plan(&missing),
+            vec![
+                TimeRange::new(start, 3),
+                TimeRange::new(start.succ_n(3), 3),
+                TimeRange::new(start.succ_n(6), 2),
+                TimeRange::new(start.succ_n(20), 3),
+            ]
+        );
+        let limited = planner.max_requests(num::NonZeroUsize::new(2).unwrap());
+        assert_eq!(limited.plan(&missing).len(), 2);
+        assert!(planner.plan::<Date>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_plan_aligned() {
+        let start = "2021-07-15 10:00".parse::<HalfHour>().unwrap();
+        let day_start = "2021-07-15 00:00".parse::<HalfHour>().unwrap();
+        let missing = vec![TimeRange::new(start, 48)];
+        assert_eq!(
+            FetchPlanner::new().plan_aligned::<_, Date>(&missing),
+            vec![TimeRange::new(day_start, 96)]
+        );
+        // two days fit in each request
+        let planner = FetchPlanner::new().max_span(span(100));
+        let missing = vec![TimeRange::new(start, 48 * 3)];
+        assert_eq!(
+            planner.plan_aligned::<_, Date>(&missing),
+            vec![TimeRange::new(day_start, 96), TimeRange::new(day_start.succ_n(96), 96)]
+        );
+        // a single day is split when it doesn't fit
+        let planner = FetchPlanner::new().max_span(span(30));
+        let plan = planner.plan_aligned::<_, Date>(&[TimeRange::new(start, 1)]);
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan[1], TimeRange::new(day_start.succ_n(30), 18));
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{
+    Date, DateResolution, Minutes, Month, Quarter, Rounding, TimeRange, TimeResolution, Year,
+};
+use bytes::BytesMut;
+use postgres_protocol::types as protocol;
+use postgres_types::{to_sql_checked, FromSql, IsNull, Kind, ToSql, Type};
+use std::error;
+
+type BoxError = Box<dyn error::Error + Sync + Send>;
+
+// Date resolutions are stored as `DATE` and sub-date resolutions as
+// `TIMESTAMP`, using the start of the period. A `TimeRange` is stored as
+// a `daterange` or `tsrange` with an inclusive start and exclusive end.
+pub trait PostgresResolution: TimeResolution + ToSql + for<'a> FromSql<'a> {
+    fn range_type() -> Type;
+}
+
+macro_rules! impl_date_resolution {
+    ($($ty:ty),*) => {
+        $(
+            impl ToSql for $ty {
+                fn to_sql(&self, ty: &Type, out: &mut BytesMut) -> Result<IsNull, BoxError> {
+                    self.start().to_sql(ty, out)
+                }
+                fn accepts(ty: &Type) -> bool {
+                    <chrono::NaiveDate as ToSql>::accepts(ty)
+                }
+                to_sql_checked!();
+            }
+
+            impl<'a> FromSql<'a> for $ty {
+                fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, BoxError> {
+                    let date = chrono::NaiveDate::from_sql(ty, raw)?;
+                    let midnight = date.and_hms_opt(0, 0, 0).expect("Midnight is always valid");
+                    Ok(<$ty>::from_datetime_with(midnight, Rounding::Strict)?)
+                }
+                fn accepts(ty: &Type) -> bool {
+                    <chrono::NaiveDate as FromSql>::accepts(ty)
+                }
+            }
+
+            impl PostgresResolution for $ty {
+                fn range_type() -> Type {
+                    Type::DATE_RANGE
+                }
+            }
+        )*
+    };
+}
+
+impl_date_resolution!(Date, Month, Quarter, Year);
+
+impl<const N: u32> ToSql for Minutes<N> {
+    fn to_sql(&self, ty: &Type, out: &mut BytesMut) -> Result<IsNull, BoxError> {
+        self.naive_date_time().to_sql(ty, out)
+    }
+    fn accepts(ty: &Type) -> bool {
+        <chrono::NaiveDateTime as ToSql>::accepts(ty)
+    }
+    to_sql_checked!();
+}
+
+impl<'a, const N: u32> FromSql<'a> for Minutes<N> {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, BoxError> {
+        let dt = chrono::NaiveDateTime::from_sql(ty, raw)?;
+        Ok(Minutes::from_datetime_with(dt, Rounding::Strict)?)
+    }
+    fn accepts(ty: &Type) -> bool {
+        <chrono::NaiveDateTime as FromSql>::accepts(ty)
+    }
+}
+
+impl<const N: u32> PostgresResolution for Minutes<N> {
+    fn range_type() -> Type {
+        Type::TS_RANGE
+    }
+}
+
+// writes a range bound, which uses the `postgres_protocol` flavour of `IsNull`
+fn bound_to_sql<P: ToSql>(
+    period: P,
+    ty: &Type,
+    buf: &mut BytesMut,
+) -> Result<postgres_protocol::IsNull, BoxError> {
+    Ok(match period.to_sql(ty, buf)? {
+        IsNull::Yes => postgres_protocol::IsNull::Yes,
+        IsNull::No => postgres_protocol::IsNull::No,
+    })
+}
+
+fn range_element(ty: &Type) -> Option<&Type> {
+    match ty.kind() {
+        Kind::Range(inner) => Some(inner),
+        _ => None,
+    }
+}
+
+impl<P: PostgresResolution> ToSql for TimeRange<P> {
+    fn to_sql(&self, ty: &Type, out: &mut BytesMut) -> Result<IsNull, BoxError> {
+        let inner = range_element(ty).ok_or("Expected a range type")?;
+        if self.is_empty() {
+            protocol::empty_range_to_sql(out);
+        } else {
+            protocol::range_to_sql(
+                |buf| {
+                    Ok(protocol::RangeBound::Inclusive(bound_to_sql(
+                        self.start(),
+                        inner,
+                        buf,
+                    )?))
+                },
+                |buf| {
+                    Ok(protocol::RangeBound::Exclusive(bound_to_sql(
+                        self.end().succ(),
+                        inner,
+                        buf,
+                    )?))
+                },
+                out,
+            )?;
+        }
+        Ok(IsNull::No)
+    }
+    fn accepts(ty: &Type) -> bool {
+        range_element(ty).is_some_and(|inner| <P as ToSql>::accepts(inner))
+    }
+    to_sql_checked!();
+}
+
+// Only non-empty, bounded ranges with an inclusive start and exclusive end
+// can be read, which is the canonical form of a `daterange`
+impl<'a, P: PostgresResolution> FromSql<'a> for TimeRange<P> {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, BoxError> {
+        let inner = range_element(ty).ok_or("Expected a range type")?;
+        match protocol::range_from_sql(raw)? {
+            protocol::Range::Nonempty(
+                protocol::RangeBound::Inclusive(Some(lower)),
+                protocol::RangeBound::Exclusive(Some(upper)),
+            ) => {
+                let start = P::from_sql(inner, lower)?;
+                let end = P::from_sql(inner, upper)?;
+                Ok(TimeRange::from_start_end_exclusive(start, end)?)
+            }
+            _ => Err("Expected a bounded range with an inclusive start and exclusive end".into()),
+        }
+    }
+    fn accepts(ty: &Type) -> bool {
+        range_element(ty).is_some_and(|inner| <P as FromSql>::accepts(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PostgresResolution;
+    use crate::{Date, HalfHour, Month, TimeRange};
+    use bytes::BytesMut;
+    use postgres_types::{FromSql, ToSql, Type};
+
+    fn roundtrip<T: ToSql + for<'a> FromSql<'a>>(value: &T, ty: &Type) -> T {
+        let mut buf = BytesMut::new();
+        value.to_sql_checked(ty, &mut buf).unwrap();
+        T::from_sql(ty, &buf).unwrap()
+    }
+
+    #[test]
+    fn test_periods() {
+        let jul = Month::from_year_month(2021, 7).unwrap();
+        assert_eq!(roundtrip(&jul, &Type::DATE), jul);
+        let period = "2021-07-15 10:30".parse::<HalfHour>().unwrap();
+        assert_eq!(roundtrip(&period, &Type::TIMESTAMP), period);
+        assert!(<Month as ToSql>::accepts(&Type::DATE));
+        assert!(!<HalfHour as ToSql>::accepts(&Type::DATE));
+
+        let mut buf = BytesMut::new();
+        Date::from_ymd(2021, 7, 15)
+            .unwrap()
+            .to_sql(&Type::DATE, &mut buf)
+            .unwrap();
+        assert!(Month::from_sql(&Type::DATE, &buf).is_err());
+    }
+
+    #[test]
+    fn test_time_range() {
+        let days = TimeRange::new(Date::from_ymd(2021, 7, 15).unwrap(), 3);
+        assert_eq!(roundtrip(&days, &Date::range_type()), days);
+        let periods = TimeRange::new("2021-07-15 10:30".parse::<HalfHour>().unwrap(), 4);
+        assert_eq!(roundtrip(&periods, &HalfHour::range_type()), periods);
+        assert!(<TimeRange<Date> as ToSql>::accepts(&Type::DATE_RANGE));
+        assert!(!<TimeRange<Date> as ToSql>::accepts(&Type::TS_RANGE));
+        assert!(!<TimeRange<HalfHour> as ToSql>::accepts(&Type::DATE));
+
+        let mut buf = BytesMut::new();
+        postgres_protocol::types::empty_range_to_sql(&mut buf);
+        assert!(TimeRange::<Date>::from_sql(&Type::DATE_RANGE, &buf).is_err());
+    }
+}
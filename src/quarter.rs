@@ -1,32 +1,47 @@
-use crate::{month, year, DateResolution};
+use crate::{month, year, DateResolution, TimeRange};
 use chrono::Datelike;
-use serde::{
-    de,
-    ser::{self, SerializeStruct},
-};
-use std::{str, cmp, convert::TryFrom, fmt};
+#[cfg(feature = "serde")]
+use serde::de;
+use std::{borrow, cmp, convert::{self, TryFrom}, fmt, str};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes, compare(PartialEq, PartialOrd)))]
+#[cfg_attr(feature = "rkyv", archive_attr(derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)))]
 pub struct Quarter(i64);
 
 impl crate::TimeResolution for Quarter {
+    fn name(&self) -> borrow::Cow<'static, str> {
+        borrow::Cow::Borrowed("Quarter")
+    }
     fn between(&self, other: Self) -> i64 {
-        i64::from(other.0 - self.0)
+        other.0 - self.0
     }
     fn succ_n(&self, n: u32) -> Self {
-        Quarter(self.0 + i64::from(n))
+        Quarter::succ_n(self, n)
     }
     fn pred_n(&self, n: u32) -> Self {
-        Quarter(self.0 - i64::from(n))
+        Quarter::pred_n(self, n)
     }
     fn naive_date_time(&self) -> chrono::NaiveDateTime {
-        self.start().and_hms(0, 0, 0)
+        self.start().and_hms_opt(0, 0, 0).expect("Midnight is always valid")
+    }
+    fn from_datetime(dt: chrono::NaiveDateTime) -> Self {
+        Quarter::from_date(dt.date())
     }
     fn from_monotonic(idx: i64) -> Self {
-        Quarter(idx)
+        Quarter::from_monotonic(idx)
+    }
+    fn try_from_monotonic(idx: i64) -> crate::Result<Self> {
+        crate::check_monotonic(
+            "Quarter",
+            idx,
+            Quarter::from_date(chrono::NaiveDate::MIN),
+            Quarter::from_date(chrono::NaiveDate::MAX),
+        )
     }
     fn to_monotonic(&self) -> i64 {
-        self.0
+        Quarter::to_monotonic(self)
     }
 }
 
@@ -34,53 +49,93 @@ impl crate::DateResolution for Quarter {
     fn start(&self) -> chrono::NaiveDate {
         let years = i32::try_from(self.0.div_euclid(4)).expect("Not pre/post historic");
         let months = u32::try_from(1 + self.0.rem_euclid(4)).unwrap();
-        chrono::NaiveDate::from_ymd(years, months * 3 - 2, 1)
+        chrono::NaiveDate::from_ymd_opt(years, months * 3 - 2, 1).expect("Not pre/post historic")
     }
 }
 
 impl Quarter {
+    // the start of the period with monotonic index 0
+    pub fn monotonic_epoch() -> chrono::NaiveDateTime {
+        Quarter(0).start().and_hms_opt(0, 0, 0).expect("Midnight is always valid")
+    }
+    pub const fn from_monotonic(idx: i64) -> Quarter {
+        Quarter(idx)
+    }
+    pub const fn to_monotonic(&self) -> i64 {
+        self.0
+    }
+    pub const fn succ_n(&self, n: u32) -> Quarter {
+        Quarter(self.0 + n as i64)
+    }
+    pub const fn pred_n(&self, n: u32) -> Quarter {
+        Quarter(self.0 - n as i64)
+    }
     pub fn first_month(&self) -> month::Month {
-        todo!()
+        month::Month::from_date(self.start())
     }
     pub fn year(&self) -> year::Year {
-        todo!()
+        year::Year::from_date(self.start())
     }
     pub fn year_num(&self) -> i32 {
         self.start().year()
     }
     pub fn quarter_num(&self) -> u32 {
-        u32::try_from(self.0.rem_euclid(4)).expect("Range of 1-4")
+        1 + u32::try_from(self.0.rem_euclid(4)).expect("Range of 1-4")
     }
     pub fn from_date(d: chrono::NaiveDate) -> Self {
-        todo!()
+        Quarter(i64::from(d.year()) * 4 + i64::from(d.month0() / 3))
+    }
+    // `quarter` is 1-4
+    pub const fn from_year_quarter(year: i32, quarter: u32) -> crate::Result<Self> {
+        if matches!(quarter, 1..=4) {
+            Ok(Quarter(year as i64 * 4 + (quarter - 1) as i64))
+        } else {
+            Err(crate::Error::InvalidComponent { component: "quarter", value: quarter })
+        }
+    }
+    // the same quarter `n` years later (or earlier), erroring when out of chrono's range
+    pub fn offset_years(&self, n: i32) -> crate::Result<Quarter> {
+        crate::TimeResolution::try_from_monotonic(self.0 + i64::from(n) * 4)
+    }
+    pub fn year_over_year(&self) -> crate::Result<Quarter> {
+        self.offset_years(-1)
+    }
+    pub fn months(&self) -> TimeRange<month::Month> {
+        TimeRange::new(self.first_month(), 3)
     }
 }
 
-impl fmt::Display for Quarter {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            format!("Q{}-{:4}", self.quarter_num(), self.year_num())
-        )
+impl PartialEq<chrono::NaiveDate> for Quarter {
+    fn eq(&self, other: &chrono::NaiveDate) -> bool {
+        crate::cmp_date(self, other) == cmp::Ordering::Equal
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::Quarter;
-    use crate::DateResolution;
+impl PartialOrd<chrono::NaiveDate> for Quarter {
+    fn partial_cmp(&self, other: &chrono::NaiveDate) -> Option<cmp::Ordering> {
+        Some(crate::cmp_date(self, other))
+    }
+}
 
-    #[test]
-    fn test_start() {
-        assert_eq!(Quarter(2).start(), chrono::NaiveDate::from_ymd(0, 7, 1));
-        assert_eq!(Quarter(1).start(), chrono::NaiveDate::from_ymd(0, 4, 1));
-        assert_eq!(Quarter(0).start(), chrono::NaiveDate::from_ymd(0, 1, 1));
-        assert_eq!(Quarter(-1).start(), chrono::NaiveDate::from_ymd(-1, 10, 1));
-        assert_eq!(Quarter(-2).start(), chrono::NaiveDate::from_ymd(-1, 7, 1));
+#[cfg(feature = "locale")]
+impl Quarter {
+    // the months of the quarter, as there are no localised names for quarters, eg: `oct.-déc. 2021` for `fr_FR`
+    pub fn format_localized(&self, locale: chrono::Locale) -> String {
+        format!(
+            "{}-{}",
+            crate::format_localized(self.start(), "%b", locale),
+            crate::format_localized(self.end(), "%b %Y", locale)
+        )
     }
 }
 
+impl fmt::Display for Quarter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Q{}-{:4}", self.quarter_num(), self.year_num())
+    }
+}
+
+#[cfg(feature = "serde")]
 impl<'de> de::Deserialize<'de> for Quarter 
 {
     fn deserialize<D>(
@@ -90,12 +145,11 @@ impl<'de> de::Deserialize<'de> for Quarter
         D: de::Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        let date = chrono::NaiveDate::parse_from_str(&s, "Q%m-%Y")
-            .map_err(serde::de::Error::custom)?;
-        Ok(Quarter(i64::from(date.year()) * 4 + i64::try_from(date.month()).unwrap()))
+        s.parse().map_err(serde::de::Error::custom)
     }
 }
 
+#[cfg(feature = "serde")]
 impl serde::Serialize for Quarter {
     fn serialize<S>(
         &self,
@@ -117,9 +171,16 @@ impl str::FromStr for Quarter {
         } else {
             let split = s.split('-').map(ToString::to_string).collect::<Vec<String>>();
             if split.len() == 2 {
-                let qtr = split[0].parse::<u32>()?;
+                let qtr = split[0].trim_start_matches('Q').parse::<u32>()?;
                 let year = split[1].parse()?;
-                let date = chrono::NaiveDate::from_ymd(year, qtr * 3 - 2, 1);
+                let date = (1..=4)
+                    .contains(&qtr)
+                    .then(|| chrono::NaiveDate::from_ymd_opt(year, qtr * 3 - 2, 1))
+                    .flatten()
+                    .ok_or_else(|| crate::Error::ParseCustom {
+                        ty_name: "Quarter",
+                        input: s.to_string(),
+                    })?;
                 Ok(Quarter::from_date(date))
             } else {
                 Err(crate::Error::ParseCustom { ty_name: "Quarter", input: s.to_string() })
@@ -128,3 +189,76 @@ impl str::FromStr for Quarter {
     }
 }
 
+impl<'a> convert::TryFrom<&'a str> for Quarter {
+    type Error = crate::Error;
+    fn try_from(s: &'a str) -> crate::Result<Quarter> {
+        s.parse()
+    }
+}
+
+impl convert::TryFrom<i64> for Quarter {
+    type Error = crate::Error;
+    fn try_from(idx: i64) -> crate::Result<Quarter> {
+        <Quarter as crate::TimeResolution>::try_from_monotonic(idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Quarter;
+    use crate::{DateResolution, Month};
+
+    #[test]
+    fn test_offset_years() {
+        let q3 = Quarter::from_year_quarter(2021, 3).unwrap();
+        assert_eq!(q3.year_over_year().unwrap(), Quarter::from_year_quarter(2020, 3).unwrap());
+        assert_eq!(q3.offset_years(5).unwrap(), Quarter::from_year_quarter(2026, 3).unwrap());
+        assert!(q3.offset_years(1_000_000).is_err());
+    }
+
+    #[test]
+    fn test_start() {
+        assert_eq!(Quarter(2).start(), chrono::NaiveDate::from_ymd_opt(0, 7, 1).unwrap());
+        assert_eq!(Quarter(1).start(), chrono::NaiveDate::from_ymd_opt(0, 4, 1).unwrap());
+        assert_eq!(Quarter(0).start(), chrono::NaiveDate::from_ymd_opt(0, 1, 1).unwrap());
+        assert_eq!(Quarter(-1).start(), chrono::NaiveDate::from_ymd_opt(-1, 10, 1).unwrap());
+        assert_eq!(Quarter(-2).start(), chrono::NaiveDate::from_ymd_opt(-1, 7, 1).unwrap());
+    }
+
+    #[test]
+    fn test_months() {
+        let q4 = Quarter::from_date(chrono::NaiveDate::from_ymd_opt(2021, 11, 5).unwrap());
+        let months = q4.months();
+        assert_eq!(months.len(), 3);
+        assert_eq!(months.start(), Month::from_date(chrono::NaiveDate::from_ymd_opt(2021, 10, 1).unwrap()));
+        assert_eq!(months.end(), Month::from_date(chrono::NaiveDate::from_ymd_opt(2021, 12, 1).unwrap()));
+    }
+
+    #[test]
+    fn test_from_year_quarter() {
+        let q4 = Quarter::from_year_quarter(2021, 4).unwrap();
+        assert_eq!(q4, Quarter::from_date(chrono::NaiveDate::from_ymd_opt(2021, 11, 5).unwrap()));
+        assert_eq!((q4.year_num(), q4.quarter_num()), (2021, 4));
+        assert_eq!(Quarter::from_year_quarter(-1, 1).unwrap().start(), chrono::NaiveDate::from_ymd_opt(-1, 1, 1).unwrap());
+        assert!(Quarter::from_year_quarter(2021, 0).is_err());
+        assert!(Quarter::from_year_quarter(2021, 5).is_err());
+    }
+
+    #[test]
+    fn test_from_str_invalid_quarter() {
+        assert!("0-2021".parse::<Quarter>().is_err());
+        assert!("5-2021".parse::<Quarter>().is_err());
+        assert_eq!(
+            "4-2021".parse::<Quarter>().unwrap(),
+            Quarter::from_date(chrono::NaiveDate::from_ymd_opt(2021, 10, 1).unwrap())
+        );
+    }
+
+    #[cfg(feature = "locale")]
+    #[test]
+    fn test_format_localized() {
+        let quarter = Quarter::from_date(chrono::NaiveDate::from_ymd_opt(2021, 12, 15).unwrap());
+        assert_eq!(quarter.format_localized(chrono::Locale::fr_FR), "oct.-déc. 2021");
+        assert_eq!(quarter.format_localized(chrono::Locale::en_US), "Oct-Dec 2021");
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{Date, Minutes, Month, Quarter, TimeRange, TimeResolution, Year};
+use quickcheck::{Arbitrary, Gen};
+use std::convert::TryFrom;
+
+// Periods are generated between 1900 and 2100, and shrink towards
+// the period with monotonic index 0
+fn arbitrary_period<P: TimeResolution>(g: &mut Gen) -> P {
+    let (start, end) = crate::arbitrary_bounds::<P>();
+    P::from_monotonic(start + i64::arbitrary(g).rem_euclid(end - start))
+}
+
+fn shrink_period<P: TimeResolution + 'static>(period: &P) -> Box<dyn Iterator<Item = P>> {
+    Box::new(period.to_monotonic().shrink().map(P::from_monotonic))
+}
+
+macro_rules! impl_arbitrary {
+    ($($ty:ty),*) => {
+        $(
+            impl Arbitrary for $ty {
+                fn arbitrary(g: &mut Gen) -> Self {
+                    arbitrary_period(g)
+                }
+                fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+                    shrink_period(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_arbitrary!(Date, Month, Quarter, Year);
+
+impl<const N: u32> Arbitrary for Minutes<N> {
+    fn arbitrary(g: &mut Gen) -> Self {
+        arbitrary_period(g)
+    }
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        shrink_period(self)
+    }
+}
+
+// Ranges are non-empty with a length of up to the size of the `Gen`,
+// and shrink towards shorter ranges before shrinking the start
+impl<P: TimeResolution + Arbitrary> Arbitrary for TimeRange<P> {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let max_len = u32::try_from(g.size()).unwrap_or(u32::MAX).max(1);
+        let len = 1 + u32::arbitrary(g) % max_len;
+        TimeRange::new(P::arbitrary(g), len)
+    }
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let start = self.start;
+        let len = self.len;
+        let shorter = (len - 1)
+            .shrink()
+            .map(move |len| TimeRange::new(start, len + 1));
+        let earlier = start.shrink().map(move |start| TimeRange::new(start, len));
+        Box::new(shorter.chain(earlier))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Date, HalfHour, Month, TimeRange};
+    use quickcheck::{quickcheck, Arbitrary};
+
+    quickcheck! {
+        fn prop_range_is_not_empty(range: TimeRange<HalfHour>) -> bool {
+            !range.is_empty() && range.iter().count() == range.len()
+        }
+
+        fn prop_month_roundtrips(month: Month) -> bool {
+            month.to_string().parse::<Month>().ok() == Some(month)
+        }
+    }
+
+    #[test]
+    fn test_shrink() {
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        assert!(date.shrink().all(|d| d.to_monotonic().abs() < date.to_monotonic()));
+        let range = TimeRange::new(date, 5);
+        let shrunk = range.shrink().collect::<Vec<_>>();
+        assert!(shrunk.iter().all(|r| !r.is_empty()));
+        assert_eq!(shrunk[0], TimeRange::new(date, 1));
+        assert!(shrunk.iter().any(|r| r.len() == 5 && r.start() < date));
+    }
+}
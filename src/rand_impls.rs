@@ -0,0 +1,102 @@
+use crate::{Date, Minutes, Month, Quarter, TimeRange, TimeResolution, Year};
+use rand::{
+    distributions::{
+        uniform::{SampleBorrow, SampleUniform, UniformInt, UniformSampler},
+        Distribution,
+    },
+    Rng,
+};
+use std::marker;
+
+// Samples periods uniformly by their monotonic index, which allows
+// `rng.gen_range(start..=end)` for any resolution
+#[derive(Clone, Copy, Debug)]
+pub struct UniformPeriod<P: TimeResolution> {
+    inner: UniformInt<i64>,
+    period: marker::PhantomData<P>,
+}
+
+impl<P: TimeResolution> UniformSampler for UniformPeriod<P> {
+    type X = P;
+    fn new<B1, B2>(low: B1, high: B2) -> Self
+    where
+        B1: SampleBorrow<P> + Sized,
+        B2: SampleBorrow<P> + Sized,
+    {
+        UniformPeriod {
+            inner: UniformInt::new(low.borrow().to_monotonic(), high.borrow().to_monotonic()),
+            period: marker::PhantomData,
+        }
+    }
+    fn new_inclusive<B1, B2>(low: B1, high: B2) -> Self
+    where
+        B1: SampleBorrow<P> + Sized,
+        B2: SampleBorrow<P> + Sized,
+    {
+        UniformPeriod {
+            inner: UniformInt::new_inclusive(
+                low.borrow().to_monotonic(),
+                high.borrow().to_monotonic(),
+            ),
+            period: marker::PhantomData,
+        }
+    }
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> P {
+        P::from_monotonic(self.inner.sample(rng))
+    }
+}
+
+macro_rules! impl_sample_uniform {
+    ($($ty:ty),*) => {
+        $(
+            impl SampleUniform for $ty {
+                type Sampler = UniformPeriod<$ty>;
+            }
+        )*
+    };
+}
+
+impl_sample_uniform!(Date, Month, Quarter, Year);
+
+impl<const N: u32> SampleUniform for Minutes<N> {
+    type Sampler = UniformPeriod<Minutes<N>>;
+}
+
+// panics if the range is empty
+impl<P: TimeResolution> Distribution<P> for TimeRange<P> {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> P {
+        assert!(!self.is_empty(), "Can't sample from an empty TimeRange");
+        self.start().succ_n(rng.gen_range(0..self.len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Date, HalfHour, Month, TimeRange};
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_gen_range() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let start = Date::from_ymd(2021, 7, 1).unwrap();
+        let end = Date::from_ymd(2021, 7, 31).unwrap();
+        for _ in 0..100 {
+            let day = rng.gen_range(start..=end);
+            assert!(day >= start && day <= end);
+            let day = rng.gen_range(start..end);
+            assert!(day >= start && day < end);
+        }
+        let jan = Month::from_year_month(2021, 1).unwrap();
+        assert_eq!(rng.gen_range(jan..=jan), jan);
+    }
+
+    #[test]
+    fn test_sample_time_range() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let range = TimeRange::new("2021-07-15 10:30".parse::<HalfHour>().unwrap(), 4);
+        for _ in 0..100 {
+            let period = rng.sample(range);
+            assert!(range.index_of(period).is_some());
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use crate::{Date, DateResolution, TimeRange, TimeRangeIter, TimeResolution};
+use std::{marker, num};
+
+// The Gregorian calendar repeats every 400 years, so a pattern which
+// hasn't matched in that long will never match
+pub(crate) const SEARCH_YEARS: u32 = 400;
+// the number of days in `SEARCH_YEARS`
+pub(crate) const SEARCH_DAYS: u32 = 146_097;
+
+// A pattern selecting some of the periods of a resolution
+// (eg: "the first Monday of every Month")
+pub trait Recurrence<P: TimeResolution> {
+    fn matches(&self, period: P) -> bool;
+
+    fn occurrences(&self, within: TimeRange<P>) -> Occurrences<'_, P, Self>
+    where
+        Self: Sized,
+    {
+        Occurrences {
+            periods: within.iter(),
+            recurrence: self,
+        }
+    }
+
+    // The first matching period after `period`. The default searches forwards
+    // one period at a time, giving `None` if there's no match within `SEARCH_YEARS`
+    // or the end of chrono's range is reached.
+    fn next_after(&self, period: P) -> Option<P> {
+        let succ = |p: P| P::try_from_monotonic(p.to_monotonic().checked_add(1)?).ok();
+        let mut current = succ(period)?;
+        let limit = current
+            .naive_date_time()
+            .checked_add_signed(chrono::Duration::days(i64::from(SEARCH_DAYS)))
+            .map_or(i64::MAX, |dt| P::from_datetime(dt).to_monotonic());
+        while current.to_monotonic() <= limit {
+            if self.matches(current) {
+                return Some(current);
+            }
+            current = succ(current)?;
+        }
+        None
+    }
+}
+
+pub fn next_occurrence_after<P: TimeResolution, R: Recurrence<P>>(pattern: &R, period: P) -> Option<P> {
+    pattern.next_after(period)
+}
+
+pub fn occurrences_between<P: TimeResolution, R: Recurrence<P>>(
+    pattern: &R,
+    range: TimeRange<P>,
+) -> Occurrences<'_, P, R> {
+    pattern.occurrences(range)
+}
+
+// Any predicate can be used as a pattern
+impl<P: TimeResolution, F: Fn(P) -> bool> Recurrence<P> for F {
+    fn matches(&self, period: P) -> bool {
+        self(period)
+    }
+}
+
+pub struct Occurrences<'a, P: TimeResolution, R: Recurrence<P>> {
+    periods: TimeRangeIter<P>,
+    recurrence: &'a R,
+}
+
+impl<'a, P: TimeResolution, R: Recurrence<P>> Iterator for Occurrences<'a, P, R> {
+    type Item = P;
+    fn next(&mut self) -> Option<Self::Item> {
+        let recurrence = self.recurrence;
+        self.periods.find(|p| recurrence.matches(*p))
+    }
+}
+
+// Every `interval`th period, counting in both directions from `anchor`
+// (eg: every 2nd Month starting from 2021-01)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Every<P: TimeResolution> {
+    anchor: P,
+    interval: num::NonZeroU32,
+}
+
+impl<P: TimeResolution> Every<P> {
+    pub fn new(anchor: P, interval: num::NonZeroU32) -> Every<P> {
+        Every { anchor, interval }
+    }
+}
+
+impl<P: TimeResolution> Recurrence<P> for Every<P> {
+    fn matches(&self, period: P) -> bool {
+        self.anchor
+            .between(period)
+            .rem_euclid(i64::from(self.interval.get()))
+            == 0
+    }
+    fn next_after(&self, period: P) -> Option<P> {
+        let interval = i64::from(self.interval.get());
+        let offset = interval - self.anchor.between(period).rem_euclid(interval);
+        let next = P::try_from_monotonic(period.to_monotonic().checked_add(offset)?).ok()?;
+        Some(next)
+    }
+}
+
+// The `nth` Date of every `C`, where 1 is the first day and -1 is the last
+// (eg: the last day of every Quarter is `NthDayOf::<Quarter>::new(-1)`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NthDayOf<C: DateResolution> {
+    nth: i32,
+    container: marker::PhantomData<C>,
+}
+
+impl<C: DateResolution> NthDayOf<C> {
+    // returns `None` when `nth` is zero
+    pub fn new(nth: i32) -> Option<NthDayOf<C>> {
+        if nth == 0 {
+            return None;
+        }
+        Some(NthDayOf {
+            nth,
+            container: marker::PhantomData,
+        })
+    }
+    pub fn first() -> NthDayOf<C> {
+        NthDayOf::new(1).expect("Non-zero")
+    }
+    pub fn last() -> NthDayOf<C> {
+        NthDayOf::new(-1).expect("Non-zero")
+    }
+}
+
+impl<C: DateResolution> Recurrence<Date> for NthDayOf<C> {
+    fn matches(&self, date: Date) -> bool {
+        is_nth_within::<C>(date, 1, self.nth)
+    }
+}
+
+// The `nth` occurrence of a weekday in every `C`, where 1 is the
+// first and -1 is the last (eg: the first Monday of every Month)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NthWeekdayOf<C: DateResolution> {
+    nth: i32,
+    weekday: chrono::Weekday,
+    container: marker::PhantomData<C>,
+}
+
+impl<C: DateResolution> NthWeekdayOf<C> {
+    // returns `None` when `nth` is zero
+    pub fn new(nth: i32, weekday: chrono::Weekday) -> Option<NthWeekdayOf<C>> {
+        if nth == 0 {
+            return None;
+        }
+        Some(NthWeekdayOf {
+            nth,
+            weekday,
+            container: marker::PhantomData,
+        })
+    }
+}
+
+impl<C: DateResolution> Recurrence<Date> for NthWeekdayOf<C> {
+    fn matches(&self, date: Date) -> bool {
+        date.weekday() == self.weekday && is_nth_within::<C>(date, 7, self.nth)
+    }
+}
+
+// Whether `date` is at the 1-based position `nth` in its containing `C`, counting
+// in steps of `step` days, and from the end when negative (so -2 is the 2nd last)
+pub(crate) fn is_nth_within<C: DateResolution>(date: Date, step: i64, nth: i32) -> bool {
+    let container = C::from_datetime(date.naive_date_time());
+    let day = date.start();
+    let position = if nth > 0 {
+        (day - container.start()).num_days() / step + 1
+    } else {
+        -((container.end() - day).num_days() / step + 1)
+    };
+    position == i64::from(nth)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{next_occurrence_after, occurrences_between, Every, NthDayOf, NthWeekdayOf, Recurrence};
+    use crate::{Date, FiveMinute, Hour, Month, Quarter, TimeRange, TimeResolution, Year};
+    use std::num;
+
+    fn year_2021() -> TimeRange<Date> {
+        let start = Date::from_ymd(2021, 1, 1).unwrap();
+        TimeRange::from_start_end(start, Date::from_ymd(2021, 12, 31).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_nth_weekday_of() {
+        let first_monday = NthWeekdayOf::<Month>::new(1, chrono::Weekday::Mon).unwrap();
+        let dates = first_monday.occurrences(year_2021()).collect::<Vec<_>>();
+        assert_eq!(dates.len(), 12);
+        assert_eq!(dates[0], Date::from_ymd(2021, 1, 4).unwrap());
+        assert_eq!(dates[1], Date::from_ymd(2021, 2, 1).unwrap());
+        let last_friday = NthWeekdayOf::<Month>::new(-1, chrono::Weekday::Fri).unwrap();
+        let dates = last_friday.occurrences(year_2021()).collect::<Vec<_>>();
+        assert_eq!(dates.len(), 12);
+        assert_eq!(dates[0], Date::from_ymd(2021, 1, 29).unwrap());
+        assert_eq!(dates[11], Date::from_ymd(2021, 12, 31).unwrap());
+        // July 2021 has four Wednesdays
+        let third_wednesday = NthWeekdayOf::<Month>::new(3, chrono::Weekday::Wed).unwrap();
+        assert!(third_wednesday.matches(Date::from_ymd(2021, 7, 21).unwrap()));
+        assert!(NthDayOf::<Month>::new(20).unwrap().matches(Date::from_ymd(2021, 7, 20).unwrap()));
+        assert!(NthWeekdayOf::<Year>::new(0, chrono::Weekday::Fri).is_none());
+    }
+
+    #[test]
+    fn test_nth_day_of() {
+        let dates = NthDayOf::<Quarter>::last().occurrences(year_2021()).collect::<Vec<_>>();
+        assert_eq!(
+            dates,
+            vec![
+                Date::from_ymd(2021, 3, 31).unwrap(),
+                Date::from_ymd(2021, 6, 30).unwrap(),
+                Date::from_ymd(2021, 9, 30).unwrap(),
+                Date::from_ymd(2021, 12, 31).unwrap(),
+            ]
+        );
+        let second_last = NthDayOf::<Month>::new(-2).unwrap();
+        assert!(second_last.matches(Date::from_ymd(2021, 2, 27).unwrap()));
+        assert_eq!(NthDayOf::<Year>::first().occurrences(year_2021()).count(), 1);
+    }
+
+    #[test]
+    fn test_every() {
+        let start = Date::from_ymd(2021, 1, 4).unwrap();
+        let fortnightly = Every::new(start, num::NonZeroU32::new(14).unwrap());
+        let dates = fortnightly.occurrences(year_2021()).collect::<Vec<_>>();
+        assert_eq!(dates.len(), 26);
+        assert!(fortnightly.matches(Date::from_ymd(2020, 12, 21).unwrap()));
+        let even = |d: Date| d.to_monotonic() % 2 == 0;
+        assert_eq!(even.occurrences(TimeRange::new(start, 4)).count(), 2);
+    }
+
+    #[test]
+    fn test_next_occurrence_after() {
+        let last_day = NthDayOf::<Quarter>::last();
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        assert_eq!(next_occurrence_after(&last_day, date), Date::from_ymd(2021, 9, 30));
+        assert_eq!(next_occurrence_after(&last_day, Date::from_ymd(2021, 9, 30).unwrap()), Date::from_ymd(2021, 12, 31));
+        let every = Every::new(date, num::NonZeroU32::new(7).unwrap());
+        assert_eq!(every.next_after(date), Some(date.succ_n(7)));
+        assert_eq!(every.next_after(date.pred()), Some(date));
+        assert_eq!(every.next_after(date.pred_n(9)), Some(date.pred_n(7)));
+        let never = |_: Date| false;
+        assert_eq!(next_occurrence_after(&never, Date::from_monotonic(i64::MAX - 1)), None);
+        let range = TimeRange::new(date, 100);
+        assert_eq!(occurrences_between(&last_day, range).count(), 1);
+    }
+
+    #[test]
+    fn test_next_whose() {
+        let month = Month::from_year_month(2021, 7).unwrap();
+        let july = month.next_whose(|m| m.month_num() == 7).unwrap();
+        assert_eq!(july, Month::from_year_month(2022, 7).unwrap());
+        let now = "2021-07-15 10:25".parse::<FiveMinute>().unwrap();
+        let boundary = now.next_whose(|p| Quarter::from_datetime(p.naive_date_time()) > Quarter::from_datetime(now.naive_date_time()));
+        assert_eq!(boundary, Some("2021-10-01 00:00".parse::<FiveMinute>().unwrap()));
+        // gives up rather than searching until the end of chrono's range
+        assert_eq!("2021-07-15 10:00".parse::<Hour>().unwrap().next_whose(|_| false), None);
+        assert_eq!(month.next_whose(|m| m.year_num() == 2420), Some(Month::from_year_month(2420, 1).unwrap()));
+        assert_eq!(month.next_whose(|m| m.year_num() == 2422), None);
+    }
+}
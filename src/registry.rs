@@ -0,0 +1,207 @@
+use crate::{
+    Date, DynMinutes, FiveMinute, HalfHour, Hour, Minute, Month, Quarter, TimeResolution, Year,
+};
+use std::{any, collections, fmt, str};
+
+type Formatter = fn(i64) -> crate::Result<String>;
+type Parser = fn(&str) -> crate::Result<i64>;
+
+#[derive(Clone, Copy)]
+struct Entry {
+    name: &'static str,
+    format: Formatter,
+    parse: Parser,
+}
+
+fn format<P: TimeResolution>(idx: i64) -> crate::Result<String> {
+    Ok(P::try_from_monotonic(idx)?.to_string())
+}
+
+fn parse<P: TimeResolution + str::FromStr>(s: &str) -> crate::Result<i64> {
+    s.parse::<P>()
+        .map(|p| p.to_monotonic())
+        .map_err(|_| crate::Error::ParseCustom {
+            ty_name: any::type_name::<P>(),
+            input: s.to_string(),
+        })
+}
+
+// the length of `Minutes[Length:N]`, as produced by `TimeResolution::name`
+fn minutes_length(name: &str) -> Option<u32> {
+    name.strip_prefix("Minutes[Length:")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+        .filter(|n| *n > 0)
+}
+
+// Allows formatting and parsing of type-erased periods, which are
+// just a monotonic index plus either a `TypeId` or a name. The
+// built-in resolutions are always registered, and custom resolutions
+// can be added with `register`. `Minutes` of any length are supported
+// by name without needing to be registered.
+#[derive(Clone)]
+pub struct ResolutionRegistry {
+    entries: collections::HashMap<any::TypeId, Entry>,
+    names: collections::HashMap<&'static str, any::TypeId>,
+}
+
+impl fmt::Debug for ResolutionRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.names.keys()).finish()
+    }
+}
+
+impl Default for ResolutionRegistry {
+    fn default() -> ResolutionRegistry {
+        ResolutionRegistry::new()
+    }
+}
+
+impl ResolutionRegistry {
+    pub fn new() -> ResolutionRegistry {
+        let mut registry = ResolutionRegistry {
+            entries: collections::HashMap::new(),
+            names: collections::HashMap::new(),
+        };
+        registry.register::<Minute>("Minutes[Length:1]");
+        registry.register::<FiveMinute>("Minutes[Length:5]");
+        registry.register::<HalfHour>("Minutes[Length:30]");
+        registry.register::<Hour>("Minutes[Length:60]");
+        registry.register::<Date>("Date");
+        registry.register::<Month>("Month");
+        registry.register::<Quarter>("Quarter");
+        registry.register::<Year>("Year");
+        registry
+    }
+    // registering a type or name a second time replaces the earlier registration
+    pub fn register<P: TimeResolution + str::FromStr + 'static>(&mut self, name: &'static str) {
+        let type_id = any::TypeId::of::<P>();
+        if let Some(old) = self.entries.get(&type_id) {
+            self.names.remove(old.name);
+        }
+        if let Some(old) = self.names.get(name) {
+            self.entries.remove(old);
+        }
+        self.entries.insert(
+            type_id,
+            Entry {
+                name,
+                format: format::<P>,
+                parse: parse::<P>,
+            },
+        );
+        self.names.insert(name, type_id);
+    }
+    pub fn is_registered(&self, type_id: any::TypeId) -> bool {
+        self.entries.contains_key(&type_id)
+    }
+    pub fn name(&self, type_id: any::TypeId) -> Option<&'static str> {
+        self.entries.get(&type_id).map(|e| e.name)
+    }
+    pub fn type_id(&self, name: &str) -> Option<any::TypeId> {
+        self.names.get(name).copied()
+    }
+    pub fn format(&self, type_id: any::TypeId, idx: i64) -> crate::Result<String> {
+        (self.entry(type_id)?.format)(idx)
+    }
+    pub fn format_by_name(&self, name: &str, idx: i64) -> crate::Result<String> {
+        match (self.type_id(name), minutes_length(name)) {
+            (Some(type_id), _) => self.format(type_id, idx),
+            (None, Some(length)) => Ok(DynMinutes::new(length, idx)?.to_string()),
+            (None, None) => Err(crate::Error::UnknownResolution(name.to_string())),
+        }
+    }
+    // formats using the registration for `P` if there is one, otherwise by `P`'s name
+    pub fn format_period<P: TimeResolution + 'static>(&self, period: &P) -> crate::Result<String> {
+        if self.is_registered(any::TypeId::of::<P>()) {
+            self.format(any::TypeId::of::<P>(), period.to_monotonic())
+        } else {
+            self.format_by_name(&period.name(), period.to_monotonic())
+        }
+    }
+    // returns the monotonic index of the parsed period
+    pub fn parse(&self, type_id: any::TypeId, s: &str) -> crate::Result<i64> {
+        (self.entry(type_id)?.parse)(s)
+    }
+    pub fn parse_by_name(&self, name: &str, s: &str) -> crate::Result<i64> {
+        match (self.type_id(name), minutes_length(name)) {
+            (Some(type_id), _) => self.parse(type_id, s),
+            (None, Some(length)) => Ok(DynMinutes::parse(length, s)?.index()),
+            (None, None) => Err(crate::Error::UnknownResolution(name.to_string())),
+        }
+    }
+    fn entry(&self, type_id: any::TypeId) -> crate::Result<&Entry> {
+        self.entries
+            .get(&type_id)
+            .ok_or_else(|| crate::Error::UnknownResolution(format!("{:?}", type_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ResolutionRegistry;
+    use crate::{Date, HalfHour, Minutes};
+    use std::any::TypeId;
+
+    #[test]
+    fn test_builtins() {
+        let registry = ResolutionRegistry::new();
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        assert_eq!(
+            registry.format(TypeId::of::<Date>(), date.to_monotonic()).unwrap(),
+            "2021-07-15"
+        );
+        assert_eq!(registry.parse_by_name("Date", "2021-07-15").unwrap(), date.to_monotonic());
+        assert_eq!(registry.name(TypeId::of::<HalfHour>()), Some("Minutes[Length:30]"));
+        assert_eq!(
+            registry.format_by_name("Minutes[Length:30]", 2).unwrap(),
+            "1970-01-01 01:00:00 - 1970-01-01 01:30:00"
+        );
+        assert!(registry.format(TypeId::of::<Date>(), i64::MAX).is_err());
+        assert!(registry.parse_by_name("Date", "Jul-2021").is_err());
+    }
+
+    #[test]
+    fn test_custom() {
+        let mut registry = ResolutionRegistry::new();
+        assert!(!registry.is_registered(TypeId::of::<Minutes<15>>()));
+        assert!(registry.format_by_name("Weeks", 1).is_err());
+
+        registry.register::<Minutes<15>>("Minutes[Length:15]");
+        assert!(registry.is_registered(TypeId::of::<Minutes<15>>()));
+        assert_eq!(
+            registry.format(TypeId::of::<Minutes<15>>(), 1).unwrap(),
+            "1970-01-01 00:15:00 - 1970-01-01 00:30:00"
+        );
+        assert_eq!(registry.parse_by_name("Minutes[Length:15]", "1970-01-01 00:30").unwrap(), 2);
+
+        // re-registering under a new name replaces the old one
+        registry.register::<Minutes<15>>("QuarterHour");
+        assert_eq!(registry.type_id("Minutes[Length:15]"), None);
+        assert_eq!(registry.name(TypeId::of::<Minutes<15>>()), Some("QuarterHour"));
+    }
+
+    #[test]
+    fn test_any_minutes_length() {
+        let registry = ResolutionRegistry::new();
+        let period = "2021-07-15 10:45".parse::<Minutes<15>>().unwrap();
+        assert_eq!(
+            registry.format_period(&period).unwrap(),
+            "2021-07-15 10:45:00 - 2021-07-15 11:00:00"
+        );
+        assert_eq!(
+            registry.format_by_name("Minutes[Length:15]", period.to_monotonic()).unwrap(),
+            period.to_string()
+        );
+        assert_eq!(
+            registry.parse_by_name("Minutes[Length:15]", "2021-07-15 10:45").unwrap(),
+            period.to_monotonic()
+        );
+        assert!(registry.parse_by_name("Minutes[Length:15]", "2021-07-15 10:50").is_err());
+        assert!(registry.format_by_name("Minutes[Length:0]", 1).is_err());
+        assert!(registry.format_by_name("Minutes[Length:15]", i64::MAX).is_err());
+        let date = crate::Date::from_ymd(2021, 7, 15).unwrap();
+        assert_eq!(registry.format_period(&date).unwrap(), "2021-07-15");
+    }
+}
@@ -0,0 +1,317 @@
+use crate::{
+    recurrence::{is_nth_within, SEARCH_DAYS, SEARCH_YEARS},
+    Date, DateResolution, Month, TimeRange, TimeResolution, Year,
+};
+use chrono::Datelike;
+use std::{collections, num, str};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+// A subset of RFC 5545 recurrence rules: FREQ (DAILY, WEEKLY, MONTHLY, YEARLY)
+// with INTERVAL, BYDAY, BYMONTHDAY, and either COUNT or UNTIL.
+// Weeks start on Monday, and the time of day (if any) in UNTIL is ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RRule {
+    freq: Frequency,
+    interval: num::NonZeroU32,
+    // an optional position (eg: the -1 of -1FR) and a weekday
+    by_day: Vec<(Option<i32>, chrono::Weekday)>,
+    by_month_day: Vec<i32>,
+    count: Option<u32>,
+    until: Option<Date>,
+}
+
+impl RRule {
+    pub fn frequency(&self) -> Frequency {
+        self.freq
+    }
+
+    // The dates matching the rule, starting from (and including if it matches) `start`
+    pub fn dates(&self, start: Date) -> RRuleIter<'_> {
+        RRuleIter {
+            rule: self,
+            start,
+            unit: 0,
+            pending: collections::VecDeque::new(),
+            emitted: 0,
+        }
+    }
+
+    // The distinct periods containing the matching dates (eg: the Months of a MONTHLY rule)
+    pub fn periods<'a, P: DateResolution + 'a>(&'a self, start: Date) -> impl Iterator<Item = P> + 'a {
+        let mut last = None;
+        self.dates(start)
+            .map(|d| P::from_datetime(d.naive_date_time()))
+            .filter(move |p| last.replace(*p) != Some(*p))
+    }
+
+    // All dates within the `unit`th frequency period (eg: the `unit`th Month
+    // for a MONTHLY rule, after skipping by the interval), or `None` if it's out of range
+    fn unit_dates(&self, start: Date, unit: u32) -> Option<TimeRange<Date>> {
+        let step = unit.checked_mul(self.interval.get())?;
+        Some(match self.freq {
+            Frequency::Daily => TimeRange::new(start.checked_succ_n(step).ok()?, 1),
+            Frequency::Weekly => {
+                let monday = start.checked_pred_n(start.weekday().num_days_from_monday()).ok()?;
+                TimeRange::new(monday.checked_succ_n(step.checked_mul(7)?).ok()?, 7)
+            }
+            Frequency::Monthly => Month::from_date(start.start()).checked_succ_n(step).ok()?.days(),
+            Frequency::Yearly => {
+                let year = Year::from_date(start.start()).checked_succ_n(step).ok()?;
+                TimeRange::new(Date::from(year.start()), year.num_days())
+            }
+        })
+    }
+
+    // The number of units in `SEARCH_YEARS`, after which an iterator stops looking for a match
+    fn max_empty_units(&self) -> u32 {
+        match self.freq {
+            Frequency::Daily => SEARCH_DAYS,
+            Frequency::Weekly => SEARCH_DAYS / 7 + 1,
+            Frequency::Monthly => SEARCH_YEARS * 12,
+            Frequency::Yearly => SEARCH_YEARS,
+        }
+    }
+
+    fn matches(&self, start: Date, date: Date) -> bool {
+        let by_day = self.by_day.iter().any(|(nth, weekday)| {
+            date.weekday() == *weekday
+                && match (nth, self.freq) {
+                    (None, _) => true,
+                    (Some(nth), Frequency::Yearly) => is_nth_within::<Year>(date, 7, *nth),
+                    (Some(nth), _) => is_nth_within::<Month>(date, 7, *nth),
+                }
+        });
+        let by_month_day = self
+            .by_month_day
+            .iter()
+            .any(|nth| is_nth_within::<Month>(date, 1, *nth));
+        match (self.by_day.is_empty(), self.by_month_day.is_empty()) {
+            (false, false) => by_day && by_month_day,
+            (false, true) => by_day,
+            (true, false) => by_month_day,
+            // otherwise the rule repeats on the same day as the start
+            (true, true) => match self.freq {
+                Frequency::Daily => true,
+                Frequency::Weekly => date.weekday() == start.weekday(),
+                Frequency::Monthly => date.start().day() == start.start().day(),
+                Frequency::Yearly => {
+                    date.start().day() == start.start().day()
+                        && date.start().month() == start.start().month()
+                }
+            },
+        }
+    }
+}
+
+pub struct RRuleIter<'a> {
+    rule: &'a RRule,
+    start: Date,
+    unit: u32,
+    pending: collections::VecDeque<Date>,
+    emitted: u32,
+}
+
+impl<'a> Iterator for RRuleIter<'a> {
+    type Item = Date;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rule.count.is_some_and(|count| self.emitted >= count) {
+            return None;
+        }
+        let mut empty_units = 0;
+        while self.pending.is_empty() {
+            if empty_units >= self.rule.max_empty_units() {
+                return None;
+            }
+            empty_units += 1;
+            let (rule, start) = (self.rule, self.start);
+            let unit = rule.unit_dates(start, self.unit)?;
+            if rule.until.is_some_and(|until| unit.start() > until) {
+                return None;
+            }
+            self.unit = self.unit.checked_add(1)?;
+            self.pending
+                .extend(unit.iter().filter(|d| *d >= start && rule.matches(start, *d)));
+        }
+        let date = self.pending.pop_front()?;
+        if self.rule.until.is_some_and(|until| date > until) {
+            return None;
+        }
+        self.emitted += 1;
+        Some(date)
+    }
+}
+
+fn parse_weekday(s: &str) -> Option<chrono::Weekday> {
+    use chrono::Weekday::*;
+    Some(match s {
+        "MO" => Mon,
+        "TU" => Tue,
+        "WE" => Wed,
+        "TH" => Thu,
+        "FR" => Fri,
+        "SA" => Sat,
+        "SU" => Sun,
+        _ => return None,
+    })
+}
+
+fn parse_by_day(s: &str) -> Option<(Option<i32>, chrono::Weekday)> {
+    let split = s.len().checked_sub(2)?;
+    let weekday = parse_weekday(s.get(split..)?)?;
+    let nth = match &s[..split] {
+        "" => None,
+        nth => Some(nth.trim_start_matches('+').parse::<i32>().ok().filter(|n| (-53..=53).contains(n) && *n != 0)?),
+    };
+    Some((nth, weekday))
+}
+
+// Accepts an optional `RRULE:` prefix (eg: `RRULE:FREQ=MONTHLY;BYDAY=-1FR;COUNT=3`)
+impl str::FromStr for RRule {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> crate::Result<RRule> {
+        let err = || crate::Error::ParseCustom {
+            ty_name: "RRule",
+            input: s.to_string(),
+        };
+        let mut freq = None;
+        let mut rule = RRule {
+            freq: Frequency::Daily,
+            interval: num::NonZeroU32::new(1).expect("Non-zero"),
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            count: None,
+            until: None,
+        };
+        let body = s.trim().strip_prefix("RRULE:").unwrap_or_else(|| s.trim());
+        for part in body.split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = part.split_once('=').ok_or_else(err)?;
+            match key {
+                "FREQ" => {
+                    freq = Some(match value {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(err()),
+                    })
+                }
+                "INTERVAL" => rule.interval = value.parse().map_err(|_| err())?,
+                "COUNT" => rule.count = Some(value.parse().map_err(|_| err())?),
+                "UNTIL" => {
+                    let date = value.get(..8).ok_or_else(err)?;
+                    rule.until = Some(Date::from(chrono::NaiveDate::parse_from_str(date, "%Y%m%d")?));
+                }
+                "BYDAY" => {
+                    rule.by_day = value.split(',').map(parse_by_day).collect::<Option<_>>().ok_or_else(err)?
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = value
+                        .split(',')
+                        .map(|d| d.parse::<i32>().ok().filter(|d| (-31..=31).contains(d) && *d != 0))
+                        .collect::<Option<_>>()
+                        .ok_or_else(err)?
+                }
+                _ => return Err(err()),
+            }
+        }
+        rule.freq = freq.ok_or_else(err)?;
+        let positional = rule.by_day.iter().any(|(nth, _)| nth.is_some());
+        let invalid = (rule.count.is_some() && rule.until.is_some())
+            || (positional && matches!(rule.freq, Frequency::Daily | Frequency::Weekly))
+            || (!rule.by_month_day.is_empty() && rule.freq == Frequency::Weekly);
+        if invalid {
+            return Err(err());
+        }
+        Ok(rule)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Frequency, RRule};
+    use crate::{Date, Month};
+
+    fn date(y: i32, m: u32, d: u32) -> Date {
+        Date::from_ymd(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let rule = "RRULE:FREQ=MONTHLY;INTERVAL=2;BYDAY=-1FR,+2MO;COUNT=3".parse::<RRule>().unwrap();
+        assert_eq!(rule.frequency(), Frequency::Monthly);
+        assert!("FREQ=WEEKLY;BYDAY=MO,WE".parse::<RRule>().is_ok());
+        assert!("FREQ=DAILY;UNTIL=20211231T000000Z".parse::<RRule>().is_ok());
+        assert!("INTERVAL=2".parse::<RRule>().is_err());
+        assert!("FREQ=HOURLY".parse::<RRule>().is_err());
+        assert!("FREQ=DAILY;INTERVAL=0".parse::<RRule>().is_err());
+        assert!("FREQ=DAILY;COUNT=2;UNTIL=20211231".parse::<RRule>().is_err());
+        assert!("FREQ=WEEKLY;BYDAY=1MO".parse::<RRule>().is_err());
+        assert!("FREQ=MONTHLY;BYMONTHDAY=32".parse::<RRule>().is_err());
+        assert!("FREQ=MONTHLY;BYSETPOS=1".parse::<RRule>().is_err());
+    }
+
+    #[test]
+    fn test_daily_and_weekly() {
+        let every_other = "FREQ=DAILY;INTERVAL=2;COUNT=3".parse::<RRule>().unwrap();
+        let dates = every_other.dates(date(2021, 7, 30)).collect::<Vec<_>>();
+        assert_eq!(dates, vec![date(2021, 7, 30), date(2021, 8, 1), date(2021, 8, 3)]);
+        // starting on a Thursday, the Monday of the first week is skipped
+        let weekly = "FREQ=WEEKLY;BYDAY=MO,FR;UNTIL=20210726".parse::<RRule>().unwrap();
+        let dates = weekly.dates(date(2021, 7, 15)).collect::<Vec<_>>();
+        assert_eq!(dates, vec![date(2021, 7, 16), date(2021, 7, 19), date(2021, 7, 23), date(2021, 7, 26)]);
+        let same_day = "FREQ=WEEKLY;INTERVAL=2;COUNT=2".parse::<RRule>().unwrap();
+        let dates = same_day.dates(date(2021, 7, 15)).collect::<Vec<_>>();
+        assert_eq!(dates, vec![date(2021, 7, 15), date(2021, 7, 29)]);
+    }
+
+    #[test]
+    fn test_monthly_and_yearly() {
+        let last_friday = "FREQ=MONTHLY;BYDAY=-1FR;COUNT=3".parse::<RRule>().unwrap();
+        let dates = last_friday.dates(date(2021, 7, 1)).collect::<Vec<_>>();
+        assert_eq!(dates, vec![date(2021, 7, 30), date(2021, 8, 27), date(2021, 9, 24)]);
+        // months without a 31st are skipped
+        let the_31st = "FREQ=MONTHLY;BYMONTHDAY=31;COUNT=3".parse::<RRule>().unwrap();
+        let dates = the_31st.dates(date(2021, 7, 1)).collect::<Vec<_>>();
+        assert_eq!(dates, vec![date(2021, 7, 31), date(2021, 8, 31), date(2021, 10, 31)]);
+        let friday_13th = "FREQ=MONTHLY;BYDAY=FR;BYMONTHDAY=13;COUNT=2".parse::<RRule>().unwrap();
+        let dates = friday_13th.dates(date(2021, 1, 1)).collect::<Vec<_>>();
+        assert_eq!(dates, vec![date(2021, 8, 13), date(2022, 5, 13)]);
+        let last_thursday = "FREQ=YEARLY;BYDAY=-1TH;COUNT=1".parse::<RRule>().unwrap();
+        assert_eq!(last_thursday.dates(date(2021, 1, 1)).next(), Some(date(2021, 12, 30)));
+        let anniversary = "FREQ=YEARLY;COUNT=2".parse::<RRule>().unwrap();
+        let dates = anniversary.dates(date(2020, 2, 29)).collect::<Vec<_>>();
+        assert_eq!(dates, vec![date(2020, 2, 29), date(2024, 2, 29)]);
+    }
+
+    #[test]
+    fn test_no_matches() {
+        // the first Monday is never the 20th
+        let never = "FREQ=MONTHLY;BYDAY=1MO;BYMONTHDAY=20".parse::<RRule>().unwrap();
+        assert_eq!(never.dates(date(2021, 1, 1)).next(), None);
+        let never = "FREQ=DAILY;INTERVAL=7;BYDAY=MO".parse::<RRule>().unwrap();
+        assert_eq!(never.dates(date(2021, 7, 13)).next(), None);
+        // stops at the end of chrono's range rather than overflowing
+        let yearly = "FREQ=YEARLY;INTERVAL=1000000".parse::<RRule>().unwrap();
+        assert_eq!(yearly.dates(date(2021, 1, 1)).count(), 1);
+    }
+
+    #[test]
+    fn test_periods() {
+        let quarterly = "FREQ=MONTHLY;INTERVAL=3;BYMONTHDAY=1,15;COUNT=4".parse::<RRule>().unwrap();
+        let months = quarterly.periods::<Month>(date(2021, 1, 1)).collect::<Vec<_>>();
+        assert_eq!(
+            months,
+            vec![
+                Month::from_year_month(2021, 1).unwrap(),
+                Month::from_year_month(2021, 4).unwrap(),
+            ]
+        );
+    }
+}
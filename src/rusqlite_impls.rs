@@ -0,0 +1,146 @@
+use crate::{Date, Minutes, Month, Quarter, TimeResolution, Year};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use std::str;
+
+// Periods are written as their `Display` strings by default. Wrap a
+// period in `Monotonic` to write its monotonic index instead, which is
+// smaller and sorts correctly. Either form can be read back.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Monotonic<P: TimeResolution>(pub P);
+
+fn from_value<P>(value: ValueRef<'_>) -> FromSqlResult<P>
+where
+    P: TimeResolution + str::FromStr<Err = crate::Error>,
+{
+    let parsed = match value {
+        ValueRef::Text(_) => value.as_str()?.parse(),
+        ValueRef::Integer(idx) => P::try_from_monotonic(idx),
+        _ => return Err(FromSqlError::InvalidType),
+    };
+    parsed.map_err(|e| FromSqlError::Other(Box::new(e)))
+}
+
+macro_rules! impl_resolution {
+    ($($ty:ty),*) => {
+        $(
+            impl ToSql for $ty {
+                fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+                    Ok(ToSqlOutput::from(self.to_string()))
+                }
+            }
+
+            impl FromSql for $ty {
+                fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+                    from_value(value)
+                }
+            }
+        )*
+    };
+}
+
+impl_resolution!(Date, Month, Quarter, Year);
+
+impl<const N: u32> ToSql for Minutes<N> {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_string()))
+    }
+}
+
+impl<const N: u32> FromSql for Minutes<N> {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        from_value(value)
+    }
+}
+
+impl<P: TimeResolution> ToSql for Monotonic<P> {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.0.to_monotonic()))
+    }
+}
+
+impl<P: TimeResolution + FromSql> FromSql for Monotonic<P> {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        P::column_result(value).map(Monotonic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Monotonic;
+    use crate::{Date, HalfHour, Month, Quarter};
+
+    fn connection() -> rusqlite::Connection {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute("CREATE TABLE periods (period, value REAL)", [])
+            .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let conn = connection();
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        let half_hour = "2021-07-15 10:30".parse::<HalfHour>().unwrap();
+        conn.execute("INSERT INTO periods VALUES (?1, 1.0)", [date])
+            .unwrap();
+        conn.execute("INSERT INTO periods VALUES (?1, 2.0)", [half_hour])
+            .unwrap();
+        conn.execute("INSERT INTO periods VALUES (?1, 3.0)", [Monotonic(date)])
+            .unwrap();
+
+        let stored = conn
+            .query_row("SELECT period FROM periods WHERE value = 1.0", [], |row| {
+                row.get::<_, String>(0)
+            })
+            .unwrap();
+        assert_eq!(stored, "2021-07-15");
+        let read = conn
+            .query_row("SELECT period FROM periods WHERE value = 1.0", [], |row| {
+                row.get::<_, Date>(0)
+            })
+            .unwrap();
+        assert_eq!(read, date);
+        let read = conn
+            .query_row("SELECT period FROM periods WHERE value = 2.0", [], |row| {
+                row.get::<_, HalfHour>(0)
+            })
+            .unwrap();
+        assert_eq!(read, half_hour);
+        let read = conn
+            .query_row("SELECT period FROM periods WHERE value = 3.0", [], |row| {
+                row.get::<_, Monotonic<Date>>(0)
+            })
+            .unwrap();
+        assert_eq!(read, Monotonic(date));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let conn = connection();
+        conn.execute("INSERT INTO periods VALUES ('not a period', 1.0)", [])
+            .unwrap();
+        conn.execute("INSERT INTO periods VALUES (?1, 2.0)", [i64::MAX])
+            .unwrap();
+        conn.execute("INSERT INTO periods VALUES ('2021-07-15 10:45', 3.0)", [])
+            .unwrap();
+        for value in &[1.0, 2.0] {
+            assert!(conn
+                .query_row(
+                    "SELECT period FROM periods WHERE value = ?1",
+                    [value],
+                    |row| { row.get::<_, Month>(0) }
+                )
+                .is_err());
+        }
+        assert!(conn
+            .query_row("SELECT period FROM periods WHERE value = 3.0", [], |row| {
+                row.get::<_, HalfHour>(0)
+            })
+            .is_err());
+        assert!(conn
+            .query_row("SELECT period FROM periods WHERE value = 1.0", [], |row| {
+                row.get::<_, Quarter>(0)
+            })
+            .is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use crate::TimeResolution;
+use serde::{de, ser, Deserialize};
+use serde_with::{DeserializeAs, SerializeAs};
+use std::{fmt, marker, str};
+
+// `serde_with` adapters for periods, which work for map keys and values
+// as well as plain fields, eg:
+//
+// #[serde_as(as = "BTreeMap<AsPeriodString, _>")]
+// values: BTreeMap<Date, f64>,
+//
+// serializes the map as an object keyed by the `Display` of each period.
+
+// A period as its monotonic index
+pub struct AsMonotonic;
+
+impl<P: TimeResolution> SerializeAs<P> for AsMonotonic {
+    fn serialize_as<S: ser::Serializer>(source: &P, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(source.to_monotonic())
+    }
+}
+
+impl<'de, P: TimeResolution> DeserializeAs<'de, P> for AsMonotonic {
+    fn deserialize_as<D: de::Deserializer<'de>>(deserializer: D) -> Result<P, D::Error> {
+        let idx = i64::deserialize(deserializer)?;
+        P::try_from_monotonic(idx).map_err(de::Error::custom)
+    }
+}
+
+// A period as its `Display` string, parsed back with `FromStr`
+pub struct AsPeriodString;
+
+impl<P: TimeResolution> SerializeAs<P> for AsPeriodString {
+    fn serialize_as<S: ser::Serializer>(source: &P, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(source)
+    }
+}
+
+impl<'de, P> DeserializeAs<'de, P> for AsPeriodString
+where
+    P: TimeResolution + str::FromStr<Err = crate::Error>,
+{
+    fn deserialize_as<D: de::Deserializer<'de>>(deserializer: D) -> Result<P, D::Error> {
+        deserializer.deserialize_str(PeriodStrVisitor(marker::PhantomData))
+    }
+}
+
+struct PeriodStrVisitor<P>(marker::PhantomData<P>);
+
+impl<'de, P> de::Visitor<'de> for PeriodStrVisitor<P>
+where
+    P: TimeResolution + str::FromStr<Err = crate::Error>,
+{
+    type Value = P;
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a period string")
+    }
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<P, E> {
+        s.parse().map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AsMonotonic, AsPeriodString};
+    use crate::{Date, HalfHour, Month};
+    use serde_with::serde_as;
+    use std::collections::BTreeMap;
+
+    #[serde_as]
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Values {
+        #[serde_as(as = "BTreeMap<AsPeriodString, _>")]
+        daily: BTreeMap<Date, f64>,
+        #[serde_as(as = "BTreeMap<AsMonotonic, _>")]
+        intervals: BTreeMap<HalfHour, f64>,
+        #[serde_as(as = "Vec<AsMonotonic>")]
+        months: Vec<Month>,
+    }
+
+    #[test]
+    fn test_adapters() {
+        let date = Date::from_ymd(2021, 7, 15).unwrap();
+        let interval = "2021-07-15 10:30".parse::<HalfHour>().unwrap();
+        let values = Values {
+            daily: vec![(date, 1.5)].into_iter().collect(),
+            intervals: vec![(interval, 2.5)].into_iter().collect(),
+            months: vec![Month::from_year_month(2021, 7).unwrap()],
+        };
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(
+            json,
+            r#"{"daily":{"2021-07-15":1.5},"intervals":{"903525":2.5},"months":[24258]}"#
+        );
+        assert_eq!(serde_json::from_str::<Values>(&json).unwrap(), values);
+
+        let invalid = r#"{"daily":{"2021-07-15":1.5},"intervals":{},"months":[9223372036854775807]}"#;
+        assert!(serde_json::from_str::<Values>(invalid).is_err());
+    }
+}
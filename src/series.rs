@@ -0,0 +1,363 @@
+use crate::TimeResolution;
+use std::{collections, iter, num};
+
+// Values keyed by period, in period order
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeSeries<P: TimeResolution, T> {
+    data: collections::BTreeMap<P, T>,
+}
+
+// Built-in ways of combining the values of the fine periods
+// within each coarse period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+    Sum,
+    Mean,
+    Min,
+    Max,
+    First,
+    Last,
+    // a mean weighted by the duration of each fine period
+    WeightedMean,
+}
+
+impl<P: TimeResolution, T> TimeSeries<P, T> {
+    pub fn empty() -> TimeSeries<P, T> {
+        TimeSeries {
+            data: collections::BTreeMap::new(),
+        }
+    }
+    pub fn insert(&mut self, period: P, value: T) -> Option<T> {
+        self.data.insert(period, value)
+    }
+    pub fn get(&self, period: P) -> Option<&T> {
+        self.data.get(&period)
+    }
+    pub fn remove(&mut self, period: P) -> Option<T> {
+        self.data.remove(&period)
+    }
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+    pub fn first(&self) -> Option<(P, &T)> {
+        self.data.iter().next().map(|(p, v)| (*p, v))
+    }
+    pub fn last(&self) -> Option<(P, &T)> {
+        self.data.iter().next_back().map(|(p, v)| (*p, v))
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (P, &T)> + '_ {
+        self.data.iter().map(|(p, v)| (*p, v))
+    }
+    // Windows covering the `window` periods ending at each period in the series.
+    // Missing periods are not filled, so a window may hold fewer values.
+    pub fn rolling(&self, window: num::NonZeroU32) -> Rolling<'_, P, T> {
+        Rolling {
+            series: self,
+            window,
+            edges: Edges::Skip,
+        }
+    }
+    pub fn into_map(self) -> collections::BTreeMap<P, T> {
+        self.data
+    }
+    // an estimate of the heap memory used, not including memory owned by the values
+    pub fn approx_memory_bytes(&self) -> usize {
+        crate::btree_memory_bytes::<P, T>(self.data.len())
+    }
+
+    // Groups the values by the coarse period containing each fine period,
+    // and combines each group with `f`. Errors if a fine period is not
+    // entirely within a single coarse period (eg: `Hour` to `Minutes<45>`).
+    pub fn aggregate_to_with<C, U, F>(&self, mut f: F) -> crate::Result<TimeSeries<C, U>>
+    where
+        C: TimeResolution,
+        F: FnMut(C, &[(P, &T)]) -> U,
+    {
+        let mut out = TimeSeries::empty();
+        let mut group: Vec<(P, &T)> = Vec::new();
+        let mut current = None;
+        for (fine, value) in self.iter() {
+            let coarse = fine.containing::<C>()?;
+            if current != Some(coarse) {
+                if let Some(prev) = current {
+                    out.insert(prev, f(prev, &group));
+                }
+                group.clear();
+                current = Some(coarse);
+            }
+            group.push((fine, value));
+        }
+        if let Some(prev) = current {
+            out.insert(prev, f(prev, &group));
+        }
+        Ok(out)
+    }
+}
+
+impl<P: TimeResolution> TimeSeries<P, f64> {
+    pub fn aggregate_to<C: TimeResolution>(
+        &self,
+        agg: Aggregation,
+    ) -> crate::Result<TimeSeries<C, f64>> {
+        self.aggregate_to_with(|_, group: &[(P, &f64)]| {
+            let mut values = group.iter().map(|(_, v)| **v);
+            match agg {
+                Aggregation::Sum => values.sum(),
+                Aggregation::Mean => values.sum::<f64>() / group.len() as f64,
+                Aggregation::Min => values.fold(f64::INFINITY, f64::min),
+                Aggregation::Max => values.fold(f64::NEG_INFINITY, f64::max),
+                Aggregation::First => values.next().expect("Groups are never empty"),
+                Aggregation::Last => values.next_back().expect("Groups are never empty"),
+                Aggregation::WeightedMean => {
+                    let (total, weights) = group.iter().fold((0.0, 0.0), |(total, weights), (p, v)| {
+                        let weight = duration_secs(*p);
+                        (total + **v * weight, weights + weight)
+                    });
+                    total / weights
+                }
+            }
+        })
+    }
+}
+
+// How to handle windows which extend before the first period in the series
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edges {
+    // emit the windows, with only the values available
+    Partial,
+    // don't emit a value until the window is entirely within the series
+    Skip,
+}
+
+pub struct Rolling<'a, P: TimeResolution, T> {
+    series: &'a TimeSeries<P, T>,
+    window: num::NonZeroU32,
+    edges: Edges,
+}
+
+impl<'a, P: TimeResolution, T> Rolling<'a, P, T> {
+    pub fn edges(self, edges: Edges) -> Rolling<'a, P, T> {
+        Rolling { edges, ..self }
+    }
+    // Calls `f` with the values in the window ending at each period,
+    // keying the result by that period
+    pub fn map<U, F>(&self, mut f: F) -> TimeSeries<P, U>
+    where
+        F: FnMut(&[(P, &T)]) -> U,
+    {
+        let first = match self.series.first() {
+            Some((first, _)) => first,
+            None => return TimeSeries::empty(),
+        };
+        let back = self.window.get() - 1;
+        let values = self.series.iter().collect::<Vec<_>>();
+        let mut out = TimeSeries::empty();
+        let mut lower = 0;
+        for (upper, (end, _)) in values.iter().enumerate() {
+            if self.edges == Edges::Skip && first.between(*end) < i64::from(back) {
+                continue;
+            }
+            let start = end.pred_n(back);
+            while values[lower].0 < start {
+                lower += 1;
+            }
+            out.insert(*end, f(&values[lower..=upper]));
+        }
+        out
+    }
+}
+
+impl<'a, P: TimeResolution> Rolling<'a, P, f64> {
+    pub fn sum(&self) -> TimeSeries<P, f64> {
+        self.map(|window| window.iter().map(|(_, v)| **v).sum())
+    }
+    pub fn mean(&self) -> TimeSeries<P, f64> {
+        self.map(|window| window.iter().map(|(_, v)| **v).sum::<f64>() / window.len() as f64)
+    }
+    pub fn min(&self) -> TimeSeries<P, f64> {
+        self.map(|window| window.iter().map(|(_, v)| **v).fold(f64::INFINITY, f64::min))
+    }
+    pub fn max(&self) -> TimeSeries<P, f64> {
+        self.map(|window| window.iter().map(|(_, v)| **v).fold(f64::NEG_INFINITY, f64::max))
+    }
+}
+
+// Groups values by the coarse period containing each fine period, keeping the input order
+// within each group. Errors if a fine period is not entirely within a single coarse period.
+pub fn group_by_resolution<F, C, T>(
+    iter: impl IntoIterator<Item = (F, T)>,
+) -> crate::Result<collections::BTreeMap<C, Vec<(F, T)>>>
+where
+    F: TimeResolution,
+    C: TimeResolution,
+{
+    let mut groups = collections::BTreeMap::<C, Vec<(F, T)>>::new();
+    for (fine, value) in iter {
+        groups.entry(fine.containing()?).or_default().push((fine, value));
+    }
+    Ok(groups)
+}
+
+// As `group_by_resolution`, but folds each group into an accumulator starting from `init`
+pub fn fold_by_resolution<F, C, T, A, G>(
+    iter: impl IntoIterator<Item = (F, T)>,
+    init: A,
+    mut f: G,
+) -> crate::Result<collections::BTreeMap<C, A>>
+where
+    F: TimeResolution,
+    C: TimeResolution,
+    A: Clone,
+    G: FnMut(A, F, T) -> A,
+{
+    let mut folded = collections::BTreeMap::new();
+    for (fine, value) in iter {
+        let coarse = fine.containing()?;
+        let acc = folded.remove(&coarse).unwrap_or_else(|| init.clone());
+        folded.insert(coarse, f(acc, fine, value));
+    }
+    Ok(folded)
+}
+
+fn duration_secs<P: TimeResolution>(period: P) -> f64 {
+    (period.succ().naive_date_time() - period.naive_date_time()).num_seconds() as f64
+}
+
+impl<P: TimeResolution, T> Default for TimeSeries<P, T> {
+    fn default() -> TimeSeries<P, T> {
+        TimeSeries::empty()
+    }
+}
+
+impl<P: TimeResolution, T> iter::FromIterator<(P, T)> for TimeSeries<P, T> {
+    fn from_iter<I: IntoIterator<Item = (P, T)>>(iter: I) -> TimeSeries<P, T> {
+        TimeSeries {
+            data: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<P: TimeResolution, T> From<collections::BTreeMap<P, T>> for TimeSeries<P, T> {
+    fn from(data: collections::BTreeMap<P, T>) -> TimeSeries<P, T> {
+        TimeSeries { data }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fold_by_resolution, group_by_resolution, Aggregation, Edges, TimeSeries};
+    use crate::{Date, FiveMinute, HalfHour, Hour, Minutes, Month, TimeRange, TimeResolution, Year};
+
+    fn five_minutes() -> TimeSeries<FiveMinute, f64> {
+        let start = "2021-07-15 10:00".parse::<FiveMinute>().unwrap();
+        TimeRange::new(start, 12)
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p, i as f64))
+            .collect()
+    }
+
+    #[test]
+    fn test_aggregate_to() {
+        let series = five_minutes();
+        let first = "2021-07-15 10:00".parse::<HalfHour>().unwrap();
+        let sums = series.aggregate_to::<HalfHour>(Aggregation::Sum).unwrap();
+        assert_eq!(sums.len(), 2);
+        assert_eq!(sums.get(first), Some(&15.0));
+        assert_eq!(sums.get(first.succ()), Some(&51.0));
+        let check = |agg, first_value, second_value| {
+            let out = series.aggregate_to::<HalfHour>(agg).unwrap();
+            assert_eq!(out.get(first), Some(&first_value), "{:?}", agg);
+            assert_eq!(out.get(first.succ()), Some(&second_value), "{:?}", agg);
+        };
+        check(Aggregation::Mean, 2.5, 8.5);
+        check(Aggregation::Min, 0.0, 6.0);
+        check(Aggregation::Max, 5.0, 11.0);
+        check(Aggregation::First, 0.0, 6.0);
+        check(Aggregation::Last, 5.0, 11.0);
+        check(Aggregation::WeightedMean, 2.5, 8.5);
+        let daily = series.aggregate_to::<Date>(Aggregation::Sum).unwrap();
+        assert_eq!(daily.first(), Some((Date::from_ymd(2021, 7, 15).unwrap(), &66.0)));
+    }
+
+    #[test]
+    fn test_approx_memory_bytes() {
+        assert_eq!(TimeSeries::<FiveMinute, f64>::empty().approx_memory_bytes(), 0);
+        let series = five_minutes();
+        // one node holds 11 entries
+        assert!(series.approx_memory_bytes() >= 12 * (8 + 8));
+        assert!(series.approx_memory_bytes() < 24 * (8 + 8) + 64);
+    }
+
+    #[test]
+    fn test_weighted_mean() {
+        let year = Year::from_date(chrono::NaiveDate::from_ymd_opt(2021, 1, 1).unwrap());
+        // February is weighted less than January
+        let series = year
+            .months()
+            .iter()
+            .take(2)
+            .zip(vec![0.0, 1.0])
+            .collect::<TimeSeries<Month, f64>>();
+        let out = series.aggregate_to::<Year>(Aggregation::WeightedMean).unwrap();
+        assert_eq!(out.get(year), Some(&(28.0 / 59.0)));
+    }
+
+    #[test]
+    fn test_rolling() {
+        let series = five_minutes();
+        let start = "2021-07-15 10:00".parse::<FiveMinute>().unwrap();
+        let window = std::num::NonZeroU32::new(3).unwrap();
+        let sums = series.rolling(window).sum();
+        assert_eq!(sums.len(), 10);
+        assert_eq!(sums.first(), Some((start.succ_n(2), &3.0)));
+        assert_eq!(sums.last(), Some((start.succ_n(11), &30.0)));
+        let partial = series.rolling(window).edges(Edges::Partial);
+        assert_eq!(partial.mean().len(), 12);
+        assert_eq!(partial.mean().get(start.succ()), Some(&0.5));
+        assert_eq!(partial.min().get(start.succ_n(5)), Some(&3.0));
+        assert_eq!(partial.max().get(start.succ_n(5)), Some(&5.0));
+        // windows are by period, so gaps shrink the window
+        let mut gaps = series.clone();
+        gaps.data.remove(&start.succ_n(4));
+        let counts = gaps.rolling(window).map(|window| window.len());
+        assert_eq!(counts.get(start.succ_n(5)), Some(&2));
+        assert_eq!(counts.get(start.succ_n(7)), Some(&3));
+        let one = std::num::NonZeroU32::new(1).unwrap();
+        assert_eq!(series.rolling(one).sum(), series);
+        assert!(TimeSeries::<Date, f64>::empty().rolling(window).mean().is_empty());
+    }
+
+    #[test]
+    fn test_group_by_resolution() {
+        let start = "2021-07-15 23:00".parse::<HalfHour>().unwrap();
+        let readings = TimeRange::new(start, 4).iter().map(|p| (p, 1.5)).collect::<Vec<_>>();
+        let groups = group_by_resolution::<_, Date, _>(readings.iter().rev().cloned()).unwrap();
+        assert_eq!(groups.len(), 2);
+        let first = groups.get(&Date::from_ymd(2021, 7, 15).unwrap()).unwrap();
+        assert_eq!(first, &vec![readings[1], readings[0]]);
+        let totals = fold_by_resolution::<_, Date, _, _, _>(readings, 0.0, |acc, _, v| acc + v).unwrap();
+        assert_eq!(totals.get(&Date::from_ymd(2021, 7, 16).unwrap()), Some(&3.0));
+        let hours = vec![("2021-07-15 10:00".parse::<Hour>().unwrap(), ())];
+        assert!(group_by_resolution::<_, Minutes<45>, _>(hours).is_err());
+    }
+
+    #[test]
+    fn test_aggregate_to_with() {
+        let series = five_minutes();
+        let counts = series
+            .aggregate_to_with(|_: HalfHour, group: &[(FiveMinute, &f64)]| group.len())
+            .unwrap();
+        assert!(counts.iter().all(|(_, n)| *n == 6));
+        assert!(series.aggregate_to::<Minutes<45>>(Aggregation::Sum).is_ok());
+        let halves = series.aggregate_to::<HalfHour>(Aggregation::Sum).unwrap();
+        assert!(halves.aggregate_to::<Minutes<20>>(Aggregation::Sum).is_err());
+        assert!(TimeSeries::<HalfHour, f64>::empty()
+            .aggregate_to::<Date>(Aggregation::Mean)
+            .unwrap()
+            .is_empty());
+    }
+}
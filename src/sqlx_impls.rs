@@ -0,0 +1,87 @@
+use crate::{Date, DateResolution, Minutes, Month, Quarter, Rounding, TimeResolution, Year};
+use sqlx::{
+    decode::Decode,
+    encode::{Encode, IsNull},
+    error::BoxDynError,
+    postgres::{PgArgumentBuffer, PgHasArrayType, PgTypeInfo, PgValueRef, Postgres},
+    types::Type,
+};
+
+// Date resolutions are stored as `DATE` and sub-date resolutions as
+// `TIMESTAMP`, using the start of the period. Decoding fails for values
+// which are not on a period boundary.
+macro_rules! impl_date_resolution {
+    ($($ty:ty),*) => {
+        $(
+            impl Type<Postgres> for $ty {
+                fn type_info() -> PgTypeInfo {
+                    <chrono::NaiveDate as Type<Postgres>>::type_info()
+                }
+            }
+
+            impl PgHasArrayType for $ty {
+                fn array_type_info() -> PgTypeInfo {
+                    <chrono::NaiveDate as PgHasArrayType>::array_type_info()
+                }
+            }
+
+            impl Encode<'_, Postgres> for $ty {
+                fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+                    Encode::<Postgres>::encode_by_ref(&self.start(), buf)
+                }
+            }
+
+            impl<'r> Decode<'r, Postgres> for $ty {
+                fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+                    let date = <chrono::NaiveDate as Decode<Postgres>>::decode(value)?;
+                    let midnight = date.and_hms_opt(0, 0, 0).expect("Midnight is always valid");
+                    Ok(<$ty>::from_datetime_with(midnight, Rounding::Strict)?)
+                }
+            }
+        )*
+    };
+}
+
+impl_date_resolution!(Date, Month, Quarter, Year);
+
+impl<const N: u32> Type<Postgres> for Minutes<N> {
+    fn type_info() -> PgTypeInfo {
+        <chrono::NaiveDateTime as Type<Postgres>>::type_info()
+    }
+}
+
+impl<const N: u32> PgHasArrayType for Minutes<N> {
+    fn array_type_info() -> PgTypeInfo {
+        <chrono::NaiveDateTime as PgHasArrayType>::array_type_info()
+    }
+}
+
+impl<const N: u32> Encode<'_, Postgres> for Minutes<N> {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        Encode::<Postgres>::encode_by_ref(&self.naive_date_time(), buf)
+    }
+}
+
+impl<'r, const N: u32> Decode<'r, Postgres> for Minutes<N> {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        let dt = <chrono::NaiveDateTime as Decode<Postgres>>::decode(value)?;
+        Ok(Minutes::from_datetime_with(dt, Rounding::Strict)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Date, HalfHour, Month, Year};
+    use sqlx::{postgres::Postgres, Type, TypeInfo};
+
+    #[test]
+    fn test_type_info() {
+        assert_eq!(<Date as Type<Postgres>>::type_info().name(), "DATE");
+        assert_eq!(<Month as Type<Postgres>>::type_info().name(), "DATE");
+        assert_eq!(<Year as Type<Postgres>>::type_info().name(), "DATE");
+        assert_eq!(
+            <HalfHour as Type<Postgres>>::type_info().name(),
+            "TIMESTAMP"
+        );
+    }
+}
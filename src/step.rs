@@ -0,0 +1,107 @@
+use crate::{Date, Minutes, Month, Quarter, TimeResolution, Year};
+use std::{convert::TryFrom, iter::Step};
+
+fn steps_between<P: TimeResolution>(start: &P, end: &P) -> (usize, Option<usize>) {
+    match usize::try_from(start.between(*end)) {
+        Ok(n) => (n, Some(n)),
+        // either the end is before the start, or
+        // the difference doesn't fit into a usize
+        Err(_) if start <= end => (usize::MAX, None),
+        Err(_) => (0, None),
+    }
+}
+
+fn forward_checked<P: TimeResolution>(start: P, count: usize) -> Option<P> {
+    let idx = start
+        .to_monotonic()
+        .checked_add(i64::try_from(count).ok()?)?;
+    P::try_from_monotonic(idx).ok()
+}
+
+fn backward_checked<P: TimeResolution>(start: P, count: usize) -> Option<P> {
+    let idx = start
+        .to_monotonic()
+        .checked_sub(i64::try_from(count).ok()?)?;
+    P::try_from_monotonic(idx).ok()
+}
+
+impl Step for Date {
+    fn steps_between(start: &Self, end: &Self) -> (usize, Option<usize>) {
+        steps_between(start, end)
+    }
+    fn forward_checked(start: Self, count: usize) -> Option<Self> {
+        forward_checked(start, count)
+    }
+    fn backward_checked(start: Self, count: usize) -> Option<Self> {
+        backward_checked(start, count)
+    }
+}
+
+impl Step for Month {
+    fn steps_between(start: &Self, end: &Self) -> (usize, Option<usize>) {
+        steps_between(start, end)
+    }
+    fn forward_checked(start: Self, count: usize) -> Option<Self> {
+        forward_checked(start, count)
+    }
+    fn backward_checked(start: Self, count: usize) -> Option<Self> {
+        backward_checked(start, count)
+    }
+}
+
+impl Step for Quarter {
+    fn steps_between(start: &Self, end: &Self) -> (usize, Option<usize>) {
+        steps_between(start, end)
+    }
+    fn forward_checked(start: Self, count: usize) -> Option<Self> {
+        forward_checked(start, count)
+    }
+    fn backward_checked(start: Self, count: usize) -> Option<Self> {
+        backward_checked(start, count)
+    }
+}
+
+impl Step for Year {
+    fn steps_between(start: &Self, end: &Self) -> (usize, Option<usize>) {
+        steps_between(start, end)
+    }
+    fn forward_checked(start: Self, count: usize) -> Option<Self> {
+        forward_checked(start, count)
+    }
+    fn backward_checked(start: Self, count: usize) -> Option<Self> {
+        backward_checked(start, count)
+    }
+}
+
+impl<const N: u32> Step for Minutes<N> {
+    fn steps_between(start: &Self, end: &Self) -> (usize, Option<usize>) {
+        steps_between(start, end)
+    }
+    fn forward_checked(start: Self, count: usize) -> Option<Self> {
+        forward_checked(start, count)
+    }
+    fn backward_checked(start: Self, count: usize) -> Option<Self> {
+        backward_checked(start, count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Date, Minutes, Month};
+
+    #[test]
+    fn test_range_syntax() {
+        let start = Date::from_ymd(2021, 12, 30).unwrap();
+        let end = Date::from_ymd(2022, 1, 2).unwrap();
+        assert_eq!((start..=end).count(), 4);
+        assert_eq!((start..end).last(), Date::from_ymd(2022, 1, 1));
+        assert_eq!((end..start).count(), 0);
+
+        let jan = Month::from_year_month(2021, 1).unwrap();
+        let months = (jan..).take(13).collect::<Vec<_>>();
+        assert_eq!(months[12], Month::from_year_month(2022, 1).unwrap());
+
+        let first = Minutes::<30>::from_monotonic(10);
+        assert_eq!((first..first.succ_n(48)).count(), 48);
+    }
+}
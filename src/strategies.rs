@@ -0,0 +1,76 @@
+// proptest strategies for generating periods and ranges. Unless a range
+// is given, periods are generated between 1900 and 2100.
+use crate::{TimeRange, TimeResolution};
+use proptest::{collection, strategy::Strategy};
+
+pub fn any_period<P: TimeResolution>() -> impl Strategy<Value = P> {
+    let (start, end) = crate::arbitrary_bounds::<P>();
+    (start..end).prop_map(P::from_monotonic)
+}
+
+// eg: `any_period_in(month.days())` for any day in a month
+pub fn any_period_in<P: TimeResolution>(range: TimeRange<P>) -> impl Strategy<Value = P> {
+    let start = range.start().to_monotonic();
+    (start..=range.end().to_monotonic()).prop_map(P::from_monotonic)
+}
+
+// non-empty ranges of up to `max_len` periods
+pub fn any_time_range<P: TimeResolution>(max_len: u32) -> impl Strategy<Value = TimeRange<P>> {
+    (any_period::<P>(), 1..=max_len.max(1)).prop_map(|(start, len)| TimeRange::new(start, len))
+}
+
+// up to `max_count` sorted, non-empty ranges of up to `max_len` periods,
+// none of which overlap (although they may be adjacent)
+pub fn non_overlapping_ranges<P: TimeResolution>(
+    max_count: usize,
+    max_len: u32,
+) -> impl Strategy<Value = Vec<TimeRange<P>>> {
+    let max_len = max_len.max(1);
+    let pieces = collection::vec((0..=max_len, 1..=max_len), 0..=max_count);
+    (any_period::<P>(), pieces).prop_map(|(start, pieces)| {
+        let mut next = start;
+        pieces
+            .into_iter()
+            .map(|(gap, len)| {
+                let range = TimeRange::new(next.succ_n(gap), len);
+                next = range.end().succ();
+                range
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{any_period, any_period_in, any_time_range, non_overlapping_ranges};
+    use crate::{Date, DateResolution, HalfHour, Month, TimeRange};
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn test_any_period_in(day in any_period_in(Month::from_year_month(2021, 2).unwrap().days())) {
+            prop_assert_eq!(Month::from_date(day.start()), Month::from_year_month(2021, 2).unwrap());
+        }
+
+        #[test]
+        fn test_any_time_range(range in any_time_range::<HalfHour>(48)) {
+            prop_assert!(!range.is_empty());
+            prop_assert!(range.len() <= 48);
+        }
+
+        #[test]
+        fn test_non_overlapping_ranges(ranges in non_overlapping_ranges::<Date>(10, 5)) {
+            prop_assert!(ranges.len() <= 10);
+            for pair in ranges.windows(2) {
+                prop_assert!(pair[0].end() < pair[1].start());
+                prop_assert!(pair[0].intersect(pair[1]).is_none());
+            }
+        }
+
+        #[test]
+        fn test_any_period(month in any_period::<Month>()) {
+            prop_assert!(month.year_num() >= 1900 && month.year_num() < 2100);
+            prop_assert_eq!(TimeRange::new(month, 1).start(), month);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+// Checks of the properties every `TimeResolution` should have, for use in the tests of
+// crates implementing their own resolutions. Each check uses a spread of periods
+// between 1900 and 2100, and panics with a description of the first failure.
+use crate::TimeResolution;
+use std::{fmt, str};
+
+// how many periods are checked, spread evenly across the range
+const SAMPLES: i64 = 500;
+
+fn samples<P: TimeResolution>() -> impl Iterator<Item = P> {
+    let (min, max) = crate::arbitrary_bounds::<P>();
+    let step = ((max - min) / SAMPLES).max(1);
+    (0..=SAMPLES)
+        .map(move |n| min + n * step)
+        .take_while(move |idx| *idx <= max)
+        .map(P::from_monotonic)
+}
+
+pub fn assert_roundtrip_display_fromstr<P>()
+where
+    P: TimeResolution + str::FromStr,
+    P::Err: fmt::Debug,
+{
+    for period in samples::<P>() {
+        let s = period.to_string();
+        let parsed = s
+            .parse::<P>()
+            .unwrap_or_else(|e| panic!("Failed to parse {:?} from {:?}: {:?}", period, s, e));
+        assert_eq!(parsed, period, "Parsing {:?} gave a different period", s);
+    }
+}
+
+#[cfg(feature = "serde")]
+pub fn assert_roundtrip_serde<P: TimeResolution>() {
+    for period in samples::<P>() {
+        let json = serde_json::to_string(&period)
+            .unwrap_or_else(|e| panic!("Failed to serialize {:?}: {}", period, e));
+        let parsed = serde_json::from_str::<P>(&json)
+            .unwrap_or_else(|e| panic!("Failed to deserialize {:?} from {}: {}", period, json, e));
+        assert_eq!(
+            parsed, period,
+            "Deserializing {} gave a different period",
+            json
+        );
+    }
+}
+
+pub fn assert_monotonic_laws<P: TimeResolution>() {
+    for period in samples::<P>() {
+        let idx = period.to_monotonic();
+        assert_eq!(
+            P::from_monotonic(idx),
+            period,
+            "from_monotonic(to_monotonic()) for {:?}",
+            period
+        );
+        assert_eq!(
+            P::try_from_monotonic(idx).ok(),
+            Some(period),
+            "try_from_monotonic for {:?}",
+            period
+        );
+        assert_eq!(
+            period.succ().pred(),
+            period,
+            "succ then pred for {:?}",
+            period
+        );
+        assert_eq!(
+            period.pred().succ(),
+            period,
+            "pred then succ for {:?}",
+            period
+        );
+        assert_eq!(
+            period.succ_n(7).pred_n(7),
+            period,
+            "succ_n then pred_n for {:?}",
+            period
+        );
+        assert_eq!(
+            period.succ().to_monotonic(),
+            idx + 1,
+            "succ increments the index of {:?}",
+            period
+        );
+        assert_eq!(
+            period.between(period.succ_n(7)),
+            7,
+            "between succ_n for {:?}",
+            period
+        );
+        assert_eq!(
+            period.succ_n(7).between(period),
+            -7,
+            "between is antisymmetric for {:?}",
+            period
+        );
+        assert!(
+            period < period.succ(),
+            "ordering matches the index for {:?}",
+            period
+        );
+        let start = period.naive_date_time();
+        assert!(
+            start < period.succ().naive_date_time(),
+            "periods are non-empty for {:?}",
+            period
+        );
+        assert_eq!(
+            P::from_datetime(start),
+            period,
+            "from_datetime of the start of {:?}",
+            period
+        );
+        let last_second = period.succ().naive_date_time() - chrono::Duration::seconds(1);
+        assert_eq!(
+            P::from_datetime(last_second),
+            period,
+            "from_datetime of the end of {:?}",
+            period
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assert_monotonic_laws, assert_roundtrip_display_fromstr};
+    use crate::{Date, FiveMinute, HalfHour, Hour, Minute, Minutes, Month, Quarter, Year};
+
+    #[test]
+    fn test_laws() {
+        assert_monotonic_laws::<Minute>();
+        assert_monotonic_laws::<FiveMinute>();
+        assert_monotonic_laws::<Minutes<15>>();
+        assert_monotonic_laws::<HalfHour>();
+        assert_monotonic_laws::<Hour>();
+        assert_monotonic_laws::<Date>();
+        assert_monotonic_laws::<Month>();
+        assert_monotonic_laws::<Quarter>();
+        assert_monotonic_laws::<Year>();
+    }
+
+    #[test]
+    fn test_display_fromstr() {
+        assert_roundtrip_display_fromstr::<Minute>();
+        assert_roundtrip_display_fromstr::<FiveMinute>();
+        assert_roundtrip_display_fromstr::<HalfHour>();
+        assert_roundtrip_display_fromstr::<Hour>();
+        assert_roundtrip_display_fromstr::<Date>();
+        assert_roundtrip_display_fromstr::<Month>();
+        assert_roundtrip_display_fromstr::<Quarter>();
+        assert_roundtrip_display_fromstr::<Year>();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use super::assert_roundtrip_serde;
+        assert_roundtrip_serde::<Minute>();
+        assert_roundtrip_serde::<HalfHour>();
+        assert_roundtrip_serde::<Date>();
+        assert_roundtrip_serde::<Month>();
+        assert_roundtrip_serde::<Quarter>();
+        assert_roundtrip_serde::<Year>();
+    }
+}
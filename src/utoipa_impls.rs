@@ -0,0 +1,130 @@
+use crate::{Date, Minutes, Month, Quarter, TimeRange, TimeResolution, Year};
+use std::borrow::Cow;
+use utoipa::{
+    openapi::{
+        schema::{KnownFormat, ObjectBuilder, Schema, SchemaFormat, Type},
+        RefOr,
+    },
+    PartialSchema, ToSchema,
+};
+
+// Schemas match the serde representation, which is the `Display` string
+fn string_schema(format: Option<KnownFormat>, pattern: &str, example: String) -> RefOr<Schema> {
+    ObjectBuilder::new()
+        .schema_type(Type::String)
+        .format(format.map(SchemaFormat::KnownFormat))
+        .pattern(Some(pattern))
+        .examples([example])
+        .into()
+}
+
+fn example<P: TimeResolution>() -> P {
+    let date_time = chrono::NaiveDate::from_ymd_opt(2021, 7, 15)
+        .and_then(|d| d.and_hms_opt(10, 30, 0))
+        .expect("Valid date");
+    P::from_datetime(date_time)
+}
+
+macro_rules! impl_schema {
+    ($($ty:ident: $format:expr, $pattern:expr;)*) => {
+        $(
+            impl PartialSchema for $ty {
+                fn schema() -> RefOr<Schema> {
+                    string_schema($format, $pattern, example::<$ty>().to_string())
+                }
+            }
+
+            impl ToSchema for $ty {
+                fn name() -> Cow<'static, str> {
+                    Cow::Borrowed(stringify!($ty))
+                }
+            }
+        )*
+    };
+}
+
+impl_schema! {
+    Date: Some(KnownFormat::Date), r"^-?\d{4,}-\d{2}-\d{2}$";
+    Month: None, r"^[A-Z][a-z]{2}-\d{4}$";
+    Quarter: None, r"^Q[1-4]-\d{4}$";
+    Year: None, r"^-?\d+$";
+}
+
+const DATE_TIME_PATTERN: &str = r"\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}";
+
+impl<const N: u32> PartialSchema for Minutes<N> {
+    fn schema() -> RefOr<Schema> {
+        let pattern = if N == 1 {
+            format!("^{}$", DATE_TIME_PATTERN)
+        } else {
+            format!("^{0} - {0}$", DATE_TIME_PATTERN)
+        };
+        string_schema(None, &pattern, example::<Minutes<N>>().to_string())
+    }
+}
+
+impl<const N: u32> ToSchema for Minutes<N> {
+    fn name() -> Cow<'static, str> {
+        Cow::Owned(format!("Minutes_{}", N))
+    }
+}
+
+impl<P: TimeResolution + ToSchema> PartialSchema for TimeRange<P> {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .property("start", P::schema())
+            .required("start")
+            .property(
+                "len",
+                ObjectBuilder::new()
+                    .schema_type(Type::Integer)
+                    .format(Some(SchemaFormat::KnownFormat(KnownFormat::Int64)))
+                    .minimum(Some(0))
+                    .maximum(Some(u32::MAX)),
+            )
+            .required("len")
+            .into()
+    }
+}
+
+impl<P: TimeResolution + ToSchema> ToSchema for TimeRange<P> {
+    fn name() -> Cow<'static, str> {
+        Cow::Owned(format!("TimeRange_{}", <P as ToSchema>::name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Date, HalfHour, Quarter, TimeRange};
+    use utoipa::{
+        openapi::{schema::Schema, RefOr},
+        PartialSchema, ToSchema,
+    };
+
+    fn object(schema: RefOr<Schema>) -> utoipa::openapi::schema::Object {
+        match schema {
+            RefOr::T(Schema::Object(object)) => object,
+            _ => panic!("Expected an object schema"),
+        }
+    }
+
+    #[test]
+    fn test_examples_match_patterns() {
+        let quarter = object(Quarter::schema());
+        assert_eq!(quarter.examples, vec![serde_json::json!("Q3-2021")]);
+        let half_hour = object(HalfHour::schema());
+        assert_eq!(
+            half_hour.examples,
+            vec![serde_json::json!("2021-07-15 10:30:00 - 2021-07-15 11:00:00")]
+        );
+        assert_eq!(HalfHour::name(), "Minutes_30");
+    }
+
+    #[test]
+    fn test_time_range() {
+        assert_eq!(TimeRange::<Date>::name(), "TimeRange_Date");
+        let range = object(TimeRange::<Date>::schema());
+        assert_eq!(range.required, vec!["start", "len"]);
+        assert!(range.properties.contains_key("start"));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{TimeRange, TimeResolution};
+use std::{collections, convert::TryFrom};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletenessReport<P: TimeResolution> {
+    // the gaps in the expected range, in order
+    pub missing: Vec<TimeRange<P>>,
+    // periods seen more than once, along with how many times they were seen
+    pub duplicates: collections::BTreeMap<P, usize>,
+    // periods outside of the expected range, in the order seen
+    pub out_of_range: Vec<P>,
+}
+
+impl<P: TimeResolution> CompletenessReport<P> {
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty() && self.duplicates.is_empty() && self.out_of_range.is_empty()
+    }
+    pub fn num_missing(&self) -> usize {
+        self.missing.iter().map(|r| r.len()).sum()
+    }
+}
+
+// Checks that `keys` has exactly one of each period in `range`, and nothing else
+pub fn check_complete<P: TimeResolution>(
+    range: &TimeRange<P>,
+    keys: impl Iterator<Item = P>,
+) -> CompletenessReport<P> {
+    let mut counts = collections::BTreeMap::<P, usize>::new();
+    let mut out_of_range = Vec::new();
+    for key in keys {
+        if range.index_of(key).is_some() {
+            *counts.entry(key).or_default() += 1;
+        } else {
+            out_of_range.push(key);
+        }
+    }
+    let mut missing = Vec::new();
+    if !range.is_empty() {
+        let mut gap_start = None;
+        for period in range.iter() {
+            match (counts.contains_key(&period), gap_start) {
+                (false, None) => gap_start = Some(period),
+                (true, Some(start)) => {
+                    missing.push(TimeRange::from_start_end(start, period.pred()).expect("Start is before end"));
+                    gap_start = None;
+                }
+                _ => (),
+            }
+        }
+        if let Some(start) = gap_start {
+            missing.push(TimeRange::from_start_end(start, range.end()).expect("Start is before end"));
+        }
+    }
+    CompletenessReport {
+        missing,
+        duplicates: counts.into_iter().filter(|(_, n)| *n > 1).collect(),
+        out_of_range,
+    }
+}
+
+// The runs of missing periods between consecutive items of a sorted iterator.
+// Repeated or out of order items are skipped over.
+pub fn gaps_in<P: TimeResolution, I: Iterator<Item = P>>(iter: I) -> Gaps<P, I> {
+    Gaps {
+        iter,
+        previous: None,
+    }
+}
+
+pub struct Gaps<P: TimeResolution, I: Iterator<Item = P>> {
+    iter: I,
+    previous: Option<P>,
+}
+
+impl<P: TimeResolution, I: Iterator<Item = P>> Iterator for Gaps<P, I> {
+    type Item = TimeRange<P>;
+    fn next(&mut self) -> Option<Self::Item> {
+        for period in self.iter.by_ref() {
+            let previous = match self.previous {
+                Some(previous) if period <= previous => continue,
+                Some(previous) => previous,
+                None => {
+                    self.previous = Some(period);
+                    continue;
+                }
+            };
+            self.previous = Some(period);
+            let missing = previous.between(period) - 1;
+            if missing > 0 {
+                let len = u32::try_from(missing).expect("Gap fits in a TimeRange");
+                return Some(TimeRange::new(previous.succ(), len));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_complete, gaps_in};
+    use crate::{Date, HalfHour, TimeRange, TimeResolution};
+
+    #[test]
+    fn test_check_complete() {
+        let start = "2021-07-15 00:00".parse::<HalfHour>().unwrap();
+        let range = TimeRange::new(start, 48);
+        let report = check_complete(&range, range.iter());
+        assert!(report.is_complete());
+
+        let keys = range
+            .iter()
+            .filter(|p| ![0, 10, 11, 47].contains(&start.between(*p)))
+            .chain(vec![start.succ_n(5), start.succ_n(5), start.pred(), start.succ_n(48)]);
+        let report = check_complete(&range, keys);
+        assert!(!report.is_complete());
+        assert_eq!(
+            report.missing,
+            vec![
+                TimeRange::new(start, 1),
+                TimeRange::new(start.succ_n(10), 2),
+                TimeRange::new(start.succ_n(47), 1),
+            ]
+        );
+        assert_eq!(report.num_missing(), 4);
+        assert_eq!(report.duplicates.get(&start.succ_n(5)), Some(&3));
+        assert_eq!(report.duplicates.len(), 1);
+        assert_eq!(report.out_of_range, vec![start.pred(), start.succ_n(48)]);
+    }
+
+    #[test]
+    fn test_gaps_in() {
+        let d = Date::from_monotonic;
+        let items = vec![d(1), d(2), d(5), d(5), d(3), d(6), d(10)];
+        let gaps = gaps_in(items.into_iter()).collect::<Vec<_>>();
+        assert_eq!(gaps, vec![TimeRange::new(d(3), 2), TimeRange::new(d(7), 3)]);
+        assert_eq!(gaps_in(std::iter::once(d(1))).count(), 0);
+        assert_eq!(gaps_in(std::iter::empty::<Date>()).count(), 0);
+    }
+
+    #[test]
+    fn test_check_complete_empty() {
+        let range = TimeRange::new(Date::from_ymd(2021, 7, 15).unwrap(), 3);
+        let report = check_complete(&range, std::iter::empty());
+        assert_eq!(report.missing, vec![range]);
+    }
+}
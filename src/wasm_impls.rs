@@ -0,0 +1,62 @@
+use crate::TimeResolution;
+use wasm_bindgen::JsValue;
+
+// Conversions to and from `js_sys::Date`, which is a UTC timestamp in
+// milliseconds. Periods are created from the period containing the
+// timestamp, and converted to the timestamp of their start.
+pub trait JsDateResolution: TimeResolution {
+    fn try_from_js_date(date: &js_sys::Date) -> crate::Result<Self> {
+        from_millis(date.get_time())
+    }
+    fn to_js_date(&self) -> js_sys::Date {
+        js_sys::Date::new(&JsValue::from_f64(to_millis(self)))
+    }
+    // the current period, using the browser's clock
+    fn now() -> Self {
+        from_millis(js_sys::Date::now()).expect("The current time is representable")
+    }
+}
+
+impl<P: TimeResolution> JsDateResolution for P {}
+
+fn to_millis<P: TimeResolution>(period: &P) -> f64 {
+    period.naive_date_time().timestamp_millis() as f64
+}
+
+fn from_millis<P: TimeResolution>(millis: f64) -> crate::Result<P> {
+    let err = || crate::Error::ParseCustom {
+        ty_name: "js_sys::Date",
+        input: millis.to_string(),
+    };
+    if !millis.is_finite() {
+        return Err(err());
+    }
+    let secs = (millis / 1000.0).floor();
+    let nanos = ((millis - secs * 1000.0) * 1_000_000.0) as u32;
+    let dt = chrono::NaiveDateTime::from_timestamp_opt(secs as i64, nanos).ok_or_else(err)?;
+    Ok(P::from_datetime(dt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_millis, to_millis};
+    use crate::{Date, HalfHour, Month};
+
+    #[test]
+    fn test_millis() {
+        let period = "2021-07-15 10:30".parse::<HalfHour>().unwrap();
+        assert_eq!(to_millis(&period), 1_626_345_000_000.0);
+        assert_eq!(from_millis::<HalfHour>(1_626_345_000_000.0).unwrap(), period);
+        assert_eq!(from_millis::<HalfHour>(1_626_346_799_999.5).unwrap(), period);
+        assert_eq!(
+            from_millis::<Month>(1_626_345_000_000.0).unwrap(),
+            Month::from_year_month(2021, 7).unwrap()
+        );
+        assert_eq!(
+            from_millis::<Date>(-1.0).unwrap(),
+            Date::from_ymd(1969, 12, 31).unwrap()
+        );
+        assert!(from_millis::<Date>(f64::NAN).is_err());
+        assert!(from_millis::<Date>(8.64e18).is_err());
+    }
+}
@@ -1,57 +1,135 @@
-use crate::{month, year, DateResolution};
+use crate::{date, month, quarter, year, DateResolution, TimeRange};
 use chrono::Datelike;
-use serde::{
-    de,
-    ser::{self, SerializeStruct},
-};
-use std::{str, convert::TryFrom, fmt};
+#[cfg(feature = "serde")]
+use serde::de;
+use std::{borrow, cmp, convert::{self, TryFrom}, fmt, str};
 
 #[derive(Clone, Copy, Debug, Eq, PartialOrd, PartialEq, Ord)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", archive(check_bytes, compare(PartialEq, PartialOrd)))]
+#[cfg_attr(feature = "rkyv", archive_attr(derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)))]
 pub struct Year(i64);
 
 impl crate::DateResolution for Year {
     fn start(&self) -> chrono::NaiveDate {
-        chrono::NaiveDate::from_ymd(self.year_num(), 1, 1)
+        chrono::NaiveDate::from_ymd_opt(self.year_num(), 1, 1).expect("Not pre/post historic")
     }
 }
 
 impl crate::TimeResolution for Year {
+    fn name(&self) -> borrow::Cow<'static, str> {
+        borrow::Cow::Borrowed("Year")
+    }
     fn between(&self, other: Self) -> i64 {
-        i64::from(other.0 - self.0)
+        other.0 - self.0
     }
     fn succ_n(&self, n: u32) -> Year {
-        Year(self.0 + i64::from(n))
+        Year::succ_n(self, n)
     }
     fn pred_n(&self, n: u32) -> Year {
-        Year(self.0 - i64::from(n))
+        Year::pred_n(self, n)
     }
     fn naive_date_time(&self) -> chrono::NaiveDateTime {
-        self.start().and_hms(0, 0, 0)
+        self.start().and_hms_opt(0, 0, 0).expect("Midnight is always valid")
+    }
+    fn from_datetime(dt: chrono::NaiveDateTime) -> Self {
+        Year::from_date(dt.date())
     }
     fn from_monotonic(idx: i64) -> Self {
-        Year(idx)
+        Year::from_monotonic(idx)
+    }
+    fn try_from_monotonic(idx: i64) -> crate::Result<Self> {
+        crate::check_monotonic(
+            "Year",
+            idx,
+            Year::from_date(chrono::NaiveDate::MIN),
+            Year::from_date(chrono::NaiveDate::MAX),
+        )
     }
     fn to_monotonic(&self) -> i64 {
-        self.0
+        Year::to_monotonic(self)
     }
 }
 
 impl Year {
+    // the start of the period with monotonic index 0
+    pub fn monotonic_epoch() -> chrono::NaiveDateTime {
+        Year(0).start().and_hms_opt(0, 0, 0).expect("Midnight is always valid")
+    }
+    pub const fn from_monotonic(idx: i64) -> Year {
+        Year(idx)
+    }
+    pub const fn to_monotonic(&self) -> i64 {
+        self.0
+    }
+    pub const fn succ_n(&self, n: u32) -> Year {
+        Year(self.0 + n as i64)
+    }
+    pub const fn pred_n(&self, n: u32) -> Year {
+        Year(self.0 - n as i64)
+    }
     pub fn first_month(&self) -> month::Month {
-        todo!()
+        month::Month::from_date(self.start())
     }
-    pub fn first_quarter(&self) -> month::Month {
-        todo!()
+    pub fn last_month(&self) -> month::Month {
+        month::Month::from_date(self.end())
+    }
+    pub fn first_quarter(&self) -> quarter::Quarter {
+        quarter::Quarter::from_date(self.start())
+    }
+    pub fn last_quarter(&self) -> quarter::Quarter {
+        quarter::Quarter::from_date(self.end())
+    }
+    // panics if the month is not within 1-12
+    pub fn with_month(&self, month: u32) -> month::Month {
+        month::Month::from_year_month(self.year_num(), month).expect("Month must be within 1-12")
+    }
+    pub fn is_leap(&self) -> bool {
+        self.num_days() == 366
+    }
+    pub fn num_days(&self) -> u32 {
+        self.end().ordinal()
+    }
+    pub fn day(&self, ordinal: u32) -> Option<date::Date> {
+        chrono::NaiveDate::from_yo_opt(self.year_num(), ordinal).map(date::Date::from)
     }
     pub fn year(&self) -> year::Year {
         todo!()
     }
+    // as `day`, but erroring when the ordinal is outside of the year
+    pub fn day_from_ordinal(&self, ordinal: u32) -> crate::Result<date::Date> {
+        self.day(ordinal).ok_or(crate::Error::InvalidComponent {
+            component: "day of the year",
+            value: ordinal,
+        })
+    }
     pub fn year_num(&self) -> i32 {
         i32::try_from(self.0).expect("Not pre/post historic")
     }
     pub fn from_date(d: chrono::NaiveDate) -> Self {
         Year(i64::from(d.year()))
     }
+    pub const fn from_num(year: i32) -> Self {
+        Year(year as i64)
+    }
+    pub fn months(&self) -> TimeRange<month::Month> {
+        TimeRange::new(self.first_month(), 12)
+    }
+    pub fn quarters(&self) -> TimeRange<quarter::Quarter> {
+        TimeRange::new(self.first_quarter(), 4)
+    }
+}
+
+impl PartialEq<chrono::NaiveDate> for Year {
+    fn eq(&self, other: &chrono::NaiveDate) -> bool {
+        crate::cmp_date(self, other) == cmp::Ordering::Equal
+    }
+}
+
+impl PartialOrd<chrono::NaiveDate> for Year {
+    fn partial_cmp(&self, other: &chrono::NaiveDate) -> Option<cmp::Ordering> {
+        Some(crate::cmp_date(self, other))
+    }
 }
 
 impl fmt::Display for Year {
@@ -67,7 +145,22 @@ impl str::FromStr for Year {
     }
 }
 
+impl<'a> convert::TryFrom<&'a str> for Year {
+    type Error = crate::Error;
+    fn try_from(s: &'a str) -> crate::Result<Year> {
+        s.parse()
+    }
+}
+
+impl convert::TryFrom<i64> for Year {
+    type Error = crate::Error;
+    fn try_from(idx: i64) -> crate::Result<Year> {
+        <Year as crate::TimeResolution>::try_from_monotonic(idx)
+    }
+}
+
 
+#[cfg(feature = "serde")]
 impl<'de> de::Deserialize<'de> for Year 
 {
     fn deserialize<D>(
@@ -76,11 +169,12 @@ impl<'de> de::Deserialize<'de> for Year
     where
         D: de::Deserializer<'de>,
     {
-        let y = i64::deserialize(deserializer)?;
-        Ok(Year(y))
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
     }
 }
 
+#[cfg(feature = "serde")]
 impl serde::Serialize for Year {
     fn serialize<S>(
         &self,
@@ -94,3 +188,45 @@ impl serde::Serialize for Year {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::Year;
+    use crate::{Date, Month, Quarter, TimeResolution};
+
+    #[test]
+    fn test_helpers() {
+        let y2020 = Year::from_date(chrono::NaiveDate::from_ymd_opt(2020, 6, 1).unwrap());
+        let y2021 = y2020.succ();
+        assert!(y2020.is_leap());
+        assert!(!y2021.is_leap());
+        assert!(Year(2000).is_leap());
+        assert!(!Year(1900).is_leap());
+        assert_eq!(y2020.num_days(), 366);
+        assert_eq!(y2021.num_days(), 365);
+        assert_eq!(y2021.day(32), Some(Date::from(chrono::NaiveDate::from_ymd_opt(2021, 2, 1).unwrap())));
+        assert_eq!(y2021.day(366), None);
+        assert_eq!(y2020.day(366), Some(Date::from(chrono::NaiveDate::from_ymd_opt(2020, 12, 31).unwrap())));
+        assert_eq!(y2021.with_month(7), Month::from_year_month(2021, 7).unwrap());
+        assert_eq!(y2021.first_month(), Month::from_year_month(2021, 1).unwrap());
+        assert_eq!(y2021.last_month(), Month::from_year_month(2021, 12).unwrap());
+        assert_eq!(y2021.first_quarter(), Quarter::from_date(chrono::NaiveDate::from_ymd_opt(2021, 1, 1).unwrap()));
+        assert_eq!(y2021.last_quarter(), Quarter::from_date(chrono::NaiveDate::from_ymd_opt(2021, 12, 31).unwrap()));
+        assert_eq!(Year::from_num(2021), y2021);
+        assert_eq!(y2020.day_from_ordinal(366).unwrap(), Date::from(chrono::NaiveDate::from_ymd_opt(2020, 12, 31).unwrap()));
+        assert!(matches!(
+            y2021.day_from_ordinal(366),
+            Err(crate::Error::InvalidComponent { value: 366, .. })
+        ));
+        assert!(y2021.day_from_ordinal(0).is_err());
+        assert_eq!(Year::from_num(-5).year_num(), -5);
+    }
+
+    #[test]
+    fn test_const() {
+        const FIRST: Year = Year::from_monotonic(2020);
+        const LAST: Year = FIRST.succ_n(2);
+        const LOOKUP: [Year; 2] = [FIRST, LAST.pred_n(1)];
+        assert_eq!(LAST.to_monotonic(), 2022);
+        assert_eq!(LOOKUP[1], Year::from_date(chrono::NaiveDate::from_ymd_opt(2021, 1, 1).unwrap()));
+    }
+}